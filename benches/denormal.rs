@@ -0,0 +1,66 @@
+//! Compares the cost of a decaying feedback tail with and without
+//! flushing subnormals, run with `cargo bench --bench denormal`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use photon::core::dsp::{denormal::flush, Comb, CombKind};
+use photon::core::effect::{Effect, Reverb, ReverbParameters};
+
+/// The number of samples processed per measurement, about 30 seconds
+/// at 44.1 kHz, which is long enough for the tails to go subnormal.
+const SAMPLES: usize = 44100 * 30;
+
+/// Times `f` over a tail excited by a single impulse.
+fn time(mut f: impl FnMut(f32) -> f32) -> Duration {
+    let start = Instant::now();
+    for index in 0..SAMPLES {
+        black_box(f(if index == 0 { 1.0 } else { 0.0 }));
+    }
+    start.elapsed()
+}
+
+/// A damped feedback comb, matching [`Comb`] but optionally skipping
+/// the flushing of its feedback path.
+fn comb(flushing: bool) -> impl FnMut(f32) -> f32 {
+    let mut buffer = vec![0.0_f32; 1116];
+    let (mut index, mut state) = (0, 0.0_f32);
+    move |x| {
+        let delayed = buffer[index];
+        state = delayed * 0.6 + state * 0.4;
+        let mut y = x + state * 0.9;
+        if flushing {
+            state = flush(state);
+            y = flush(y);
+        }
+        buffer[index] = y;
+        index = (index + 1) % buffer.len();
+        y
+    }
+}
+
+fn main() {
+    let unflushed = time(comb(false));
+    let flushed = time(comb(true));
+    println!("comb tail without flushing: {:?}", unflushed);
+    println!("comb tail with flushing:    {:?}", flushed);
+
+    let mut primitive = Comb::new(CombKind::Feedback, 1116);
+    primitive.set_feedback(0.9);
+    primitive.set_damping(0.4);
+    println!(
+        "dsp::Comb tail:             {:?}",
+        time(|x| primitive.process(x))
+    );
+
+    let mut reverb = Reverb::new(44100.0);
+    reverb.initialize(ReverbParameters::new(0.5, 0.5, 1.0, 1.0));
+    let mut frame = [0.0; 2];
+    println!(
+        "Reverb tail:                {:?}",
+        time(|x| {
+            frame = [x, x];
+            reverb.process(0, &mut frame);
+            frame[0]
+        })
+    );
+}
@@ -0,0 +1,65 @@
+//! Measures the throughput of the trance gate, run with `cargo bench
+//! --bench trance_gate` and again with `--features simd` to compare.
+//!
+//! Also compares computing the gate factors one frame at a time with
+//! [`TranceGateParameters::gate_factor`] against a whole block at once
+//! with [`TranceGateParameters::gate_factors`].
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use photon::core::effect::{TranceGate, TranceGateParameters, UNKNOWN_POSITION};
+
+/// The number of frames per call to `process`.
+const BLOCK_FRAMES: usize = 4096;
+
+/// The number of calls to `process` per measurement.
+const ITERATIONS: usize = 2000;
+
+/// Times `f` over every block, passing the position of its start.
+fn time(mut f: impl FnMut(usize)) -> Duration {
+    let start = Instant::now();
+    for iteration in 0..ITERATIONS {
+        f(iteration * BLOCK_FRAMES);
+    }
+    start.elapsed()
+}
+
+fn report(label: &str, elapsed: Duration) {
+    println!(
+        "{}: {:?} total, {:.2} ns per frame",
+        label,
+        elapsed,
+        elapsed.as_nanos() as f64 / (BLOCK_FRAMES * ITERATIONS) as f64
+    );
+}
+
+fn main() {
+    let parameters = TranceGateParameters::new(0.125, 0.8, 44100.0);
+    let mut gate = TranceGate::new();
+    gate.initialize(parameters);
+    let mut buffer: Vec<f32> = (0..BLOCK_FRAMES * 2)
+        .map(|index| (index as f32 * 0.01).sin())
+        .collect();
+    let elapsed = time(|_| gate.process(UNKNOWN_POSITION, black_box(&mut buffer)));
+    let path = if cfg!(feature = "simd") {
+        "simd"
+    } else {
+        "scalar"
+    };
+    report(&format!("{} path", path), elapsed);
+
+    let length = parameters.gate_length;
+    let mut factors = vec![0.0; BLOCK_FRAMES];
+    let branching = time(|position| {
+        for (index, factor) in factors.iter_mut().enumerate() {
+            *factor = parameters.gate_factor((position + index) % length);
+        }
+        black_box(&mut factors);
+    });
+    report("branching gate factors", branching);
+    let branchless = time(|position| {
+        parameters.gate_factors(position % length, &mut factors);
+        black_box(&mut factors);
+    });
+    report("branchless gate factors", branchless);
+}
@@ -1,4 +1,19 @@
 //! Core functionality and utilities.
+pub mod analysis;
+#[cfg(feature = "std")]
 pub mod audio;
+pub mod chain;
+pub mod dsp;
 pub mod effect;
+#[cfg(feature = "std")]
 pub mod engine;
+pub mod io;
+pub mod math;
+pub mod midi;
+#[cfg(feature = "serde")]
+pub mod preset;
+pub mod sample;
+pub mod tempo;
+#[cfg(all(feature = "std", any(test, feature = "testing")))]
+pub mod testing;
+pub mod transport;
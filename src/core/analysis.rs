@@ -0,0 +1,12 @@
+//! Observes audio without modifying it, e.g. for metering or display.
+pub mod loudness;
+pub mod meter;
+pub mod scope;
+pub mod spectrum;
+pub mod true_peak;
+
+pub use loudness::LoudnessMeter;
+pub use meter::Meter;
+pub use scope::Scope;
+pub use spectrum::Spectrum;
+pub use true_peak::TruePeak;
@@ -0,0 +1,285 @@
+//! Integrated loudness following [ITU-R
+//! BS.1770](https://www.itu.int/rec/R-REC-BS.1770).
+//!
+//! # Overview
+//!
+//! Each channel is K-weighted by a high shelf modelling the acoustic
+//! effect of the head, followed by a high-pass. The weighted power is
+//! measured over 400ms blocks overlapping by 75%, and the integrated
+//! loudness is the average over blocks that pass both the absolute gate
+//! at -70 LUFS and a relative gate 10 LU below the absolutely-gated
+//! loudness. Rather than keeping every block, blocks are tallied into a
+//! histogram of 0.1 LU bins, such that memory stays fixed however long
+//! the measurement runs, and the relative gate is resolved to the bin.
+use alloc::{vec, vec::Vec};
+use core::f64::consts::PI;
+
+use crate::core::dsp::{Biquad, BiquadCoefficients};
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The loudness below which blocks are always ignored, in LUFS.
+pub const ABSOLUTE_GATE: f64 = -70.0;
+
+/// The offset from the absolutely-gated loudness below which blocks
+/// are ignored, in LU.
+pub const RELATIVE_GATE: f64 = -10.0;
+
+/// The reported loudness when nothing has been measured, in LUFS.
+pub const SILENCE_LUFS: f64 = f64::NEG_INFINITY;
+
+/// The number of 100ms steps in a momentary block.
+const MOMENTARY_STEPS: usize = 4;
+
+/// The number of 100ms steps in a short-term block.
+const SHORT_TERM_STEPS: usize = 30;
+
+/// The width of a bin of the gating histogram, in LU.
+const HISTOGRAM_RESOLUTION: f64 = 0.1;
+
+/// The number of bins of the gating histogram, spanning from the
+/// absolute gate up to +30 LUFS, above which blocks share the last bin.
+const HISTOGRAM_BINS: usize = 1000;
+
+/// Computes the first stage of the K-weighting filter.
+pub fn k_weighting_shelf(sample_rate: f64) -> BiquadCoefficients {
+    let frequency = 1681.974450955533;
+    let gain_db = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * frequency / sample_rate).tan();
+    let vh = 10.0_f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    BiquadCoefficients {
+        b0: (vh + vb * k / q + k * k) / a0,
+        b1: 2.0 * (k * k - vh) / a0,
+        b2: (vh - vb * k / q + k * k) / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+    }
+}
+
+/// Computes the second stage of the K-weighting filter.
+pub fn k_weighting_highpass(sample_rate: f64) -> BiquadCoefficients {
+    let frequency = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * frequency / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    BiquadCoefficients {
+        b0: 1.0,
+        b1: -2.0,
+        b2: 1.0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+    }
+}
+
+/// Converts a mean square power into loudness.
+fn power_to_lufs(power: f64) -> f64 {
+    if power <= 0.0 {
+        SILENCE_LUFS
+    } else {
+        -0.691 + 10.0 * power.log10()
+    }
+}
+
+/// The bin of the gating histogram holding a `loudness` above the
+/// absolute gate.
+fn histogram_bin(loudness: f64) -> usize {
+    (((loudness - ABSOLUTE_GATE) / HISTOGRAM_RESOLUTION) as usize).min(HISTOGRAM_BINS - 1)
+}
+
+/// Measures momentary, short-term, and integrated loudness.
+#[derive(Debug, Clone)]
+pub struct LoudnessMeter {
+    /// The sample rate of the measured audio.
+    sample_rate: f64,
+    /// The K-weighting filters of each channel.
+    filters: Vec<[Biquad; 2]>,
+    /// The number of frames in a 100ms step.
+    step_len: usize,
+    /// The weighted energy summed across channels in the current step.
+    step_energy: f64,
+    /// The number of frames measured in the current step.
+    step_frames: usize,
+    /// The energy of the most recent steps, oldest first.
+    steps: [f64; SHORT_TERM_STEPS],
+    /// The number of steps measured so far, saturating.
+    steps_measured: usize,
+    /// The summed power and the number of the gating blocks above the
+    /// absolute gate measured so far, by bin of loudness.
+    histogram: Vec<(f64, usize)>,
+}
+
+impl LoudnessMeter {
+    /// Creates a new [`LoudnessMeter`].
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            filters: vec![],
+            step_len: ((sample_rate * 0.1) as usize).max(1),
+            step_energy: 0.0,
+            step_frames: 0,
+            steps: [0.0; SHORT_TERM_STEPS],
+            steps_measured: 0,
+            histogram: vec![(0.0, 0); HISTOGRAM_BINS],
+        }
+    }
+
+    /// Clears the measurements.
+    pub fn reset(&mut self) {
+        for filter in self.filters.iter_mut().flatten() {
+            filter.reset();
+        }
+        self.step_energy = 0.0;
+        self.step_frames = 0;
+        self.steps = [0.0; SHORT_TERM_STEPS];
+        self.steps_measured = 0;
+        self.histogram.fill((0.0, 0));
+    }
+
+    /// Measures a `buffer` of samples with interleaved `channels`.
+    ///
+    /// Changing the number of channels between calls resets the meter.
+    pub fn process(&mut self, buffer: &[f32], channels: usize) {
+        if channels == 0 {
+            return;
+        }
+        if self.filters.len() != channels {
+            let filter = [
+                Biquad::new(k_weighting_shelf(self.sample_rate)),
+                Biquad::new(k_weighting_highpass(self.sample_rate)),
+            ];
+            self.filters = vec![filter; channels];
+            self.reset();
+        }
+        for frame in buffer.chunks_exact(channels) {
+            for (sample, [shelf, highpass]) in frame.iter().zip(self.filters.iter_mut()) {
+                let weighted = highpass.process(shelf.process(*sample)) as f64;
+                self.step_energy += weighted * weighted;
+            }
+            self.step_frames += 1;
+            if self.step_frames == self.step_len {
+                self.finish_step();
+            }
+        }
+    }
+
+    /// Pushes the current step, recording a gating block once enough
+    /// steps have been measured.
+    fn finish_step(&mut self) {
+        self.steps.rotate_left(1);
+        self.steps[SHORT_TERM_STEPS - 1] = self.step_energy;
+        self.steps_measured = (self.steps_measured + 1).min(SHORT_TERM_STEPS);
+        self.step_energy = 0.0;
+        self.step_frames = 0;
+        if self.steps_measured >= MOMENTARY_STEPS {
+            let power = self.power(MOMENTARY_STEPS);
+            let loudness = power_to_lufs(power);
+            if loudness > ABSOLUTE_GATE {
+                let (sum, count) = &mut self.histogram[histogram_bin(loudness)];
+                *sum += power;
+                *count += 1;
+            }
+        }
+    }
+
+    /// The mean power over the most recent `steps`, or zero if fewer
+    /// have been measured.
+    fn power(&self, steps: usize) -> f64 {
+        if self.steps_measured < steps {
+            return 0.0;
+        }
+        let energy: f64 = self.steps[SHORT_TERM_STEPS - steps..].iter().sum();
+        energy / (steps * self.step_len) as f64
+    }
+
+    /// The loudness over the last 400ms, in LUFS.
+    pub fn momentary_lufs(&self) -> f64 {
+        power_to_lufs(self.power(MOMENTARY_STEPS))
+    }
+
+    /// The loudness over the last 3s, in LUFS.
+    pub fn short_term_lufs(&self) -> f64 {
+        power_to_lufs(self.power(SHORT_TERM_STEPS))
+    }
+
+    /// The gated loudness over everything measured so far, in LUFS.
+    pub fn integrated_lufs(&self) -> f64 {
+        let gated_mean = |first_bin: usize| {
+            let (sum, count) = self.histogram[first_bin..]
+                .iter()
+                .fold((0.0, 0), |(sum, count), (bin_sum, bin_count)| {
+                    (sum + bin_sum, count + bin_count)
+                });
+            if count == 0 {
+                0.0
+            } else {
+                sum / count as f64
+            }
+        };
+        let absolute = gated_mean(0);
+        if absolute <= 0.0 {
+            return SILENCE_LUFS;
+        }
+        let relative = power_to_lufs(absolute) + RELATIVE_GATE;
+        if relative <= ABSOLUTE_GATE {
+            return power_to_lufs(absolute);
+        }
+        power_to_lufs(gated_mean(histogram_bin(relative)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::{LoudnessMeter, SILENCE_LUFS};
+    use crate::core::testing::assert_no_alloc;
+
+    fn sine(amplitude: f32, seconds: usize, sample_rate: usize) -> Vec<f32> {
+        (0..seconds * sample_rate)
+            .flat_map(|index| {
+                let x = amplitude * (TAU * 1000.0 * index as f32 / sample_rate as f32).sin();
+                [x, x]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reference_tone() {
+        for sample_rate in [44100, 48000] {
+            let mut meter = LoudnessMeter::new(sample_rate as f64);
+            meter.process(&sine(10.0_f32.powf(-23.0 / 20.0), 5, sample_rate), 2);
+            assert!((meter.integrated_lufs() + 23.0).abs() < 0.1);
+            assert!((meter.momentary_lufs() + 23.0).abs() < 0.1);
+            assert!((meter.short_term_lufs() + 23.0).abs() < 0.1);
+        }
+    }
+
+    #[test]
+    fn quiet_passages_are_gated() {
+        let mut meter = LoudnessMeter::new(48000.0);
+        meter.process(&sine(10.0_f32.powf(-23.0 / 20.0), 5, 48000), 2);
+        meter.process(&sine(10.0_f32.powf(-50.0 / 20.0), 5, 48000), 2);
+        meter.process(&vec![0.0; 2 * 48000 * 5], 2);
+        // Blocks straddling the transition still pass the relative gate.
+        assert!((meter.integrated_lufs() + 23.0).abs() < 0.2);
+
+        meter.reset();
+        assert_eq!(meter.integrated_lufs(), SILENCE_LUFS);
+    }
+
+    #[test]
+    fn long_sessions_do_not_allocate() {
+        let mut meter = LoudnessMeter::new(48000.0);
+        let tone = sine(10.0_f32.powf(-23.0 / 20.0), 5, 48000);
+        meter.process(&tone, 2);
+        assert_no_alloc(|| {
+            for _ in 0..12 {
+                meter.process(&tone, 2);
+            }
+        });
+        assert!((meter.integrated_lufs() + 23.0).abs() < 0.1);
+    }
+}
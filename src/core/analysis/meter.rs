@@ -0,0 +1,128 @@
+//! Peak and RMS metering for interleaved stereo audio.
+use alloc::{vec, vec::Vec};
+
+use crate::core::dsp::envelope::time_coefficient;
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The number of channels measured by the [`Meter`].
+pub const CHANNELS: usize = 2;
+
+/// Reports the peak and RMS levels of the audio passing through it.
+#[derive(Debug, Clone)]
+pub struct Meter {
+    /// The squared samples of each frame in the RMS window.
+    squares: Vec<[f32; CHANNELS]>,
+    /// The running sum of `squares` for each channel.
+    sums: [f64; CHANNELS],
+    /// The frame in the window that is written next.
+    index: usize,
+    /// The held peak of each channel.
+    peaks: [f32; CHANNELS],
+    /// The per-sample multiplier applied to the held peaks.
+    decay: f32,
+}
+
+impl Meter {
+    /// Creates a new [`Meter`] averaging the RMS over `window_ms`,
+    /// with held peaks falling to 37% of their value over `decay_ms`.
+    pub fn new(window_ms: f32, decay_ms: f32, sample_rate: f64) -> Self {
+        let window = ((window_ms as f64 * 0.001 * sample_rate) as usize).max(1);
+        Self {
+            squares: vec![[0.0; CHANNELS]; window],
+            sums: [0.0; CHANNELS],
+            index: 0,
+            peaks: [0.0; CHANNELS],
+            decay: time_coefficient(decay_ms, sample_rate),
+        }
+    }
+
+    /// Measures a `buffer` of interleaved stereo samples.
+    pub fn process(&mut self, buffer: &[f32]) {
+        for frame in buffer.chunks_exact(CHANNELS) {
+            let squares = &mut self.squares[self.index];
+            for channel in 0..CHANNELS {
+                let sample = frame[channel];
+                let square = sample * sample;
+                self.sums[channel] += (square - squares[channel]) as f64;
+                squares[channel] = square;
+                self.peaks[channel] = sample.abs().max(self.peaks[channel] * self.decay);
+            }
+            self.index = (self.index + 1) % self.squares.len();
+        }
+    }
+
+    /// Clears the measurements.
+    pub fn reset(&mut self) {
+        self.squares.fill([0.0; CHANNELS]);
+        self.sums = [0.0; CHANNELS];
+        self.index = 0;
+        self.peaks = [0.0; CHANNELS];
+    }
+
+    /// The held peak of a `channel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `channel` is out of bounds.
+    pub fn peak_channel(&self, channel: usize) -> f32 {
+        self.peaks[channel]
+    }
+
+    /// The RMS of a `channel` over the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `channel` is out of bounds.
+    pub fn rms_channel(&self, channel: usize) -> f32 {
+        (self.sums[channel].max(0.0) / self.squares.len() as f64).sqrt() as f32
+    }
+
+    /// The highest held peak across channels.
+    pub fn peak(&self) -> f32 {
+        self.peaks.iter().fold(0.0, |a, &b| a.max(b))
+    }
+
+    /// The RMS across channels over the window.
+    pub fn rms(&self) -> f32 {
+        let sum: f64 = self.sums.iter().map(|sum| sum.max(0.0)).sum();
+        (sum / (self.squares.len() * CHANNELS) as f64).sqrt() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{SQRT_2, TAU};
+
+    use super::Meter;
+
+    #[test]
+    fn sine_levels() {
+        let amplitude = 0.8;
+        let mut meter = Meter::new(100.0, 500.0, 44100.0);
+        let buffer: Vec<f32> = (0..44100)
+            .flat_map(|index| {
+                let x = amplitude * (TAU * 441.0 * index as f32 / 44100.0).sin();
+                [x, x * 0.5]
+            })
+            .collect();
+        meter.process(&buffer);
+
+        assert!((meter.rms_channel(0) - amplitude / SQRT_2).abs() < 1e-3);
+        assert!((meter.rms_channel(1) - amplitude / SQRT_2 * 0.5).abs() < 1e-3);
+        assert!((meter.peak() - amplitude).abs() < 1e-3);
+        assert!((meter.peak_channel(1) - amplitude * 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn peak_holds_then_decays() {
+        let mut meter = Meter::new(10.0, 100.0, 44100.0);
+        meter.process(&[1.0, 1.0]);
+        meter.process(&vec![0.0; 2 * 441]);
+        let held = meter.peak();
+        assert!(held > 0.8 && held < 1.0);
+        meter.process(&vec![0.0; 2 * 44100]);
+        assert!(meter.peak() < 1e-3);
+        assert!(meter.rms() < 1e-6);
+    }
+}
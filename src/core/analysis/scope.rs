@@ -0,0 +1,168 @@
+//! Hands the most recent samples from the audio thread to a display.
+//!
+//! # Overview
+//!
+//! The samples live in a ring of atomics, such that both threads can
+//! touch it at once without locking. The writer announces how far it
+//! is about to write before writing, and publishes how far it has
+//! written after, both as running counts that never wrap in practice.
+//! A reader copies the samples behind the published count, then checks
+//! the announced count to find out which of them may have been
+//! overwritten mid-copy, and drops those.
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::sync::atomic::{fence, AtomicU32, AtomicUsize, Ordering};
+
+/// A lock-free ring of the latest samples pushed by a single writer,
+/// readable from another thread.
+///
+/// When the reader falls behind, the oldest samples are overwritten
+/// rather than the writer waiting for it.
+#[derive(Debug)]
+pub struct Scope {
+    /// The bits of the samples, indexed by their count modulo the
+    /// capacity.
+    samples: Box<[AtomicU32]>,
+    /// The number of samples that the writer has started writing.
+    started: AtomicUsize,
+    /// The number of samples that the writer has finished writing.
+    written: AtomicUsize,
+}
+
+impl Scope {
+    /// Creates a new [`Scope`] holding the latest `capacity` samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be non-zero!");
+        Self {
+            samples: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            started: AtomicUsize::new(0),
+            written: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of samples held by the scope.
+    pub fn capacity(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Appends the samples of a `buffer`, overwriting the oldest, e.g.
+    /// a mono mixdown or interleaved frames.
+    ///
+    /// This neither allocates nor locks, so it is safe to call from the
+    /// audio thread. Only one thread may push at a time, as concurrent
+    /// pushes are not ordered with respect to each other.
+    pub fn push(&self, buffer: &[f32]) {
+        let capacity = self.samples.len();
+        // Only the latest samples of a buffer longer than the ring
+        // survive, so skip the rest.
+        let buffer = &buffer[buffer.len().saturating_sub(capacity)..];
+        let start = self.written.load(Ordering::Relaxed);
+        let end = start + buffer.len();
+        self.started.store(end, Ordering::Relaxed);
+        // Readers that see any of the samples below also see `started`.
+        fence(Ordering::Release);
+        for (count, sample) in (start..end).zip(buffer.iter()) {
+            self.samples[count % capacity].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        self.written.store(end, Ordering::Release);
+    }
+
+    /// Copies the latest samples into the front of `out`, oldest first,
+    /// returning the number of samples copied.
+    ///
+    /// Fewer samples than fit in `out` are copied if fewer have been
+    /// pushed so far, or if the writer overwrote the oldest of them
+    /// while they were being copied.
+    pub fn read_latest(&self, out: &mut [f32]) -> usize {
+        let capacity = self.samples.len();
+        let written = self.written.load(Ordering::Acquire);
+        let len = out.len().min(capacity).min(written);
+        let first = written - len;
+        for (count, sample) in (first..written).zip(out.iter_mut()) {
+            *sample = f32::from_bits(self.samples[count % capacity].load(Ordering::Relaxed));
+        }
+        // Pairs with the fence in `push`, such that any sample from a
+        // later push shows up in `started`.
+        fence(Ordering::Acquire);
+        let started = self.started.load(Ordering::Relaxed);
+        let overwritten = started
+            .saturating_sub(capacity)
+            .saturating_sub(first)
+            .min(len);
+        out.copy_within(overwritten..len, 0);
+        len - overwritten
+    }
+
+    /// Copies the latest `len` samples into a new [`Vec`], which may be
+    /// shorter as with [`read_latest`].
+    ///
+    /// [`read_latest`]: Self::read_latest
+    pub fn to_vec(&self, len: usize) -> Vec<f32> {
+        let mut out = vec![0.0; len];
+        let copied = self.read_latest(&mut out);
+        out.truncate(copied);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::Scope;
+    use crate::core::testing::assert_no_alloc;
+
+    #[test]
+    fn oldest_samples_are_overwritten() {
+        let scope = Scope::new(8);
+        assert!(scope.to_vec(4).is_empty());
+        scope.push(&[1.0, 2.0, 3.0]);
+        assert_eq!(scope.to_vec(4), [1.0, 2.0, 3.0]);
+        scope.push(&[4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
+        assert_eq!(scope.to_vec(4), [7.0, 8.0, 9.0, 10.0]);
+        assert_eq!(scope.to_vec(16), [3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
+        let long: Vec<f32> = (0..20).map(|index| index as f32).collect();
+        assert_no_alloc(|| scope.push(&long));
+        assert_eq!(scope.to_vec(8), long[12..]);
+    }
+
+    #[test]
+    fn concurrent_reads_are_consistent() {
+        let scope = Arc::new(Scope::new(256));
+        let done = Arc::new(AtomicBool::new(false));
+        let writer = {
+            let (scope, done) = (scope.clone(), done.clone());
+            std::thread::spawn(move || {
+                // A ramp, such that every sample names its own count.
+                let mut count = 0;
+                for block in 0..20_000 {
+                    let len = 1 + block % 97;
+                    let buffer: Vec<f32> = (count..count + len).map(|x| x as f32).collect();
+                    scope.push(&buffer);
+                    count += len;
+                }
+                done.store(true, Ordering::Release);
+            })
+        };
+
+        let mut out = vec![0.0; 256];
+        let mut reads = 0;
+        while !done.load(Ordering::Acquire) || reads == 0 {
+            let len = scope.read_latest(&mut out);
+            for pair in out[..len].windows(2) {
+                assert_eq!(pair[1], pair[0] + 1.0);
+            }
+            reads += 1;
+        }
+        writer.join().unwrap();
+        let len = scope.read_latest(&mut out);
+        assert_eq!(len, 256);
+        assert!(out.windows(2).all(|pair| pair[1] == pair[0] + 1.0));
+    }
+}
@@ -0,0 +1,203 @@
+//! Spectrum analysis of interleaved stereo audio, e.g. for display.
+//!
+//! # Overview
+//!
+//! Both channels are mixed to mono and collected into frames of
+//! `fft_size` samples, starting a new frame every `fft_size / overlap`
+//! samples such that the display updates more often than once per
+//! frame. Each frame is windowed with a Hann window and transformed,
+//! and the magnitude of every bin is averaged with the previous frames
+//! before being converted to dB, where a full scale sine centered on a
+//! bin reads as 0 dB.
+use alloc::{vec, vec::Vec};
+
+use crate::core::dsp::{
+    decibel::{gain_to_db, SILENCE_DB},
+    fft::{hann, Complex, Fft},
+};
+
+/// The number of channels mixed by the [`Spectrum`].
+pub const CHANNELS: usize = 2;
+
+/// Reports the magnitude spectrum of the audio passing through it.
+#[derive(Debug, Clone)]
+pub struct Spectrum {
+    /// The transform of a frame.
+    fft: Fft,
+    /// The analysis window.
+    window: Vec<f32>,
+    /// The factor reading a full scale sine as unity, computed once
+    /// along with the `window`.
+    scale: f32,
+    /// The most recent frame of input, oldest first.
+    input: Vec<f32>,
+    /// The number of samples between the starts of frames.
+    hop: usize,
+    /// The position within the current hop.
+    index: usize,
+    /// The weight of the previous frames in the average, from `0.0` for
+    /// none to just below `1.0` for a very slow average.
+    smoothing: f32,
+    /// The averaged linear magnitude of each bin.
+    magnitudes: Vec<f32>,
+    /// The averaged magnitude of each bin, in dB.
+    magnitudes_db: Vec<f32>,
+    /// The work buffer for the spectrum of a frame.
+    scratch: Vec<Complex<f32>>,
+}
+
+impl Spectrum {
+    /// Creates a new [`Spectrum`] transforming frames of at least
+    /// `fft_size` samples rounded up to a power of two, starting
+    /// `overlap` frames within each frame, without smoothing.
+    pub fn new(fft_size: usize, overlap: usize) -> Self {
+        let size = fft_size.max(2).next_power_of_two();
+        let overlap = overlap.clamp(1, size);
+        let mut window = vec![0.0; size];
+        hann(&mut window);
+        // A full scale sine peaks at half the sum of the window.
+        let scale = 2.0 / window.iter().sum::<f32>();
+        Self {
+            fft: Fft::new(size),
+            window,
+            scale,
+            input: vec![0.0; size],
+            hop: size / overlap,
+            index: 0,
+            smoothing: 0.0,
+            magnitudes: vec![0.0; size / 2 + 1],
+            magnitudes_db: vec![SILENCE_DB; size / 2 + 1],
+            scratch: vec![Complex::ZERO; size],
+        }
+    }
+
+    /// Sets the weight of the previous frames in the average, clamped
+    /// to `0.0..=0.999`, where higher values fall more slowly.
+    pub fn set_smoothing(&mut self, smoothing: f32) {
+        self.smoothing = smoothing.clamp(0.0, 0.999);
+    }
+
+    /// The number of samples in a frame.
+    pub fn fft_size(&self) -> usize {
+        self.fft.size()
+    }
+
+    /// The center frequency of a `bin` in Hz.
+    pub fn bin_frequency(&self, bin: usize, sample_rate: f64) -> f64 {
+        bin as f64 * sample_rate / self.fft_size() as f64
+    }
+
+    /// Measures a `buffer` of interleaved stereo samples, updating the
+    /// magnitudes once per hop.
+    pub fn process(&mut self, buffer: &[f32]) {
+        let size = self.fft_size();
+        for frame in buffer.chunks_exact(CHANNELS) {
+            self.input[size - self.hop + self.index] = (frame[0] + frame[1]) / 2.0;
+            self.index += 1;
+            if self.index == self.hop {
+                self.analyze();
+                self.input.copy_within(self.hop.., 0);
+                self.index = 0;
+            }
+        }
+    }
+
+    /// Transforms the most recent frame, folding it into the averages.
+    fn analyze(&mut self) {
+        for ((bin, &x), &w) in self
+            .scratch
+            .iter_mut()
+            .zip(self.input.iter())
+            .zip(self.window.iter())
+        {
+            *bin = Complex::new(x * w, 0.0);
+        }
+        self.fft.forward(&mut self.scratch);
+        for ((magnitude, db), bin) in self
+            .magnitudes
+            .iter_mut()
+            .zip(self.magnitudes_db.iter_mut())
+            .zip(self.scratch.iter())
+        {
+            *magnitude =
+                self.smoothing * *magnitude + (1.0 - self.smoothing) * bin.norm() * self.scale;
+            *db = gain_to_db(*magnitude);
+        }
+    }
+
+    /// The averaged magnitude of each bin from DC up to Nyquist, in dB.
+    pub fn magnitudes_db(&self) -> &[f32] {
+        &self.magnitudes_db
+    }
+
+    /// Clears the measurements.
+    pub fn reset(&mut self) {
+        self.input.fill(0.0);
+        self.index = 0;
+        self.magnitudes.fill(0.0);
+        self.magnitudes_db.fill(SILENCE_DB);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::Spectrum;
+    use crate::core::dsp::decibel::SILENCE_DB;
+
+    fn sine(frequency: f32, amplitude: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|index| {
+                let x = amplitude * (TAU * frequency * index as f32 / 44100.0).sin();
+                [x, x]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tone_lands_in_its_bin() {
+        let mut spectrum = Spectrum::new(2048, 4);
+        assert!(spectrum.magnitudes_db().iter().all(|&db| db == SILENCE_DB));
+        spectrum.process(&sine(1000.0, 0.5, 8192));
+
+        let magnitudes = spectrum.magnitudes_db();
+        assert_eq!(magnitudes.len(), 1025);
+        let peak = (0..magnitudes.len())
+            .max_by(|&a, &b| magnitudes[a].total_cmp(&magnitudes[b]))
+            .unwrap();
+        assert_eq!(peak, 46);
+        assert!((spectrum.bin_frequency(peak, 44100.0) - 1000.0).abs() < 44100.0 / 2048.0);
+        // Half of full scale, less the loss of falling between bins.
+        assert!(
+            (magnitudes[peak] + 6.02).abs() < 1.5,
+            "{}",
+            magnitudes[peak]
+        );
+        assert!(magnitudes[200] < -60.0);
+
+        // A tone centered on a bin reads at its level exactly.
+        spectrum.reset();
+        spectrum.process(&sine(64.0 * 44100.0 / 2048.0, 0.5, 8192));
+        assert!((spectrum.magnitudes_db()[64] + 6.02).abs() < 0.01);
+    }
+
+    #[test]
+    fn smoothing_averages_frames() {
+        let mut spectrum = Spectrum::new(1024, 2);
+        spectrum.set_smoothing(0.8);
+        let bin = 32;
+        let tone = sine(bin as f32 * 44100.0 / 1024.0, 1.0, 44100);
+        spectrum.process(&tone);
+        assert!(spectrum.magnitudes_db()[bin].abs() < 0.01);
+
+        // Each hop of silence keeps 80% of the previous magnitude.
+        let silence = vec![0.0; 2 * 1024];
+        spectrum.process(&silence);
+        let once = spectrum.magnitudes_db()[bin];
+        spectrum.process(&silence[..2 * 512]);
+        let twice = spectrum.magnitudes_db()[bin];
+        assert!(once > -10.0 && twice < once);
+        assert!((twice - once - 20.0 * 0.8_f32.log10()).abs() < 0.01);
+    }
+}
@@ -0,0 +1,123 @@
+//! Inter-sample peak metering for interleaved stereo audio.
+//!
+//! # Overview
+//!
+//! The peak of the continuous signal rebuilt by a DAC can lie between
+//! samples, exceeding every sample around it. [`TruePeak`] estimates it
+//! by upsampling each channel four times with the polyphase FIR used by
+//! the [`Oversampler`], as recommended by ITU-R BS.1770, and holding the
+//! largest magnitude of the upsampled signal.
+//!
+//! [`Oversampler`]: crate::core::dsp::Oversampler
+use crate::core::dsp::{decibel::gain_to_db, fir::Upsampler};
+
+/// The number of channels measured by the [`TruePeak`].
+pub const CHANNELS: usize = 2;
+
+/// The upsampling factor used to find the inter-sample peaks.
+pub const FACTOR: usize = 4;
+
+/// Reports the highest inter-sample peak of the audio passing through
+/// it.
+#[derive(Debug, Clone)]
+pub struct TruePeak {
+    /// The interpolation filter of each channel.
+    upsamplers: [Upsampler; CHANNELS],
+    /// The highest magnitude of each channel since the last reset.
+    peaks: [f32; CHANNELS],
+}
+
+impl TruePeak {
+    /// Creates a new [`TruePeak`].
+    pub fn new() -> Self {
+        let upsampler = Upsampler::new(FACTOR);
+        Self {
+            upsamplers: [upsampler.clone(), upsampler],
+            peaks: [0.0; CHANNELS],
+        }
+    }
+
+    /// Measures a `buffer` of interleaved stereo samples.
+    pub fn process(&mut self, buffer: &[f32]) {
+        let mut upsampled = [0.0; FACTOR];
+        for frame in buffer.chunks_exact(CHANNELS) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                self.upsamplers[channel].process(sample, &mut upsampled);
+                // The samples themselves are peaks too, even where the
+                // filter rings slightly below them.
+                self.peaks[channel] = upsampled
+                    .iter()
+                    .fold(self.peaks[channel].max(sample.abs()), |peak, x| {
+                        peak.max(x.abs())
+                    });
+            }
+        }
+    }
+
+    /// Clears the measurements.
+    pub fn reset(&mut self) {
+        self.upsamplers.iter_mut().for_each(Upsampler::reset);
+        self.peaks = [0.0; CHANNELS];
+    }
+
+    /// The true peak of a `channel` as a linear gain.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `channel` is out of bounds.
+    pub fn true_peak_channel(&self, channel: usize) -> f32 {
+        self.peaks[channel]
+    }
+
+    /// The highest true peak across channels as a linear gain.
+    pub fn true_peak(&self) -> f32 {
+        self.peaks.iter().fold(0.0, |a, &b| a.max(b))
+    }
+
+    /// The highest true peak across channels in dBTP.
+    pub fn true_peak_db(&self) -> f32 {
+        gain_to_db(self.true_peak())
+    }
+}
+
+impl Default for TruePeak {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    use super::TruePeak;
+
+    #[test]
+    fn inter_sample_peak_exceeds_samples() {
+        // A quarter of the sample rate, sampled halfway between its
+        // peaks and zero crossings.
+        let amplitude = 1.2;
+        let buffer: Vec<f32> = (0..4096)
+            .flat_map(|index| {
+                let x = amplitude * (FRAC_PI_2 * index as f32 + FRAC_PI_4).sin();
+                [x, 0.0]
+            })
+            .collect();
+        let sample_peak = buffer.iter().fold(0.0_f32, |peak, x| peak.max(x.abs()));
+        assert!(sample_peak < 1.0);
+
+        let mut meter = TruePeak::new();
+        meter.process(&buffer);
+        assert!(meter.true_peak() > sample_peak);
+        assert!(
+            (meter.true_peak() - amplitude).abs() < 0.02,
+            "{}",
+            meter.true_peak()
+        );
+        assert!(meter.true_peak_db() > 0.0);
+        assert_eq!(meter.true_peak_channel(1), 0.0);
+
+        meter.reset();
+        assert_eq!(meter.true_peak(), 0.0);
+    }
+}
@@ -0,0 +1,408 @@
+//! Runs effects in series over the same buffer, or in parallel over
+//! copies of it.
+use alloc::{boxed::Box, vec, vec::Vec};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::core::effect::Effect;
+use crate::core::transport::Transport;
+
+/// An ordered list of effects, each processing the output of the
+/// previous one.
+#[derive(Default)]
+pub struct Chain {
+    /// The effects, in processing order.
+    effects: Vec<Box<dyn Effect>>,
+}
+
+impl Chain {
+    pub fn new() -> Self {
+        Self { effects: vec![] }
+    }
+
+    /// Appends an effect to the end of the chain.
+    pub fn push(&mut self, effect: Box<dyn Effect>) {
+        self.effects.push(effect);
+    }
+
+    /// Inserts an effect at `index`, shifting the effects after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the chain.
+    pub fn insert(&mut self, index: usize, effect: Box<dyn Effect>) {
+        self.effects.insert(index, effect);
+    }
+
+    /// Removes and returns the effect at `index`, if any.
+    pub fn remove(&mut self, index: usize) -> Option<Box<dyn Effect>> {
+        if index < self.effects.len() {
+            Some(self.effects.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// The number of effects in the chain.
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Whether the chain has no effects, processing as a no-op.
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Applies each effect to the `buffer` in order.
+    pub fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        for effect in self.effects.iter_mut() {
+            effect.process(position, buffer);
+        }
+    }
+
+    /// Applies each effect to the `buffer` in order, at the playhead of
+    /// the `transport`.
+    pub fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [f32]) {
+        for effect in self.effects.iter_mut() {
+            effect.process_with_transport(transport, buffer);
+        }
+    }
+
+    /// Clears the internal state of every effect.
+    pub fn reset(&mut self) {
+        for effect in self.effects.iter_mut() {
+            effect.reset();
+        }
+    }
+
+    /// The total delay introduced by the effects in frames.
+    pub fn latency_samples(&self) -> usize {
+        self.effects
+            .iter()
+            .map(|effect| effect.latency_samples())
+            .sum()
+    }
+}
+
+impl core::fmt::Debug for Chain {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Chain")
+            .field("len", &self.effects.len())
+            .finish()
+    }
+}
+
+/// A bank of effects, each processing its own copy of the same input,
+/// whose outputs are summed.
+///
+/// With the `rayon` feature, the branches are processed on worker
+/// threads. Either way, the outputs are summed in branch order once all
+/// of them are done, such that the result is the same down to the bit
+/// regardless of threading.
+#[derive(Default)]
+pub struct ParallelChain {
+    /// The effects, in summation order.
+    branches: Vec<Box<dyn Effect + Send>>,
+    /// The copy of the input processed by each branch.
+    scratch: Vec<Vec<f32>>,
+}
+
+impl ParallelChain {
+    pub fn new() -> Self {
+        Self {
+            branches: vec![],
+            scratch: vec![],
+        }
+    }
+
+    /// Appends a branch to the bank.
+    pub fn push(&mut self, effect: Box<dyn Effect + Send>) {
+        self.branches.push(effect);
+        self.scratch.push(vec![]);
+    }
+
+    /// Removes and returns the branch at `index`, if any.
+    pub fn remove(&mut self, index: usize) -> Option<Box<dyn Effect + Send>> {
+        if index < self.branches.len() {
+            self.scratch.remove(index);
+            Some(self.branches.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// The number of branches in the bank.
+    pub fn len(&self) -> usize {
+        self.branches.len()
+    }
+
+    /// Whether the bank has no branches, processing into silence.
+    pub fn is_empty(&self) -> bool {
+        self.branches.is_empty()
+    }
+
+    /// Preallocates the scratch buffers for blocks of up to `len`
+    /// samples, such that `process` does not allocate.
+    pub fn reserve(&mut self, len: usize) {
+        for scratch in self.scratch.iter_mut() {
+            if scratch.len() < len {
+                scratch.resize(len, 0.0);
+            }
+        }
+    }
+
+    /// Applies each branch to a copy of the `buffer`, replacing it with
+    /// the sum of their outputs.
+    pub fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        self.process_with(buffer, |effect, buffer| effect.process(position, buffer));
+    }
+
+    /// Applies each branch to a copy of the `buffer` at the playhead of
+    /// the `transport`, replacing it with the sum of their outputs.
+    pub fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [f32]) {
+        self.process_with(buffer, |effect, buffer| {
+            effect.process_with_transport(transport, buffer)
+        });
+    }
+
+    /// Runs `process` over a copy of the `buffer` for each branch, then
+    /// sums the copies back into the `buffer` in branch order.
+    fn process_with(
+        &mut self,
+        buffer: &mut [f32],
+        process: impl Fn(&mut dyn Effect, &mut [f32]) + Sync,
+    ) {
+        self.reserve(buffer.len());
+        let input: &[f32] = buffer;
+        let branch = |(effect, scratch): (&mut Box<dyn Effect + Send>, &mut Vec<f32>)| {
+            let scratch = &mut scratch[..input.len()];
+            scratch.copy_from_slice(input);
+            process(effect.as_mut(), scratch);
+        };
+        #[cfg(feature = "rayon")]
+        self.branches
+            .par_iter_mut()
+            .zip(self.scratch.par_iter_mut())
+            .for_each(branch);
+        #[cfg(not(feature = "rayon"))]
+        self.branches
+            .iter_mut()
+            .zip(self.scratch.iter_mut())
+            .for_each(branch);
+        self.sum_into(buffer);
+    }
+
+    /// Replaces the `buffer` with the sum of the scratch buffers, adding
+    /// them in branch order.
+    fn sum_into(&self, buffer: &mut [f32]) {
+        buffer.fill(0.0);
+        for scratch in self.scratch.iter() {
+            for (sample, x) in buffer.iter_mut().zip(scratch.iter()) {
+                *sample += x;
+            }
+        }
+    }
+
+    /// Clears the internal state of every branch.
+    pub fn reset(&mut self) {
+        for effect in self.branches.iter_mut() {
+            effect.reset();
+        }
+    }
+
+    /// The delay introduced by the slowest branch in frames.
+    ///
+    /// The branches are not lined up with each other, so any with less
+    /// latency lead the others in the sum.
+    pub fn latency_samples(&self) -> usize {
+        self.branches
+            .iter()
+            .map(|effect| effect.latency_samples())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl core::fmt::Debug for ParallelChain {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ParallelChain")
+            .field("len", &self.branches.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Chain, ParallelChain};
+    use crate::core::effect::Effect;
+
+    /// Scales the signal, growing louder every time it is reset.
+    struct Scale {
+        gain: f32,
+    }
+
+    impl Scale {
+        fn boxed(gain: f32) -> Box<dyn Effect> {
+            Box::new(Scale { gain })
+        }
+    }
+
+    impl Effect for Scale {
+        type Parameters = f32;
+
+        fn process(&mut self, _: usize, buffer: &mut [f32]) {
+            for sample in buffer.iter_mut() {
+                *sample *= self.gain;
+            }
+        }
+
+        fn reset(&mut self) {
+            self.gain += 1.0;
+        }
+
+        fn set_parameters(&mut self, gain: f32) {
+            self.gain = gain;
+        }
+    }
+
+    /// Passes the signal through, reporting a fixed delay.
+    struct Latent {
+        latency: usize,
+    }
+
+    impl Effect for Latent {
+        type Parameters = usize;
+
+        fn process(&mut self, _: usize, _: &mut [f32]) {}
+
+        fn reset(&mut self) {}
+
+        fn set_parameters(&mut self, latency: usize) {
+            self.latency = latency;
+        }
+
+        fn latency_samples(&self) -> usize {
+            self.latency
+        }
+    }
+
+    #[test]
+    fn empty_chain_is_a_no_op() {
+        let mut chain = Chain::new();
+        let mut buffer = vec![0.25, -0.5, 1.0, 0.0];
+        chain.process(0, &mut buffer);
+        assert_eq!(buffer, [0.25, -0.5, 1.0, 0.0]);
+        assert_eq!(chain.latency_samples(), 0);
+    }
+
+    #[test]
+    fn gains_multiply() {
+        let mut chain = Chain::new();
+        chain.push(Scale::boxed(2.0));
+        chain.push(Scale::boxed(0.25));
+        chain.insert(1, Scale::boxed(3.0));
+        let mut buffer = vec![1.0, -1.0];
+        chain.process(0, &mut buffer);
+        assert_eq!(buffer, [1.5, -1.5]);
+
+        assert!(chain.remove(1).is_some());
+        assert!(chain.remove(5).is_none());
+        let mut buffer = vec![1.0, -1.0];
+        chain.process(0, &mut buffer);
+        assert_eq!(buffer, [0.5, -0.5]);
+    }
+
+    #[test]
+    fn reset_reaches_every_effect() {
+        let mut chain = Chain::new();
+        chain.push(Scale::boxed(1.0));
+        chain.push(Scale::boxed(1.0));
+        chain.reset();
+        let mut buffer = vec![1.0, 1.0];
+        chain.process(0, &mut buffer);
+        assert_eq!(buffer, [4.0, 4.0]);
+    }
+
+    #[test]
+    fn latencies_add_up() {
+        let mut chain = Chain::new();
+        chain.push(Box::new(Latent { latency: 64 }));
+        chain.push(Scale::boxed(1.0));
+        chain.push(Box::new(Latent { latency: 17 }));
+        assert_eq!(chain.latency_samples(), 81);
+        chain.remove(0);
+        assert_eq!(chain.latency_samples(), 17);
+    }
+
+    #[test]
+    fn branches_sum() {
+        let mut bank = ParallelChain::new();
+        let mut buffer = vec![1.0, -1.0];
+        bank.process(0, &mut buffer);
+        assert_eq!(buffer, [0.0, 0.0]);
+
+        bank.push(Box::new(Scale { gain: 2.0 }));
+        bank.push(Box::new(Scale { gain: 0.5 }));
+        bank.push(Box::new(Latent { latency: 32 }));
+        let mut buffer = vec![1.0, -1.0];
+        bank.process(0, &mut buffer);
+        assert_eq!(buffer, [3.5, -3.5]);
+        assert_eq!(bank.latency_samples(), 32);
+
+        assert!(bank.remove(0).is_some());
+        assert!(bank.remove(5).is_none());
+        let mut buffer = vec![1.0, -1.0];
+        bank.process(0, &mut buffer);
+        assert_eq!(buffer, [1.5, -1.5]);
+    }
+
+    #[test]
+    fn output_is_deterministic() {
+        use crate::core::effect::{
+            Delay, DelayParameters, Distortion, DistortionParameters, TranceGate,
+            TranceGateParameters,
+        };
+
+        let bank = || {
+            let mut bank = ParallelChain::new();
+            let mut gate = TranceGate::new();
+            gate.initialize(TranceGateParameters::new(0.05, 0.9, 44100.0));
+            bank.push(Box::new(gate));
+            let mut delay = Delay::new();
+            delay.initialize(DelayParameters::new(441, 0.7, 0.5));
+            bank.push(Box::new(delay));
+            let mut distortion = Distortion::new(44100.0);
+            distortion.initialize(DistortionParameters::new(4.0, 0.5, 0.8, 2));
+            bank.push(Box::new(distortion));
+            bank
+        };
+        let input: Vec<f32> = (0..8192)
+            .map(|index| (index as f32 * 0.013).sin() * 0.7)
+            .collect();
+
+        // Sums each branch one after another without the bank.
+        let mut expected = vec![0.0; input.len()];
+        let mut sequential = bank();
+        for branch in sequential.branches.iter_mut() {
+            let mut copy = input.clone();
+            // Passes the running frame offset, such that the gate runs
+            // continuously across chunks.
+            for (index, chunk) in copy.chunks_mut(512).enumerate() {
+                branch.process(index * 256, chunk);
+            }
+            for (sum, x) in expected.iter_mut().zip(copy.iter()) {
+                *sum += x;
+            }
+        }
+
+        for _ in 0..4 {
+            let mut bank = bank();
+            let mut buffer = input.clone();
+            for (index, chunk) in buffer.chunks_mut(512).enumerate() {
+                bank.process(index * 256, chunk);
+            }
+            assert_eq!(buffer, expected);
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Building blocks shared by the effects.
+pub mod adsr;
+pub mod allpass;
+pub mod biquad;
+pub mod comb;
+pub mod crossover;
+pub mod decibel;
+pub mod delay_comp;
+pub mod delay_line;
+pub mod denormal;
+pub mod envelope;
+pub mod fft;
+pub mod fir;
+pub mod lfo;
+pub mod ms;
+pub mod onepole;
+pub mod oversample;
+pub mod resample;
+pub mod smooth;
+pub mod tables;
+
+pub use adsr::Adsr;
+pub use allpass::Allpass;
+pub use biquad::{Biquad, BiquadCoefficients};
+pub use comb::{Comb, CombKind};
+pub use crossover::{Crossover, LinkwitzRiley};
+pub use delay_comp::DelayCompensator;
+pub use delay_line::DelayLine;
+pub use envelope::EnvelopeFollower;
+pub use fft::{Complex, Fft};
+pub use lfo::{Lfo, Waveform};
+pub use onepole::OnePole;
+pub use oversample::Oversampler;
+pub use resample::Resampler;
+pub use smooth::{SmoothedValue, Smoothing};
+pub use tables::{FastSin, FastTanh};
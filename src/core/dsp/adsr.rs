@@ -0,0 +1,294 @@
+//! Generates an attack, decay, sustain, release envelope.
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// How far past its target an exponential attack aims, such that it
+/// reaches `1.0` in a finite time with a gently rounded top.
+const ATTACK_OVERSHOOT: f32 = 0.3;
+
+/// How far past its target an exponential decay or release aims, such
+/// that it settles in a finite time.
+const DECAY_OVERSHOOT: f32 = 0.0001;
+
+/// How close a segment must get to its end before snapping to it,
+/// absorbing the rounding accumulated along the way.
+const SETTLE_THRESHOLD: f32 = 1e-5;
+
+/// The shape of each segment traced by an [`Adsr`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Curve {
+    /// Moves at a constant rate.
+    #[default]
+    Linear,
+    /// Moves quickly at first, then slows down as it approaches the
+    /// target, like an analog envelope.
+    Exponential,
+}
+
+/// The segment that an [`Adsr`] is in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Stage {
+    /// Resting at `0.0` until the gate opens.
+    #[default]
+    Idle,
+    /// Rising to `1.0` after the gate opens.
+    Attack,
+    /// Falling from `1.0` to the sustain level.
+    Decay,
+    /// Holding the sustain level until the gate closes.
+    Sustain,
+    /// Falling to `0.0` after the gate closes.
+    Release,
+}
+
+/// The per-sample movement of a segment.
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    /// The change per sample of a linear segment.
+    step: f32,
+    /// The smoothing coefficient of an exponential segment.
+    coefficient: f32,
+}
+
+impl Segment {
+    /// Computes a segment that spans the whole range within `ms`, where
+    /// an exponential segment aims `overshoot` past its target.
+    fn new(ms: f32, sample_rate: f64, overshoot: f32) -> Self {
+        let samples = (ms.max(0.0) as f64 * 0.001 * sample_rate) as f32;
+        if samples < 1.0 {
+            return Self {
+                step: 1.0,
+                coefficient: 0.0,
+            };
+        }
+        Self {
+            step: 1.0 / samples,
+            coefficient: (overshoot / (1.0 + overshoot)).powf(1.0 / samples),
+        }
+    }
+}
+
+/// An envelope generator driven by a gate.
+///
+/// The attack, decay, and release times are measured across the whole
+/// range from `0.0` to `1.0`, such that a partial swing, e.g. a decay to
+/// a high sustain level or a retrigger during the release, takes a
+/// proportionally shorter time.
+#[derive(Debug, Clone)]
+pub struct Adsr {
+    /// The sample rate of the generated envelope.
+    sample_rate: f64,
+    /// The shape of each segment.
+    curve: Curve,
+    /// The current segment.
+    stage: Stage,
+    /// The current output.
+    level: f32,
+    /// The level held while the gate is open.
+    sustain: f32,
+    /// The times of the attack, decay, and release, in milliseconds.
+    times_ms: [f32; 3],
+    /// The movement of the attack, decay, and release.
+    segments: [Segment; 3],
+}
+
+impl Adsr {
+    /// Creates a new, idle [`Adsr`] with instant segments and a full
+    /// sustain level.
+    ///
+    /// # Example
+    ///
+    /// If you want a plucked envelope:
+    ///
+    /// ```rust
+    /// # use photon::core::dsp::adsr::*;
+    /// let mut adsr = Adsr::new(Curve::Exponential, 44100.0);
+    /// adsr.set_attack_ms(2.0);
+    /// adsr.set_decay_ms(300.0);
+    /// adsr.set_sustain(0.0);
+    /// adsr.set_release_ms(100.0);
+    /// adsr.gate_on();
+    /// let _ = adsr.next();
+    /// ```
+    pub fn new(curve: Curve, sample_rate: f64) -> Self {
+        let mut adsr = Self {
+            sample_rate,
+            curve,
+            stage: Stage::Idle,
+            level: 0.0,
+            sustain: 1.0,
+            times_ms: [0.0; 3],
+            segments: [Segment::new(0.0, sample_rate, 1.0); 3],
+        };
+        adsr.update_segments();
+        adsr
+    }
+
+    /// Sets the time taken to rise from `0.0` to `1.0`.
+    pub fn set_attack_ms(&mut self, ms: f32) {
+        self.times_ms[0] = ms;
+        self.update_segments();
+    }
+
+    /// Sets the time taken to fall from `1.0` to `0.0` after the attack,
+    /// stopping at the sustain level.
+    pub fn set_decay_ms(&mut self, ms: f32) {
+        self.times_ms[1] = ms;
+        self.update_segments();
+    }
+
+    /// Sets the level held while the gate is open, clamped to
+    /// `0.0..=1.0`.
+    pub fn set_sustain(&mut self, level: f32) {
+        self.sustain = level.clamp(0.0, 1.0);
+    }
+
+    /// Sets the time taken to fall from `1.0` to `0.0` after the gate
+    /// closes.
+    pub fn set_release_ms(&mut self, ms: f32) {
+        self.times_ms[2] = ms;
+        self.update_segments();
+    }
+
+    /// Sets the shape of each segment.
+    pub fn set_curve(&mut self, curve: Curve) {
+        self.curve = curve;
+    }
+
+    /// Opens the gate, starting the attack from the current level such
+    /// that retriggering does not click.
+    pub fn gate_on(&mut self) {
+        self.stage = Stage::Attack;
+    }
+
+    /// Closes the gate, starting the release from the current level.
+    pub fn gate_off(&mut self) {
+        if self.stage != Stage::Idle {
+            self.stage = Stage::Release;
+        }
+    }
+
+    /// Returns to silence immediately.
+    pub fn reset(&mut self) {
+        self.stage = Stage::Idle;
+        self.level = 0.0;
+    }
+
+    /// The current segment.
+    pub fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// Whether the envelope is producing anything other than silence.
+    pub fn is_active(&self) -> bool {
+        self.stage != Stage::Idle
+    }
+
+    /// Advances the envelope by one sample, returning its level.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> f32 {
+        match self.stage {
+            Stage::Idle => {}
+            Stage::Attack => {
+                self.level = self.approach(0, 1.0 + ATTACK_OVERSHOOT, 1.0);
+                if self.level >= 1.0 - SETTLE_THRESHOLD {
+                    self.level = 1.0;
+                    self.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                self.level = self.approach(1, self.sustain - DECAY_OVERSHOOT, -1.0);
+                if self.level <= self.sustain + SETTLE_THRESHOLD {
+                    self.level = self.sustain;
+                    self.stage = Stage::Sustain;
+                }
+            }
+            Stage::Sustain => self.level = self.sustain,
+            Stage::Release => {
+                self.level = self.approach(2, -DECAY_OVERSHOOT, -1.0);
+                if self.level <= SETTLE_THRESHOLD {
+                    self.level = 0.0;
+                    self.stage = Stage::Idle;
+                }
+            }
+        }
+        self.level
+    }
+
+    /// Moves the level along a segment, where an exponential segment
+    /// aims at `target` and a linear one moves in the `direction`.
+    fn approach(&self, segment: usize, target: f32, direction: f32) -> f32 {
+        let segment = self.segments[segment];
+        match self.curve {
+            Curve::Linear => self.level + segment.step * direction,
+            Curve::Exponential => target + (self.level - target) * segment.coefficient,
+        }
+    }
+
+    /// Recomputes the movement of each segment from its time.
+    fn update_segments(&mut self) {
+        let [attack, decay, release] = self.times_ms;
+        self.segments = [
+            Segment::new(attack, self.sample_rate, ATTACK_OVERSHOOT),
+            Segment::new(decay, self.sample_rate, DECAY_OVERSHOOT),
+            Segment::new(release, self.sample_rate, DECAY_OVERSHOOT),
+        ];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Adsr, Curve, Stage};
+
+    fn envelope(curve: Curve) -> Adsr {
+        let mut adsr = Adsr::new(curve, 1000.0);
+        adsr.set_attack_ms(100.0);
+        adsr.set_decay_ms(200.0);
+        adsr.set_sustain(0.5);
+        adsr.set_release_ms(300.0);
+        adsr
+    }
+
+    #[test]
+    fn stages_reach_their_levels() {
+        for curve in [Curve::Linear, Curve::Exponential] {
+            let mut adsr = envelope(curve);
+            adsr.gate_on();
+            let attack: Vec<f32> = (0..100).map(|_| adsr.next()).collect();
+            assert!(attack.windows(2).all(|pair| pair[1] > pair[0]));
+            assert_eq!(attack[99], 1.0, "{:?}", curve);
+
+            let decay: Vec<f32> = (0..200).map(|_| adsr.next()).collect();
+            assert_eq!(decay[199], 0.5, "{:?}", curve);
+            assert_eq!(adsr.stage(), Stage::Sustain);
+            assert!((0..1000).all(|_| adsr.next() == 0.5));
+
+            adsr.gate_off();
+            let release: Vec<f32> = (0..300).map(|_| adsr.next()).collect();
+            assert_eq!(release[299], 0.0, "{:?}", curve);
+            assert!(!adsr.is_active());
+        }
+    }
+
+    #[test]
+    fn retrigger_continues_from_current_level() {
+        for curve in [Curve::Linear, Curve::Exponential] {
+            let mut adsr = envelope(curve);
+            adsr.gate_on();
+            (0..500).for_each(|_| {
+                adsr.next();
+            });
+            adsr.gate_off();
+            let before = (0..50).map(|_| adsr.next()).last().unwrap();
+            adsr.gate_on();
+            let after = adsr.next();
+            assert!(
+                after > before && after - before < 0.05,
+                "{} {}",
+                before,
+                after
+            );
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! A Schroeder allpass for smearing the phase of a signal.
+use alloc::{vec, vec::Vec};
+
+use super::denormal::flush;
+
+/// The largest gain magnitude accepted by [`Allpass::set_gain`],
+/// keeping the filter stable.
+pub const MAX_GAIN: f32 = 0.999;
+
+/// A Schroeder allpass, `y[n] = -g * x[n] + x[n - d] + g * y[n - d]`,
+/// which passes every frequency at unity gain while delaying each by a
+/// different amount.
+#[derive(Debug, Clone)]
+pub struct Allpass {
+    /// The circular buffer of the internal signal, `v[n] = x[n] + g *
+    /// v[n - d]`.
+    buffer: Vec<f32>,
+    /// The index that is written to next.
+    index: usize,
+    /// The delay in samples, within `1..=buffer.len()`.
+    delay: usize,
+    /// The gain of the filter.
+    gain: f32,
+}
+
+impl Allpass {
+    /// Creates a new [`Allpass`] holding up to `max_delay` samples, with
+    /// the delay initially set to the maximum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_delay` is zero.
+    pub fn new(max_delay: usize) -> Self {
+        assert!(max_delay > 0, "max_delay must be non-zero!");
+        Self {
+            buffer: vec![0.0; max_delay],
+            index: 0,
+            delay: max_delay,
+            gain: 0.0,
+        }
+    }
+
+    /// Sets the delay in samples, clamped to `1..=max_delay`.
+    pub fn set_delay(&mut self, samples: usize) {
+        self.delay = samples.clamp(1, self.buffer.len());
+    }
+
+    /// Sets the gain, clamped to `-MAX_GAIN..=MAX_GAIN`, where larger
+    /// magnitudes smear the phase for longer.
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain.clamp(-MAX_GAIN, MAX_GAIN);
+    }
+
+    /// The delay in samples.
+    pub fn delay(&self) -> usize {
+        self.delay
+    }
+
+    /// Fills the allpass with silence.
+    pub fn clear(&mut self) {
+        self.buffer.fill(0.0);
+        self.index = 0;
+    }
+
+    /// Filters a sample.
+    pub fn process(&mut self, x: f32) -> f32 {
+        let len = self.buffer.len();
+        let delayed = self.buffer[(self.index + len - self.delay) % len];
+        let v = flush(x + self.gain * delayed);
+        self.buffer[self.index] = v;
+        self.index = (self.index + 1) % len;
+        delayed - self.gain * v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::Allpass;
+
+    #[test]
+    fn magnitude_is_flat() {
+        let sample_rate = 44100.0;
+        for gain in [0.5, 0.7, -0.6] {
+            for frequency in [50.0, 440.0, 1234.0, 5000.0, 15000.0] {
+                let mut allpass = Allpass::new(347);
+                allpass.set_gain(gain);
+                let (mut re, mut im) = (0.0, 0.0);
+                for index in 0..44100 * 2 {
+                    let phase = TAU * frequency * index as f64 / sample_rate;
+                    let y = allpass.process(phase.sin() as f32) as f64;
+                    if index >= 44100 {
+                        re += y * phase.cos();
+                        im -= y * phase.sin();
+                    }
+                }
+                let magnitude = 2.0 * re.hypot(im) / 44100.0;
+                assert!((magnitude - 1.0).abs() < 0.01, "{}", magnitude);
+            }
+        }
+    }
+
+    #[test]
+    fn impulse_energy_is_preserved() {
+        let mut allpass = Allpass::new(100);
+        allpass.set_delay(37);
+        allpass.set_gain(0.7);
+        let energy: f32 = (0..44100)
+            .map(|index| allpass.process(if index == 0 { 1.0 } else { 0.0 }).powi(2))
+            .sum();
+        assert!((energy - 1.0).abs() < 1e-4, "{}", energy);
+    }
+}
@@ -0,0 +1,324 @@
+//! Second-order filters following the [Audio EQ
+//! Cookbook](https://www.w3.org/TR/audio-eq-cookbook/).
+use core::f64::consts::TAU;
+
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The normalized coefficients of a [`Biquad`], where `a0` is `1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiquadCoefficients {
+    pub b0: f64,
+    pub b1: f64,
+    pub b2: f64,
+    pub a1: f64,
+    pub a2: f64,
+}
+
+/// The intermediate values shared by the cookbook designs.
+struct Design {
+    cos: f64,
+    alpha: f64,
+    /// The square root of the linear gain, `10^(gain_db / 40)`.
+    a: f64,
+}
+
+impl Design {
+    fn new(frequency: f64, q: f64, gain_db: f64, sample_rate: f64) -> Self {
+        let frequency = frequency.clamp(1.0, sample_rate * 0.499);
+        let w0 = TAU * frequency / sample_rate;
+        let q = q.max(f64::EPSILON);
+        Self {
+            cos: w0.cos(),
+            alpha: w0.sin() / (2.0 * q),
+            a: 10.0_f64.powf(gain_db / 40.0),
+        }
+    }
+}
+
+impl BiquadCoefficients {
+    /// Passes the signal through unchanged.
+    pub const IDENTITY: Self = Self {
+        b0: 1.0,
+        b1: 0.0,
+        b2: 0.0,
+        a1: 0.0,
+        a2: 0.0,
+    };
+
+    /// Normalizes raw coefficients by `a0`.
+    fn normalized(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    /// Attenuates content above the `cutoff`.
+    pub fn lowpass(cutoff: f64, q: f64, sample_rate: f64) -> Self {
+        let Design { cos, alpha, .. } = Design::new(cutoff, q, 0.0, sample_rate);
+        let b1 = 1.0 - cos;
+        Self::normalized(b1 / 2.0, b1, b1 / 2.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// Attenuates content below the `cutoff`.
+    pub fn highpass(cutoff: f64, q: f64, sample_rate: f64) -> Self {
+        let Design { cos, alpha, .. } = Design::new(cutoff, q, 0.0, sample_rate);
+        let b0 = (1.0 + cos) / 2.0;
+        Self::normalized(b0, -2.0 * b0, b0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// Keeps content around the `center`, with a peak gain of 0 dB.
+    pub fn bandpass(center: f64, q: f64, sample_rate: f64) -> Self {
+        let Design { cos, alpha, .. } = Design::new(center, q, 0.0, sample_rate);
+        Self::normalized(alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// Removes content around the `center`.
+    pub fn notch(center: f64, q: f64, sample_rate: f64) -> Self {
+        let Design { cos, alpha, .. } = Design::new(center, q, 0.0, sample_rate);
+        Self::normalized(1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// Passes all content at unity gain, shifting the phase by 180
+    /// degrees at the `center`.
+    pub fn allpass(center: f64, q: f64, sample_rate: f64) -> Self {
+        let Design { cos, alpha, .. } = Design::new(center, q, 0.0, sample_rate);
+        Self::normalized(
+            1.0 - alpha,
+            -2.0 * cos,
+            1.0 + alpha,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    /// Boosts or cuts content around the `center` by `gain_db`.
+    pub fn peak(center: f64, q: f64, gain_db: f64, sample_rate: f64) -> Self {
+        let Design { cos, alpha, a } = Design::new(center, q, gain_db, sample_rate);
+        Self::normalized(
+            1.0 + alpha * a,
+            -2.0 * cos,
+            1.0 - alpha * a,
+            1.0 + alpha / a,
+            -2.0 * cos,
+            1.0 - alpha / a,
+        )
+    }
+
+    /// Boosts or cuts content below the `cutoff` by `gain_db`.
+    pub fn lowshelf(cutoff: f64, q: f64, gain_db: f64, sample_rate: f64) -> Self {
+        let Design { cos, alpha, a } = Design::new(cutoff, q, gain_db, sample_rate);
+        let shelf = 2.0 * a.sqrt() * alpha;
+        Self::normalized(
+            a * ((a + 1.0) - (a - 1.0) * cos + shelf),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+            a * ((a + 1.0) - (a - 1.0) * cos - shelf),
+            (a + 1.0) + (a - 1.0) * cos + shelf,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+            (a + 1.0) + (a - 1.0) * cos - shelf,
+        )
+    }
+
+    /// Boosts or cuts content above the `cutoff` by `gain_db`.
+    pub fn highshelf(cutoff: f64, q: f64, gain_db: f64, sample_rate: f64) -> Self {
+        let Design { cos, alpha, a } = Design::new(cutoff, q, gain_db, sample_rate);
+        let shelf = 2.0 * a.sqrt() * alpha;
+        Self::normalized(
+            a * ((a + 1.0) + (a - 1.0) * cos + shelf),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+            a * ((a + 1.0) + (a - 1.0) * cos - shelf),
+            (a + 1.0) - (a - 1.0) * cos + shelf,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos),
+            (a + 1.0) - (a - 1.0) * cos - shelf,
+        )
+    }
+
+    /// Evaluates the linear magnitude response at a `frequency`.
+    pub fn magnitude(&self, frequency: f64, sample_rate: f64) -> f64 {
+        let w = TAU * frequency / sample_rate;
+        let (cos_1, sin_1) = (w.cos(), w.sin());
+        let (cos_2, sin_2) = ((2.0 * w).cos(), (2.0 * w).sin());
+        let numerator_re = self.b0 + self.b1 * cos_1 + self.b2 * cos_2;
+        let numerator_im = -self.b1 * sin_1 - self.b2 * sin_2;
+        let denominator_re = 1.0 + self.a1 * cos_1 + self.a2 * cos_2;
+        let denominator_im = -self.a1 * sin_1 - self.a2 * sin_2;
+        numerator_re.hypot(numerator_im) / denominator_re.hypot(denominator_im)
+    }
+}
+
+impl Default for BiquadCoefficients {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// A second-order filter, processed in Direct Form I.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Biquad {
+    /// The coefficients of the filter.
+    coefficients: BiquadCoefficients,
+    /// The previous two inputs.
+    x: [f64; 2],
+    /// The previous two outputs.
+    y: [f64; 2],
+}
+
+impl Biquad {
+    /// Creates a new [`Biquad`] from its `coefficients`.
+    pub fn new(coefficients: BiquadCoefficients) -> Self {
+        Self {
+            coefficients,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// See [`BiquadCoefficients::lowpass`].
+    pub fn lowpass(cutoff: f64, q: f64, sample_rate: f64) -> Self {
+        Self::new(BiquadCoefficients::lowpass(cutoff, q, sample_rate))
+    }
+
+    /// See [`BiquadCoefficients::highpass`].
+    pub fn highpass(cutoff: f64, q: f64, sample_rate: f64) -> Self {
+        Self::new(BiquadCoefficients::highpass(cutoff, q, sample_rate))
+    }
+
+    /// See [`BiquadCoefficients::bandpass`].
+    pub fn bandpass(center: f64, q: f64, sample_rate: f64) -> Self {
+        Self::new(BiquadCoefficients::bandpass(center, q, sample_rate))
+    }
+
+    /// See [`BiquadCoefficients::notch`].
+    pub fn notch(center: f64, q: f64, sample_rate: f64) -> Self {
+        Self::new(BiquadCoefficients::notch(center, q, sample_rate))
+    }
+
+    /// See [`BiquadCoefficients::allpass`].
+    pub fn allpass(center: f64, q: f64, sample_rate: f64) -> Self {
+        Self::new(BiquadCoefficients::allpass(center, q, sample_rate))
+    }
+
+    /// See [`BiquadCoefficients::peak`].
+    pub fn peak(center: f64, q: f64, gain_db: f64, sample_rate: f64) -> Self {
+        Self::new(BiquadCoefficients::peak(center, q, gain_db, sample_rate))
+    }
+
+    /// See [`BiquadCoefficients::lowshelf`].
+    pub fn lowshelf(cutoff: f64, q: f64, gain_db: f64, sample_rate: f64) -> Self {
+        Self::new(BiquadCoefficients::lowshelf(
+            cutoff,
+            q,
+            gain_db,
+            sample_rate,
+        ))
+    }
+
+    /// See [`BiquadCoefficients::highshelf`].
+    pub fn highshelf(cutoff: f64, q: f64, gain_db: f64, sample_rate: f64) -> Self {
+        Self::new(BiquadCoefficients::highshelf(
+            cutoff,
+            q,
+            gain_db,
+            sample_rate,
+        ))
+    }
+
+    /// The current coefficients of the filter.
+    pub fn coefficients(&self) -> BiquadCoefficients {
+        self.coefficients
+    }
+
+    /// Replaces the coefficients while keeping the filter state, which
+    /// is cheap enough to do once per block during a sweep.
+    pub fn set_coefficients(&mut self, coefficients: BiquadCoefficients) {
+        self.coefficients = coefficients;
+    }
+
+    /// Clears the state of the filter.
+    pub fn reset(&mut self) {
+        self.x = [0.0; 2];
+        self.y = [0.0; 2];
+    }
+
+    /// Filters a sample.
+    pub fn process(&mut self, x: f32) -> f32 {
+        let BiquadCoefficients { b0, b1, b2, a1, a2 } = self.coefficients;
+        let x = x as f64;
+        let y = b0 * x + b1 * self.x[0] + b2 * self.x[1] - a1 * self.y[0] - a2 * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, TAU};
+
+    use super::{Biquad, BiquadCoefficients};
+
+    /// Measures the steady-state gain of a filter for a sine.
+    fn measure_gain(filter: &mut Biquad, frequency: f64, sample_rate: f64) -> f64 {
+        let len = sample_rate as usize;
+        (0..len)
+            .map(|index| {
+                let x = (TAU * frequency * index as f64 / sample_rate).sin();
+                filter.process(x as f32)
+            })
+            .skip(len / 2)
+            .fold(0.0, |peak: f64, sample| peak.max(sample.abs() as f64))
+    }
+
+    #[test]
+    fn lowpass_cutoff_is_3db() {
+        let mut lowpass = Biquad::lowpass(1000.0, FRAC_1_SQRT_2, 44100.0);
+        let gain_db = 20.0 * measure_gain(&mut lowpass, 1000.0, 44100.0).log10();
+        assert!((gain_db + 3.0).abs() < 0.1, "{}", gain_db);
+    }
+
+    #[test]
+    fn designs_match_their_gains() {
+        let sample_rate = 44100.0;
+        let cases = [
+            (
+                BiquadCoefficients::highpass(1000.0, 0.7, sample_rate),
+                20.0,
+                0.0,
+            ),
+            (
+                BiquadCoefficients::bandpass(1000.0, 2.0, sample_rate),
+                1000.0,
+                1.0,
+            ),
+            (
+                BiquadCoefficients::notch(1000.0, 2.0, sample_rate),
+                1000.0,
+                0.0,
+            ),
+            (
+                BiquadCoefficients::peak(1000.0, 1.0, 6.0, sample_rate),
+                1000.0,
+                1.995,
+            ),
+            (
+                BiquadCoefficients::lowshelf(200.0, 0.7, -6.0, sample_rate),
+                20.0,
+                0.501,
+            ),
+            (
+                BiquadCoefficients::highshelf(5000.0, 0.7, 6.0, sample_rate),
+                20000.0,
+                1.995,
+            ),
+        ];
+        for (coefficients, frequency, expected) in cases {
+            let magnitude = coefficients.magnitude(frequency, sample_rate);
+            assert!((magnitude - expected).abs() < 0.02, "{:?}", coefficients);
+        }
+    }
+}
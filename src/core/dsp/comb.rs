@@ -0,0 +1,152 @@
+//! A comb filter adding a delayed copy of a signal to itself.
+use alloc::{vec, vec::Vec};
+
+use super::denormal::flush;
+
+/// The largest feedback magnitude accepted by [`Comb::set_feedback`],
+/// keeping the feedback variant stable.
+pub const MAX_FEEDBACK: f32 = 0.999;
+
+/// The topology of a [`Comb`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CombKind {
+    /// Adds the delayed input, `y[n] = x[n] + g * x[n - d]`, producing a
+    /// single echo.
+    FeedForward,
+    /// Adds the delayed output, `y[n] = x[n] + g * y[n - d]`, producing
+    /// a decaying train of echoes.
+    #[default]
+    Feedback,
+}
+
+/// A comb filter with an optional one-pole low-pass in its delayed
+/// path, as used by Freeverb to darken the tail.
+#[derive(Debug, Clone)]
+pub struct Comb {
+    /// The topology of the filter.
+    kind: CombKind,
+    /// The circular buffer of the delayed signal.
+    buffer: Vec<f32>,
+    /// The index that is written to next.
+    index: usize,
+    /// The delay in samples, within `1..=buffer.len()`.
+    delay: usize,
+    /// The gain of the delayed path.
+    feedback: f32,
+    /// The low-pass coefficient of the delayed path, where `0.0` leaves
+    /// it unfiltered.
+    damping: f32,
+    /// The previous output of the low-pass.
+    filter_state: f32,
+}
+
+impl Comb {
+    /// Creates a new [`Comb`] holding up to `max_delay` samples, with
+    /// the delay initially set to the maximum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_delay` is zero.
+    pub fn new(kind: CombKind, max_delay: usize) -> Self {
+        assert!(max_delay > 0, "max_delay must be non-zero!");
+        Self {
+            kind,
+            buffer: vec![0.0; max_delay],
+            index: 0,
+            delay: max_delay,
+            feedback: 0.0,
+            damping: 0.0,
+            filter_state: 0.0,
+        }
+    }
+
+    /// Sets the delay in samples, clamped to `1..=max_delay`.
+    pub fn set_delay(&mut self, samples: usize) {
+        self.delay = samples.clamp(1, self.buffer.len());
+    }
+
+    /// Sets the gain of the delayed path, clamped to
+    /// `-MAX_FEEDBACK..=MAX_FEEDBACK`.
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(-MAX_FEEDBACK, MAX_FEEDBACK);
+    }
+
+    /// Sets the low-pass in the delayed path, clamped to `0.0..=1.0`,
+    /// where higher values darken each echo more than the last.
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.clamp(0.0, 1.0);
+    }
+
+    /// The delay in samples.
+    pub fn delay(&self) -> usize {
+        self.delay
+    }
+
+    /// Fills the comb with silence.
+    pub fn clear(&mut self) {
+        self.buffer.fill(0.0);
+        self.index = 0;
+        self.filter_state = 0.0;
+    }
+
+    /// Filters a sample.
+    pub fn process(&mut self, x: f32) -> f32 {
+        let len = self.buffer.len();
+        let delayed = self.buffer[(self.index + len - self.delay) % len];
+        self.filter_state =
+            flush(delayed * (1.0 - self.damping) + self.filter_state * self.damping);
+        let y = x + self.filter_state * self.feedback;
+        self.buffer[self.index] = match self.kind {
+            CombKind::FeedForward => x,
+            CombKind::Feedback => flush(y),
+        };
+        self.index = (self.index + 1) % len;
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Comb, CombKind, MAX_FEEDBACK};
+
+    fn impulse_response(comb: &mut Comb, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|index| comb.process(if index == 0 { 1.0 } else { 0.0 }))
+            .collect()
+    }
+
+    #[test]
+    fn feedback_echoes_decay() {
+        let mut comb = Comb::new(CombKind::Feedback, 100);
+        comb.set_delay(30);
+        comb.set_feedback(0.5);
+        let response = impulse_response(&mut comb, 100);
+        for (index, sample) in response.iter().enumerate() {
+            let expected = if index % 30 == 0 {
+                0.5_f32.powi(index as i32 / 30)
+            } else {
+                0.0
+            };
+            assert_eq!(*sample, expected);
+        }
+
+        comb.set_feedback(2.0);
+        comb.set_damping(0.5);
+        comb.clear();
+        let response = impulse_response(&mut comb, 100_000);
+        assert!(response[99_000..].iter().all(|sample| sample.abs() < 1.0));
+        comb.set_feedback(-2.0);
+        assert!(comb.feedback >= -MAX_FEEDBACK);
+    }
+
+    #[test]
+    fn feedforward_echoes_once() {
+        let mut comb = Comb::new(CombKind::FeedForward, 50);
+        comb.set_feedback(-0.5);
+        let response = impulse_response(&mut comb, 200);
+        assert_eq!(response[0], 1.0);
+        assert_eq!(response[50], -0.5);
+        let echoes = response.iter().filter(|&&sample| sample != 0.0).count();
+        assert_eq!(echoes, 2);
+    }
+}
@@ -0,0 +1,102 @@
+//! Splits a signal into a low and a high band that sum back to an
+//! allpass.
+use core::f64::consts::FRAC_1_SQRT_2;
+
+use super::Biquad;
+
+/// A 4th-order Linkwitz-Riley low-pass or high-pass, i.e. two cascaded
+/// Butterworth filters.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinkwitzRiley([Biquad; 2]);
+
+impl LinkwitzRiley {
+    /// Creates a new low-pass [`LinkwitzRiley`] at a `frequency` in Hz.
+    pub fn lowpass(frequency: f64, sample_rate: f64) -> Self {
+        Self([Biquad::lowpass(frequency, FRAC_1_SQRT_2, sample_rate); 2])
+    }
+
+    /// Creates a new high-pass [`LinkwitzRiley`] at a `frequency` in Hz.
+    pub fn highpass(frequency: f64, sample_rate: f64) -> Self {
+        Self([Biquad::highpass(frequency, FRAC_1_SQRT_2, sample_rate); 2])
+    }
+
+    /// Copies the coefficients of `other`, keeping the current state.
+    pub fn set_coefficients(&mut self, other: &LinkwitzRiley) {
+        for (filter, other) in self.0.iter_mut().zip(other.0.iter()) {
+            filter.set_coefficients(other.coefficients());
+        }
+    }
+
+    /// Clears the state of the filters.
+    pub fn reset(&mut self) {
+        self.0.iter_mut().for_each(Biquad::reset);
+    }
+
+    /// Filters a sample.
+    pub fn process(&mut self, x: f32) -> f32 {
+        let [first, second] = &mut self.0;
+        second.process(first.process(x))
+    }
+}
+
+/// A pair of [`LinkwitzRiley`] filters at the same frequency, whose
+/// outputs sum to a flat magnitude response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Crossover {
+    /// The filter producing the low band.
+    low: LinkwitzRiley,
+    /// The filter producing the high band.
+    high: LinkwitzRiley,
+}
+
+impl Crossover {
+    /// Creates a new [`Crossover`] at a `frequency` in Hz.
+    pub fn new(frequency: f64, sample_rate: f64) -> Self {
+        Self {
+            low: LinkwitzRiley::lowpass(frequency, sample_rate),
+            high: LinkwitzRiley::highpass(frequency, sample_rate),
+        }
+    }
+
+    /// Moves the crossover to a `frequency` in Hz, keeping the current
+    /// state.
+    pub fn set_frequency(&mut self, frequency: f64, sample_rate: f64) {
+        let tuned = Self::new(frequency, sample_rate);
+        self.low.set_coefficients(&tuned.low);
+        self.high.set_coefficients(&tuned.high);
+    }
+
+    /// Clears the state of the filters.
+    pub fn reset(&mut self) {
+        self.low.reset();
+        self.high.reset();
+    }
+
+    /// Splits a sample into its low and high bands.
+    pub fn split(&mut self, x: f32) -> (f32, f32) {
+        (self.low.process(x), self.high.process(x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::Crossover;
+
+    #[test]
+    fn bands_sum_flat() {
+        for frequency in [50.0, 500.0, 1000.0, 2000.0, 10000.0] {
+            let mut crossover = Crossover::new(1000.0, 44100.0);
+            let peak = (0..44100)
+                .map(|index| {
+                    let (low, high) =
+                        crossover.split((TAU * frequency * index as f32 / 44100.0).sin());
+                    low + high
+                })
+                .skip(22050)
+                .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+            assert!((peak - 1.0).abs() < 0.01, "{} {}", frequency, peak);
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Conversions between decibels and linear gain.
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The level reported for silence, in dB.
+pub const SILENCE_DB: f32 = -144.0;
+
+/// Converts a level in dB to a linear gain.
+pub fn db_to_gain(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
+
+/// Converts a linear gain to a level in dB, bottoming out at
+/// [`SILENCE_DB`].
+pub fn gain_to_db(gain: f32) -> f32 {
+    if gain <= 0.0 {
+        SILENCE_DB
+    } else {
+        (20.0 * gain.log10()).max(SILENCE_DB)
+    }
+}
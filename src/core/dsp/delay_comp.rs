@@ -0,0 +1,72 @@
+//! Delays a signal by a whole number of samples, such that it lines up
+//! with a copy that went through an effect with latency.
+use super::DelayLine;
+
+/// A mono delay of a fixed number of samples.
+#[derive(Debug, Clone)]
+pub struct DelayCompensator {
+    /// The delayed samples, holding one more than the `delay`.
+    line: DelayLine,
+    /// The delay in samples.
+    delay: usize,
+}
+
+impl DelayCompensator {
+    /// Creates a new [`DelayCompensator`] delaying by `delay` samples.
+    pub fn new(delay: usize) -> Self {
+        Self {
+            line: DelayLine::new(delay + 1),
+            delay,
+        }
+    }
+
+    /// The delay in samples.
+    pub fn delay(&self) -> usize {
+        self.delay
+    }
+
+    /// Sets the delay in samples, clearing the delayed samples and only
+    /// reallocating if it grows.
+    pub fn set_delay(&mut self, delay: usize) {
+        if delay + 1 > self.line.len() {
+            self.line = DelayLine::new(delay + 1);
+        } else {
+            self.line.clear();
+        }
+        self.delay = delay;
+    }
+
+    /// Fills the delay with silence.
+    pub fn reset(&mut self) {
+        self.line.clear();
+    }
+
+    /// Pushes a sample in, returning the one pushed `delay` samples ago.
+    pub fn process(&mut self, x: f32) -> f32 {
+        self.line.write(x);
+        self.line.tap(self.delay as isize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DelayCompensator;
+
+    #[test]
+    fn impulse_is_delayed_exactly() {
+        for delay in [0, 1, 17] {
+            let mut compensator = DelayCompensator::new(delay);
+            let output: Vec<f32> = (0..64)
+                .map(|index| compensator.process(if index == 0 { 1.0 } else { 0.0 }))
+                .collect();
+            assert_eq!(output.iter().position(|&x| x == 1.0), Some(delay));
+            assert_eq!(output.iter().sum::<f32>(), 1.0);
+        }
+
+        let mut compensator = DelayCompensator::new(32);
+        compensator.process(1.0);
+        compensator.set_delay(3);
+        let output: Vec<f32> = (0..8).map(|_| compensator.process(0.5)).collect();
+        assert_eq!(output, [0.0, 0.0, 0.0, 0.5, 0.5, 0.5, 0.5, 0.5]);
+    }
+}
@@ -0,0 +1,136 @@
+//! A circular buffer of samples that can be read at fractional delays.
+
+use alloc::{vec, vec::Vec};
+
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// A mono delay line supporting interpolated reads.
+#[derive(Debug, Clone)]
+pub struct DelayLine {
+    /// The circular buffer of samples.
+    buffer: Vec<f32>,
+    /// The index that is written to next.
+    write_index: usize,
+}
+
+impl DelayLine {
+    /// Creates a new [`DelayLine`] holding up to `len` samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero.
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "len must be non-zero!");
+        Self {
+            buffer: vec![0.0; len],
+            write_index: 0,
+        }
+    }
+
+    /// The number of samples held by the delay line.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Always `false`, as delay lines hold at least one sample.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Fills the delay line with silence.
+    pub fn clear(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_index = 0;
+    }
+
+    /// Pushes a `sample` onto the delay line, overwriting the oldest.
+    pub fn write(&mut self, sample: f32) {
+        self.buffer[self.write_index] = sample;
+        self.write_index = (self.write_index + 1) % self.buffer.len();
+    }
+
+    /// Reads the sample written `delay` samples ago, where a `delay` of
+    /// `0` is the most recently written sample.
+    ///
+    /// Delays outside of the delay line are clamped to its bounds.
+    pub fn tap(&self, delay: isize) -> f32 {
+        let len = self.buffer.len();
+        let delay = delay.clamp(0, len as isize - 1) as usize;
+        self.buffer[(self.write_index + len - 1 - delay) % len]
+    }
+
+    /// Reads at a fractional `delay` using linear interpolation between
+    /// the adjacent taps.
+    pub fn read(&self, delay: f32) -> f32 {
+        let (index, fraction) = split(delay);
+        let a = self.tap(index);
+        let b = self.tap(index + 1);
+        a + (b - a) * fraction
+    }
+
+    /// Reads at a fractional `delay` using 4-point Catmull-Rom
+    /// interpolation, which is smoother than [`read`] for modulated
+    /// delays.
+    ///
+    /// Delays below `1.0` have no newer sample to interpolate with, so
+    /// that tap is linearly extrapolated instead.
+    ///
+    /// [`read`]: Self::read
+    pub fn read_cubic(&self, delay: f32) -> f32 {
+        let (index, t) = split(delay);
+        let y1 = self.tap(index);
+        let y2 = self.tap(index + 1);
+        let y0 = if index > 0 {
+            self.tap(index - 1)
+        } else {
+            2.0 * y1 - y2
+        };
+        let y3 = self.tap(index + 2);
+        y1 + 0.5
+            * t
+            * (y2 - y0
+                + t * (2.0 * y0 - 5.0 * y1 + 4.0 * y2 - y3 + t * (3.0 * (y1 - y2) + y3 - y0)))
+    }
+}
+
+/// Splits a non-negative `delay` into its integer and fractional parts.
+fn split(delay: f32) -> (isize, f32) {
+    let delay = delay.max(0.0);
+    let index = delay.floor();
+    (index as isize, delay - index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DelayLine;
+
+    #[test]
+    fn interpolated_ramp() {
+        let mut delay_line = DelayLine::new(32);
+        // Wrap around the delay line a few times.
+        for index in 0..100 {
+            delay_line.write(index as f32);
+        }
+        for step in 0..80 {
+            let delay = step as f32 * 0.37;
+            let expected = 99.0 - delay;
+            assert!((delay_line.read(delay) - expected).abs() < 1e-4);
+            assert!((delay_line.read_cubic(delay) - expected).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn cubic_sine() {
+        let frequency = 0.05;
+        let mut delay_line = DelayLine::new(64);
+        for index in 0..64 {
+            delay_line.write((index as f32 * frequency).sin());
+        }
+        for step in 1..100 {
+            let delay = step as f32 * 0.5;
+            let expected = ((63.0 - delay) * frequency).sin();
+            assert!((delay_line.read_cubic(delay) - expected).abs() < 1e-4);
+        }
+    }
+}
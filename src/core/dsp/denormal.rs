@@ -0,0 +1,36 @@
+//! Keeps feedback paths out of the subnormal range.
+//!
+//! # Overview
+//!
+//! A decaying feedback loop, such as the tail of a reverb, eventually
+//! produces values too small for the normal floating-point range. Many
+//! CPUs handle these subnormals far slower than normal values, so a
+//! silent tail can cost more than a loud one. Flushing them to zero
+//! where they are written back into a feedback path stops the decay
+//! from ever reaching that range.
+
+/// Flushes a subnormal `x` to zero, leaving other values unchanged.
+#[inline]
+pub fn flush(x: f32) -> f32 {
+    if x.abs() < f32::MIN_POSITIVE {
+        0.0
+    } else {
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::flush;
+
+    #[test]
+    fn subnormals_are_zeroed() {
+        let subnormal = f32::MIN_POSITIVE / 2.0;
+        assert!(subnormal.is_subnormal());
+        assert_eq!(flush(subnormal), 0.0);
+        assert_eq!(flush(-subnormal), 0.0);
+        for x in [0.0, f32::MIN_POSITIVE, -1e-30, 0.5, f32::MAX] {
+            assert_eq!(flush(x), x);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! Tracks the level of a signal over time.
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// Converts a time constant into a per-sample smoothing coefficient,
+/// such that a one-pole smoother reaches 63% of a step within `ms`.
+pub fn time_coefficient(ms: f32, sample_rate: f64) -> f32 {
+    let samples = ms as f64 * 0.001 * sample_rate;
+    if samples <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / samples).exp() as f32
+    }
+}
+
+/// The level measured by the [`EnvelopeFollower`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Smooths the absolute value of the signal.
+    #[default]
+    Peak,
+    /// Smooths the square of the signal, reporting its square root.
+    Rms,
+}
+
+/// A level detector with separate attack and release times.
+#[derive(Debug, Clone)]
+pub struct EnvelopeFollower {
+    /// The level being measured.
+    mode: Mode,
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The smoothing coefficient for rising levels.
+    attack: f32,
+    /// The smoothing coefficient for falling levels.
+    release: f32,
+    /// The smoothed level, squared in [`Mode::Rms`].
+    state: f32,
+}
+
+impl EnvelopeFollower {
+    /// Creates a new [`EnvelopeFollower`] that reacts instantly until
+    /// attack and release times are set.
+    pub fn new(mode: Mode, sample_rate: f64) -> Self {
+        Self {
+            mode,
+            sample_rate,
+            attack: 0.0,
+            release: 0.0,
+            state: 0.0,
+        }
+    }
+
+    /// Sets the time taken to react to rising levels.
+    pub fn set_attack_ms(&mut self, ms: f32) {
+        self.attack = time_coefficient(ms, self.sample_rate);
+    }
+
+    /// Sets the time taken to react to falling levels.
+    pub fn set_release_ms(&mut self, ms: f32) {
+        self.release = time_coefficient(ms, self.sample_rate);
+    }
+
+    /// Clears the measured level.
+    pub fn reset(&mut self) {
+        self.state = 0.0;
+    }
+
+    /// The current envelope.
+    pub fn value(&self) -> f32 {
+        match self.mode {
+            Mode::Peak => self.state,
+            Mode::Rms => self.state.sqrt(),
+        }
+    }
+
+    /// Feeds a sample to the detector, returning the updated envelope.
+    pub fn process(&mut self, x: f32) -> f32 {
+        self.process_with_release(x, self.release)
+    }
+
+    /// Feeds a sample to the detector like [`process`], but falling with
+    /// a `release` coefficient from [`time_coefficient`] in place of the
+    /// release time, such that it can vary from sample to sample.
+    ///
+    /// [`process`]: Self::process
+    pub fn process_with_release(&mut self, x: f32, release: f32) -> f32 {
+        let level = match self.mode {
+            Mode::Peak => x.abs(),
+            Mode::Rms => x * x,
+        };
+        let coefficient = if level > self.state {
+            self.attack
+        } else {
+            release
+        };
+        self.state = coefficient * self.state + (1.0 - coefficient) * level;
+        self.value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnvelopeFollower, Mode};
+
+    #[test]
+    fn attack_time_constant() {
+        let sample_rate = 44100.0;
+        let attack_ms = 10.0;
+        let mut follower = EnvelopeFollower::new(Mode::Peak, sample_rate);
+        follower.set_attack_ms(attack_ms);
+        follower.set_release_ms(100.0);
+
+        let samples = (0..44100)
+            .position(|_| follower.process(1.0) >= 1.0 - (-1.0_f32).exp())
+            .unwrap();
+        let expected = (attack_ms as f64 * 0.001 * sample_rate) as usize;
+        assert!(samples.abs_diff(expected) <= 1, "{}", samples);
+    }
+
+    #[test]
+    fn rms_of_square_wave() {
+        let mut follower = EnvelopeFollower::new(Mode::Rms, 44100.0);
+        follower.set_attack_ms(50.0);
+        follower.set_release_ms(50.0);
+        for index in 0..44100 {
+            follower.process(if index % 2 == 0 { 0.5 } else { -0.5 });
+        }
+        assert!((follower.value() - 0.5).abs() < 1e-3);
+    }
+}
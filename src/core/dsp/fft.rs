@@ -0,0 +1,272 @@
+//! A radix-2 fast Fourier transform along with the windows used for
+//! spectral processing.
+use alloc::vec::Vec;
+use core::f64::consts::TAU;
+use core::ops::{Add, AddAssign, Mul, Sub};
+
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// A complex number, i.e. a single bin of a spectrum.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Complex<T> {
+    /// The real part.
+    pub re: T,
+    /// The imaginary part.
+    pub im: T,
+}
+
+impl Complex<f32> {
+    /// The complex number `0 + 0i`.
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+
+    /// Creates a new [`Complex`].
+    pub const fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    /// Creates a [`Complex`] from its magnitude and phase in radians.
+    pub fn from_polar(norm: f32, phase: f32) -> Self {
+        Self::new(norm * phase.cos(), norm * phase.sin())
+    }
+
+    /// The magnitude.
+    pub fn norm(self) -> f32 {
+        self.re.hypot(self.im)
+    }
+
+    /// The phase in radians, in `-PI..=PI`.
+    pub fn arg(self) -> f32 {
+        self.im.atan2(self.re)
+    }
+
+    /// The complex conjugate.
+    pub fn conj(self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+
+    /// Scales both parts by `factor`.
+    pub fn scale(self, factor: f32) -> Self {
+        Self::new(self.re * factor, self.im * factor)
+    }
+}
+
+impl Add for Complex<f32> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl AddAssign for Complex<f32> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Complex<f32> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex<f32> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+/// A complex FFT of a fixed, power of two size, with its twiddle
+/// factors computed up front such that transforms do not allocate.
+#[derive(Debug, Clone)]
+pub struct Fft {
+    /// The twiddle factors for the forward transform.
+    twiddles: Vec<Complex<f32>>,
+    /// The bit-reversed index of each bin.
+    reversed: Vec<usize>,
+}
+
+impl Fft {
+    /// Creates a new [`Fft`] transforming `size` bins at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is not a power of two, as only radix-2
+    /// transforms are supported.
+    pub fn new(size: usize) -> Self {
+        assert!(size.is_power_of_two(), "size must be a power of two!");
+        let bits = size.trailing_zeros();
+        let twiddles = (0..size / 2)
+            .map(|index| {
+                let phase = -TAU * index as f64 / size as f64;
+                Complex::new(phase.cos() as f32, phase.sin() as f32)
+            })
+            .collect();
+        let reversed = (0..size)
+            .map(|index| match bits {
+                0 => 0,
+                _ => index.reverse_bits() >> (usize::BITS - bits),
+            })
+            .collect();
+        Self { twiddles, reversed }
+    }
+
+    /// The number of bins transformed at a time.
+    pub fn size(&self) -> usize {
+        self.reversed.len()
+    }
+
+    /// Transforms the samples in `buffer` into their spectrum in-place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `buffer` is not the [`size`].
+    ///
+    /// [`size`]: Self::size
+    pub fn forward(&self, buffer: &mut [Complex<f32>]) {
+        self.transform(buffer, false);
+    }
+
+    /// Transforms the spectrum in `buffer` back into samples in-place,
+    /// scaling by the [`size`] such that it undoes [`forward`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `buffer` is not the [`size`].
+    ///
+    /// [`size`]: Self::size
+    /// [`forward`]: Self::forward
+    pub fn inverse(&self, buffer: &mut [Complex<f32>]) {
+        self.transform(buffer, true);
+        let scale = 1.0 / buffer.len() as f32;
+        buffer.iter_mut().for_each(|bin| *bin = bin.scale(scale));
+    }
+
+    /// Runs the iterative Cooley-Tukey butterflies over `buffer`.
+    fn transform(&self, buffer: &mut [Complex<f32>], inverse: bool) {
+        let size = self.size();
+        assert_eq!(buffer.len(), size, "buffer must match the size!");
+        for (index, &reversed) in self.reversed.iter().enumerate() {
+            if index < reversed {
+                buffer.swap(index, reversed);
+            }
+        }
+        let mut len = 2;
+        while len <= size {
+            let stride = size / len;
+            for start in (0..size).step_by(len) {
+                for k in 0..len / 2 {
+                    let mut twiddle = self.twiddles[k * stride];
+                    if inverse {
+                        twiddle = twiddle.conj();
+                    }
+                    let (a, b) = (start + k, start + k + len / 2);
+                    let t = buffer[b] * twiddle;
+                    buffer[b] = buffer[a] - t;
+                    buffer[a] += t;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
+
+/// Fills `window` with a generalized cosine window with coefficients
+/// `a`, which is periodic such that overlapping copies sum to a
+/// constant.
+fn cosine_window(window: &mut [f32], a: [f64; 3]) {
+    let len = window.len() as f64;
+    for (index, x) in window.iter_mut().enumerate() {
+        let phase = TAU * index as f64 / len;
+        *x = (a[0] - a[1] * phase.cos() + a[2] * (2.0 * phase).cos()) as f32;
+    }
+}
+
+/// Fills `window` with a periodic Hann window, which overlaps to a
+/// constant at 50% and 75% overlap.
+pub fn hann(window: &mut [f32]) {
+    cosine_window(window, [0.5, 0.5, 0.0]);
+}
+
+/// Fills `window` with a periodic Hamming window, which has lower
+/// sidelobes than [`hann`] but does not reach zero at its edges.
+pub fn hamming(window: &mut [f32]) {
+    cosine_window(window, [0.54, 0.46, 0.0]);
+}
+
+/// Fills `window` with a periodic Blackman window, which trades a wider
+/// main lobe for much lower sidelobes.
+pub fn blackman(window: &mut [f32]) {
+    cosine_window(window, [0.42, 0.5, 0.08]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blackman, hamming, hann, Complex, Fft};
+
+    #[test]
+    fn sine_lands_in_one_bin() {
+        let fft = Fft::new(256);
+        let mut buffer: Vec<Complex<f32>> = (0..256)
+            .map(|index| {
+                let phase = core::f32::consts::TAU * 8.0 * index as f32 / 256.0;
+                Complex::new(phase.sin(), 0.0)
+            })
+            .collect();
+        fft.forward(&mut buffer);
+        for (bin, value) in buffer.iter().enumerate() {
+            if bin == 8 || bin == 256 - 8 {
+                assert!((value.norm() - 128.0).abs() < 1e-3, "{}", value.norm());
+            } else {
+                assert!(value.norm() < 1e-3, "{} {}", bin, value.norm());
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_round_trips() {
+        let fft = Fft::new(64);
+        let input: Vec<Complex<f32>> = (0..64)
+            .map(|index| Complex::new((index as f32 * 0.3).sin(), (index as f32 * 0.7).cos()))
+            .collect();
+        let mut buffer = input.clone();
+        fft.forward(&mut buffer);
+        fft.inverse(&mut buffer);
+        for (x, y) in buffer.iter().zip(input.iter()) {
+            assert!((*x - *y).norm() < 1e-5);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_non_power_of_two() {
+        Fft::new(100);
+    }
+
+    #[test]
+    fn hann_overlaps_to_constant() {
+        let mut window = [0.0; 64];
+        hann(&mut window);
+        for index in 0..32 {
+            assert!((window[index] + window[index + 32] - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn windows_peak_at_center() {
+        for fill in [hann, hamming, blackman] {
+            let mut window = [0.0; 64];
+            fill(&mut window);
+            assert!((window[32] - 1.0).abs() < 1e-6);
+            assert!(window.iter().all(|&x| (-1e-6..=1.0 + 1e-6).contains(&x)));
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Linear-phase FIR filters for changing the rate of a signal by an
+//! integer factor.
+use alloc::{vec, vec::Vec};
+use core::f64::consts::PI;
+
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The number of taps on each side of the center of a [`lowpass`]
+/// filter, per unit of the rate change factor.
+///
+/// [`lowpass`]: lowpass_taps
+pub const TAPS_PER_SIDE: usize = 16;
+
+/// Designs a Blackman-windowed sinc low-pass with `len` taps, where the
+/// `cutoff` is relative to the sample rate, i.e. `0.5` is Nyquist.
+///
+/// The taps are normalized to a gain of `1.0` at DC.
+pub fn lowpass_taps(len: usize, cutoff: f64) -> Vec<f32> {
+    let center = (len as f64 - 1.0) / 2.0;
+    let mut taps: Vec<f64> = (0..len)
+        .map(|index| {
+            let x = index as f64 - center;
+            let sinc = if x == 0.0 {
+                2.0 * cutoff
+            } else {
+                (2.0 * PI * cutoff * x).sin() / (PI * x)
+            };
+            let phase = 2.0 * PI * index as f64 / (len as f64 - 1.0).max(1.0);
+            let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+            sinc * window
+        })
+        .collect();
+    let sum: f64 = taps.iter().sum();
+    taps.iter_mut().for_each(|tap| *tap /= sum);
+    taps.into_iter().map(|tap| tap as f32).collect()
+}
+
+/// Designs the anti-imaging/anti-aliasing filter for a rate change by
+/// `factor`, with a delay of [`TAPS_PER_SIDE`] samples at the lower
+/// rate.
+fn resampling_taps(factor: usize) -> Vec<f32> {
+    lowpass_taps(2 * TAPS_PER_SIDE * factor + 1, 0.45 / factor as f64)
+}
+
+/// Raises the sample rate of a signal by an integer factor.
+#[derive(Debug, Clone)]
+pub struct Upsampler {
+    /// The rate change factor.
+    factor: usize,
+    /// The taps of the interpolation filter.
+    taps: Vec<f32>,
+    /// The most recent input samples, newest at `index`.
+    history: Vec<f32>,
+    /// The position of the newest input sample.
+    index: usize,
+}
+
+impl Upsampler {
+    /// Creates a new [`Upsampler`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `factor` is zero.
+    pub fn new(factor: usize) -> Self {
+        assert!(factor > 0, "factor must be non-zero!");
+        let taps = resampling_taps(factor);
+        let history = vec![0.0; taps.len().div_ceil(factor)];
+        Self {
+            factor,
+            taps,
+            history,
+            index: 0,
+        }
+    }
+
+    /// The delay introduced by the filter, in samples at the lower rate.
+    pub fn latency_samples(&self) -> usize {
+        TAPS_PER_SIDE
+    }
+
+    /// Clears the state of the filter.
+    pub fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.index = 0;
+    }
+
+    /// Pushes an input sample `x`, writing `factor` samples to `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `out` is shorter than the `factor`.
+    pub fn process(&mut self, x: f32, out: &mut [f32]) {
+        let len = self.history.len();
+        self.index = (self.index + 1) % len;
+        self.history[self.index] = x;
+        for (phase, out) in out[..self.factor].iter_mut().enumerate() {
+            let mut sum = 0.0;
+            for (delay, tap) in self.taps[phase..].iter().step_by(self.factor).enumerate() {
+                sum += tap * self.history[(self.index + len - delay) % len];
+            }
+            *out = sum * self.factor as f32;
+        }
+    }
+}
+
+/// Lowers the sample rate of a signal by an integer factor.
+#[derive(Debug, Clone)]
+pub struct Downsampler {
+    /// The rate change factor.
+    factor: usize,
+    /// The taps of the decimation filter.
+    taps: Vec<f32>,
+    /// The most recent input samples, newest at `index`.
+    history: Vec<f32>,
+    /// The position of the newest input sample.
+    index: usize,
+}
+
+impl Downsampler {
+    /// Creates a new [`Downsampler`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `factor` is zero.
+    pub fn new(factor: usize) -> Self {
+        assert!(factor > 0, "factor must be non-zero!");
+        let taps = resampling_taps(factor);
+        let history = vec![0.0; taps.len()];
+        Self {
+            factor,
+            taps,
+            history,
+            index: 0,
+        }
+    }
+
+    /// The delay introduced by the filter, in samples at the lower rate.
+    pub fn latency_samples(&self) -> usize {
+        TAPS_PER_SIDE
+    }
+
+    /// Clears the state of the filter.
+    pub fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.index = 0;
+    }
+
+    /// Pushes `factor` samples from `input`, returning one output sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is shorter than the `factor`.
+    pub fn process(&mut self, input: &[f32]) -> f32 {
+        let len = self.history.len();
+        // The output is aligned with the first sample of the group.
+        self.push(input[0]);
+        let output = self
+            .taps
+            .iter()
+            .enumerate()
+            .map(|(delay, tap)| tap * self.history[(self.index + len - delay) % len])
+            .sum();
+        for &x in &input[1..self.factor] {
+            self.push(x);
+        }
+        output
+    }
+
+    /// Pushes a sample onto the history.
+    fn push(&mut self, x: f32) {
+        self.index = (self.index + 1) % self.history.len();
+        self.history[self.index] = x;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Downsampler, Upsampler, TAPS_PER_SIDE};
+
+    #[test]
+    fn round_trip_is_delayed() {
+        for factor in [2, 4] {
+            let mut upsampler = Upsampler::new(factor);
+            let mut downsampler = Downsampler::new(factor);
+            let input: Vec<f32> = (0..512).map(|index| (index as f32 * 0.05).sin()).collect();
+            let mut high = vec![0.0; factor];
+            let output: Vec<f32> = input
+                .iter()
+                .map(|&x| {
+                    upsampler.process(x, &mut high);
+                    downsampler.process(&high)
+                })
+                .collect();
+            let latency = 2 * TAPS_PER_SIDE;
+            for (out, expected) in output[latency..].iter().zip(input.iter()) {
+                assert!((out - expected).abs() < 1e-2);
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! A low-frequency oscillator for modulating effect parameters.
+use core::f64::consts::TAU;
+
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The shape traced by the [`Lfo`] over a cycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Waveform {
+    /// A sine starting at `0.0`.
+    #[default]
+    Sine,
+    /// A triangle aligned with [`Waveform::Sine`], starting at `0.0`
+    /// and peaking at a quarter of the cycle.
+    Triangle,
+    /// A rising ramp from `-1.0` to `1.0`.
+    Saw,
+    /// Sits at `1.0` for the first half of the cycle and `-1.0` for the
+    /// second.
+    Square,
+}
+
+impl Waveform {
+    /// Evaluates the waveform at a `phase` in `[0, 1)`.
+    pub fn evaluate(&self, phase: f64) -> f32 {
+        let value = match self {
+            Waveform::Sine => (TAU * phase).sin(),
+            Waveform::Triangle => 4.0 * ((phase + 0.75).fract() - 0.5).abs() - 1.0,
+            Waveform::Saw => 2.0 * phase - 1.0,
+            Waveform::Square => {
+                if phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        };
+        value as f32
+    }
+}
+
+/// A phase-accumulating oscillator producing values in `-1.0..=1.0`.
+#[derive(Debug, Clone)]
+pub struct Lfo {
+    /// The shape of the oscillator.
+    waveform: Waveform,
+    /// The current phase, kept in `[0, 1)`.
+    phase: f64,
+    /// The phase advanced per sample.
+    increment: f64,
+    /// The sample rate used to derive the increment.
+    sample_rate: f64,
+}
+
+impl Lfo {
+    /// Creates a new [`Lfo`] running at `frequency` Hz.
+    pub fn new(waveform: Waveform, frequency: f64, sample_rate: f64) -> Self {
+        Self {
+            waveform,
+            phase: 0.0,
+            increment: frequency / sample_rate,
+            sample_rate,
+        }
+    }
+
+    /// Sets the frequency of the oscillator in Hz.
+    pub fn set_frequency(&mut self, frequency: f64) {
+        self.increment = frequency / self.sample_rate;
+    }
+
+    /// Sets the shape of the oscillator.
+    pub fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform = waveform;
+    }
+
+    /// Sets the current phase, wrapped into `[0, 1)`.
+    pub fn set_phase(&mut self, phase: f64) {
+        self.phase = wrap(phase);
+    }
+
+    /// The current phase in `[0, 1)`.
+    pub fn phase(&self) -> f64 {
+        self.phase
+    }
+
+    /// Restarts the oscillator from the beginning of its cycle.
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+
+    /// Evaluates the oscillator at the current phase then advances it
+    /// by a sample.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> f32 {
+        let value = self.waveform.evaluate(self.phase);
+        self.phase = wrap(self.phase + self.increment);
+        value
+    }
+
+    /// Fills the `out` buffer with consecutive values of the oscillator.
+    pub fn tick_block(&mut self, out: &mut [f32]) {
+        for value in out.iter_mut() {
+            *value = self.next();
+        }
+    }
+}
+
+/// Wraps a `phase` into `[0, 1)`.
+fn wrap(phase: f64) -> f64 {
+    let phase = phase - phase.floor();
+    // Rounding can produce exactly 1.0 for tiny negative phases.
+    if phase >= 1.0 {
+        0.0
+    } else {
+        phase
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lfo, Waveform};
+
+    #[test]
+    fn waveform_range() {
+        for waveform in [
+            Waveform::Sine,
+            Waveform::Triangle,
+            Waveform::Saw,
+            Waveform::Square,
+        ] {
+            let mut lfo = Lfo::new(waveform, 3.0, 1000.0);
+            let mut block = vec![0.0; 1000];
+            lfo.tick_block(&mut block);
+            assert!(block.iter().all(|value| (-1.0..=1.0).contains(value)));
+        }
+        let triangle = Waveform::Triangle;
+        assert_eq!(triangle.evaluate(0.0), 0.0);
+        assert_eq!(triangle.evaluate(0.25), 1.0);
+        assert_eq!(triangle.evaluate(0.75), -1.0);
+    }
+
+    #[test]
+    fn phase_is_stable() {
+        let mut lfo = Lfo::new(Waveform::Saw, 1.0, 48000.0);
+        for _ in 0..48000 * 100 {
+            lfo.next();
+            assert!((0.0..1.0).contains(&lfo.phase()));
+        }
+        let drift = lfo.phase().min(1.0 - lfo.phase());
+        assert!(drift < 1e-6);
+    }
+}
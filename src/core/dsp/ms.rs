@@ -0,0 +1,45 @@
+//! Converts interleaved stereo between left/right and mid/side.
+
+/// Converts interleaved left/right samples to mid/side in place, where
+/// `M = (L + R) / 2` and `S = (L - R) / 2`.
+pub fn encode(buffer: &mut [f32]) {
+    for frame in buffer.chunks_exact_mut(2) {
+        let (left, right) = (frame[0], frame[1]);
+        frame[0] = (left + right) * 0.5;
+        frame[1] = (left - right) * 0.5;
+    }
+}
+
+/// Converts interleaved mid/side samples back to left/right in place,
+/// undoing [`encode`].
+pub fn decode(buffer: &mut [f32]) {
+    for frame in buffer.chunks_exact_mut(2) {
+        let (mid, side) = (frame[0], frame[1]);
+        frame[0] = mid + side;
+        frame[1] = mid - side;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+
+    #[test]
+    fn round_trip() {
+        let input: Vec<f32> = (0..1024).map(|index| (index as f32 * 0.37).sin()).collect();
+        let mut buffer = input.clone();
+        encode(&mut buffer);
+        assert_ne!(buffer, input);
+        decode(&mut buffer);
+        for (x, y) in buffer.iter().zip(input.iter()) {
+            assert!((x - y).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn mono_has_no_side() {
+        let mut buffer = vec![0.5, 0.5, -0.25, -0.25];
+        encode(&mut buffer);
+        assert_eq!(buffer, [0.5, 0.0, -0.25, 0.0]);
+    }
+}
@@ -0,0 +1,94 @@
+//! A one-pole filter for smoothing and gentle tone shaping.
+use core::f64::consts::TAU;
+
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The cutoff used by [`OnePole::dc_blocker`], in Hz.
+pub const DC_BLOCKER_CUTOFF: f64 = 10.0;
+
+/// A one-pole filter with a single state variable, usable as either a
+/// low-pass or a high-pass.
+#[derive(Debug, Clone, Copy)]
+pub struct OnePole {
+    /// The feedback coefficient, `exp(-2 * pi * fc / fs)`.
+    coefficient: f32,
+    /// The previous low-pass output.
+    state: f32,
+}
+
+impl OnePole {
+    /// Creates a new [`OnePole`] with a `cutoff` in Hz.
+    pub fn new(cutoff: f64, sample_rate: f64) -> Self {
+        let mut one_pole = Self {
+            coefficient: 0.0,
+            state: 0.0,
+        };
+        one_pole.set_cutoff(cutoff, sample_rate);
+        one_pole
+    }
+
+    /// Creates a new [`OnePole`] suited for removing DC offset with
+    /// [`process_highpass`].
+    ///
+    /// [`process_highpass`]: Self::process_highpass
+    pub fn dc_blocker(sample_rate: f64) -> Self {
+        Self::new(DC_BLOCKER_CUTOFF, sample_rate)
+    }
+
+    /// Sets the `cutoff` in Hz, keeping the current state.
+    pub fn set_cutoff(&mut self, cutoff: f64, sample_rate: f64) {
+        let cutoff = cutoff.clamp(0.0, sample_rate / 2.0);
+        self.coefficient = (-TAU * cutoff / sample_rate).exp() as f32;
+    }
+
+    /// Clears the state of the filter.
+    pub fn reset(&mut self) {
+        self.state = 0.0;
+    }
+
+    /// Filters a sample, attenuating content above the cutoff.
+    pub fn process_lowpass(&mut self, x: f32) -> f32 {
+        self.state = (1.0 - self.coefficient) * x + self.coefficient * self.state;
+        self.state
+    }
+
+    /// Filters a sample, attenuating content below the cutoff.
+    pub fn process_highpass(&mut self, x: f32) -> f32 {
+        x - self.process_lowpass(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::OnePole;
+
+    fn peak_after_settling(mut filter: impl FnMut(f32) -> f32, frequency: f32) -> f32 {
+        (0..44100)
+            .map(|index| filter((TAU * frequency * index as f32 / 44100.0).sin()))
+            .skip(22050)
+            .fold(0.0, |peak, sample| sample.abs().max(peak))
+    }
+
+    #[test]
+    fn lowpass_attenuates_highs() {
+        let mut low = OnePole::new(1000.0, 44100.0);
+        let mut high = OnePole::new(1000.0, 44100.0);
+        let low_peak = peak_after_settling(|x| low.process_lowpass(x), 100.0);
+        let high_peak = peak_after_settling(|x| high.process_lowpass(x), 10000.0);
+        assert!(low_peak > 0.9);
+        assert!(high_peak < 0.2);
+    }
+
+    #[test]
+    fn dc_blocker_removes_offset() {
+        let mut dc_blocker = OnePole::dc_blocker(44100.0);
+        let last = (0..44100)
+            .map(|_| dc_blocker.process_highpass(0.5))
+            .last()
+            .unwrap();
+        assert!(last.abs() < 1e-3);
+    }
+}
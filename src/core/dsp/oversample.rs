@@ -0,0 +1,273 @@
+//! Runs an effect at a multiple of the sample rate, such that the
+//! harmonics added by nonlinear effects are filtered out before they
+//! alias.
+use alloc::{vec, vec::Vec};
+
+use super::fir::{Downsampler, Upsampler};
+use crate::core::effect::{Effect, UNKNOWN_POSITION};
+
+/// The highest oversampling factor accepted by [`Oversampler`].
+pub const MAX_FACTOR: usize = 4;
+
+/// Rounds a `factor` down to one accepted by [`Oversampler`], i.e.
+/// `1`, `2`, or [`MAX_FACTOR`].
+pub fn supported_factor(factor: usize) -> usize {
+    match factor {
+        0 | 1 => 1,
+        2 | 3 => 2,
+        _ => MAX_FACTOR,
+    }
+}
+
+/// Wraps an [`Effect`], running it at `factor` times the sample rate.
+///
+/// The inner effect must be created for the higher sample rate, e.g.
+/// `Wrapped::new(sample_rate * factor as f64)`, as it only ever sees
+/// the upsampled signal.
+#[derive(Debug)]
+pub struct Oversampler<E: Effect> {
+    /// The effect running at the higher rate.
+    effect: E,
+    /// The oversampling factor, one of `1`, `2`, or `4`.
+    factor: usize,
+    /// The interpolation filter of each channel.
+    upsamplers: [Upsampler; 2],
+    /// The decimation filter of each channel.
+    downsamplers: [Downsampler; 2],
+    /// The upsampled interleaved block, reused across calls to
+    /// `process`.
+    scratch: Vec<f32>,
+}
+
+impl<E: Effect> Oversampler<E> {
+    /// Creates a new [`Oversampler`] wrapping an `effect`, rounding the
+    /// `factor` down to a supported value.
+    pub fn new(effect: E, factor: usize) -> Self {
+        let factor = supported_factor(factor);
+        let upsampler = Upsampler::new(factor);
+        let downsampler = Downsampler::new(factor);
+        Self {
+            effect,
+            factor,
+            upsamplers: [upsampler.clone(), upsampler],
+            downsamplers: [downsampler.clone(), downsampler],
+            scratch: vec![],
+        }
+    }
+
+    /// The oversampling factor.
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Sets the oversampling factor, rounding it down to a supported
+    /// value and reallocating the resampling filters if it changes.
+    ///
+    /// The inner effect is left as is, so it must be updated for the
+    /// new sample rate separately if it depends on it.
+    pub fn set_factor(&mut self, factor: usize) {
+        let factor = supported_factor(factor);
+        if factor == self.factor {
+            return;
+        }
+        let upsampler = Upsampler::new(factor);
+        let downsampler = Downsampler::new(factor);
+        self.factor = factor;
+        self.upsamplers = [upsampler.clone(), upsampler];
+        self.downsamplers = [downsampler.clone(), downsampler];
+    }
+
+    /// Preallocates the scratch buffer for blocks of up to `len`
+    /// samples, such that `process` does not allocate.
+    pub fn reserve(&mut self, len: usize) {
+        if self.scratch.len() < len * self.factor {
+            self.scratch.resize(len * self.factor, 0.0);
+        }
+    }
+
+    /// A reference to the inner effect.
+    pub fn inner(&self) -> &E {
+        &self.effect
+    }
+
+    /// A mutable reference to the inner effect.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.effect
+    }
+}
+
+impl<E: Effect> Effect for Oversampler<E> {
+    type Parameters = E::Parameters;
+
+    fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        if self.factor == 1 {
+            self.effect.process(position, buffer);
+            return;
+        }
+        let factor = self.factor;
+        self.reserve(buffer.len());
+        let high = &mut self.scratch[..buffer.len() * factor];
+        let mut phases = [0.0; MAX_FACTOR];
+        for (frame, block) in buffer
+            .chunks_exact(2)
+            .zip(high.chunks_exact_mut(2 * factor))
+        {
+            for (channel, upsampler) in self.upsamplers.iter_mut().enumerate() {
+                upsampler.process(frame[channel], &mut phases);
+                for (phase, &x) in phases[..factor].iter().enumerate() {
+                    block[phase * 2 + channel] = x;
+                }
+            }
+        }
+
+        // The unknown position is passed through as is, as the inner
+        // effect keeps its own count of frames at the higher rate.
+        let position = if position == UNKNOWN_POSITION {
+            position
+        } else {
+            position.saturating_mul(factor)
+        };
+        self.effect.process(position, high);
+
+        for (frame, block) in buffer
+            .chunks_exact_mut(2)
+            .zip(high.chunks_exact(2 * factor))
+        {
+            for (channel, downsampler) in self.downsamplers.iter_mut().enumerate() {
+                for (phase, x) in phases[..factor].iter_mut().enumerate() {
+                    *x = block[phase * 2 + channel];
+                }
+                frame[channel] = downsampler.process(&phases);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        for upsampler in self.upsamplers.iter_mut() {
+            upsampler.reset();
+        }
+        for downsampler in self.downsamplers.iter_mut() {
+            downsampler.reset();
+        }
+        self.effect.reset();
+    }
+
+    fn set_parameters(&mut self, parameters: E::Parameters) {
+        self.effect.set_parameters(parameters);
+    }
+
+    /// The delay introduced by the resampling filters and the inner
+    /// effect, in frames at the lower rate.
+    fn latency_samples(&self) -> usize {
+        if self.factor == 1 {
+            return self.effect.latency_samples();
+        }
+        self.upsamplers[0].latency_samples()
+            + self.downsamplers[0].latency_samples()
+            + self.effect.latency_samples() / self.factor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::Oversampler;
+    use crate::core::effect::{
+        waveshaper::{Waveshaper, WaveshaperParameters},
+        Effect, TranceGate, TranceGateParameters, UNKNOWN_POSITION,
+    };
+
+    /// Measures the magnitude of a `frequency` in the left channel.
+    fn goertzel(buffer: &[f32], frequency: f64, sample_rate: f64) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (index, frame) in buffer.chunks_exact(2).enumerate() {
+            let phase = TAU * frequency * index as f64 / sample_rate;
+            re += frame[0] as f64 * phase.cos();
+            im -= frame[0] as f64 * phase.sin();
+        }
+        re.hypot(im) / (buffer.len() / 2) as f64
+    }
+
+    #[test]
+    fn unit_factor_passes_through() {
+        let mut oversampler = Oversampler::new(Waveshaper::new(|x| x), 1);
+        oversampler.set_parameters(WaveshaperParameters::new(1.0, 1.0, 1));
+        let input: Vec<f32> = (0..1024).map(|index| (index as f32 * 0.1).sin()).collect();
+        let mut buffer = input.clone();
+        oversampler.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+        assert_eq!(oversampler.latency_samples(), 0);
+    }
+
+    #[test]
+    fn hard_clip_aliases_less() {
+        let sample_rate = 44100.0;
+        let input: Vec<f32> = (0..44100 * 2)
+            .flat_map(|index| {
+                let x = (TAU * 5000.0 * index as f64 / sample_rate).sin() as f32;
+                [x, x]
+            })
+            .collect();
+        let parameters = WaveshaperParameters::new(4.0, 1.0, 1);
+
+        let mut plain = Waveshaper::hard_clip();
+        plain.initialize(parameters);
+        let mut aliased = input.clone();
+        plain.process(0, &mut aliased);
+
+        let mut oversampler = Oversampler::new(Waveshaper::hard_clip(), 4);
+        oversampler.inner_mut().initialize(parameters);
+        let mut filtered = input.clone();
+        oversampler.process(0, &mut filtered);
+        assert_eq!(oversampler.latency_samples(), 32);
+
+        // The 7th and 9th harmonics fold back to 9.1 kHz and 900 Hz.
+        let window = 44100 * 2;
+        for alias in [9100.0, 900.0] {
+            let plain = goertzel(&aliased[window..], alias, sample_rate);
+            let oversampled = goertzel(&filtered[window..], alias, sample_rate);
+            assert!(oversampled * 10.0 < plain, "{} vs {}", oversampled, plain);
+        }
+    }
+
+    #[test]
+    fn unknown_position_continues_inner_cycle() {
+        let gate = || {
+            let mut gate = TranceGate::new();
+            gate.initialize(TranceGateParameters::new(0.01, 1.0, 2.0 * 44100.0));
+            Oversampler::new(gate, 2)
+        };
+        let input: Vec<f32> = (0..2 * 4096)
+            .map(|index| (index as f32 * 0.01).sin())
+            .collect();
+        let mut expected = input.clone();
+        gate().process(0, &mut expected);
+
+        for tracked in [true, false] {
+            let mut oversampler = gate();
+            let mut buffer = input.clone();
+            for (index, block) in buffer.chunks_mut(2 * 300).enumerate() {
+                let position = index * 300;
+                oversampler.process(if tracked { position } else { UNKNOWN_POSITION }, block);
+            }
+            assert_eq!(buffer, expected);
+        }
+
+        let mut oversampler = gate();
+        let (mut left, mut right): (Vec<f32>, Vec<f32>) = input
+            .chunks_exact(2)
+            .map(|frame| (frame[0], frame[1]))
+            .unzip();
+        for (left, right) in left.chunks_mut(300).zip(right.chunks_mut(300)) {
+            let frames = left.len();
+            oversampler.process_planar(frames, &mut [left, right]);
+        }
+        let planar: Vec<f32> = left
+            .iter()
+            .zip(right.iter())
+            .flat_map(|(&left, &right)| [left, right])
+            .collect();
+        assert_eq!(planar, expected);
+    }
+}
@@ -0,0 +1,278 @@
+//! Converts a signal between arbitrary sample rates.
+//!
+//! # Overview
+//!
+//! Each output sample is the input convolved with a Blackman-windowed
+//! sinc centered on its position in time, which is rarely aligned with
+//! an input sample. The kernel is tabulated at a fixed number of
+//! fractional offsets and interpolated between them, such that any
+//! ratio between the rates costs the same. When lowering the rate, the
+//! cutoff of the kernel follows the new Nyquist frequency, and the
+//! kernel is widened to match.
+use alloc::{vec, vec::Vec};
+use core::f64::consts::PI;
+
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The number of fractional offsets at which the kernel is tabulated.
+const PHASES: usize = 512;
+
+/// The trade-off between the cost of a [`Resampler`] and the width of
+/// its flat passband.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Quality {
+    /// 8 taps on each side, flat up to about 80% of Nyquist.
+    Low,
+    /// 16 taps on each side, flat up to about 90% of Nyquist.
+    #[default]
+    Medium,
+    /// 32 taps on each side, flat up to about 95% of Nyquist.
+    High,
+}
+
+impl Quality {
+    /// The number of taps on each side of the center of the kernel.
+    fn taps_per_side(&self) -> usize {
+        match self {
+            Quality::Low => 8,
+            Quality::Medium => 16,
+            Quality::High => 32,
+        }
+    }
+
+    /// The cutoff of the kernel relative to the lower Nyquist frequency.
+    fn rolloff(&self) -> f64 {
+        match self {
+            Quality::Low => 0.8,
+            Quality::Medium => 0.9,
+            Quality::High => 0.95,
+        }
+    }
+}
+
+/// Converts a mono signal from one sample rate to another.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    /// The number of input samples per output sample.
+    step: f64,
+    /// The number of taps on each side of the center of the kernel.
+    half: usize,
+    /// The kernel at each fractional offset, `2 * half` taps apiece.
+    table: Vec<f32>,
+    /// The input samples that are still needed, preceded by `half`
+    /// samples of silence before the start of the signal.
+    history: Vec<f32>,
+    /// The absolute index of the first sample in the history.
+    offset: u64,
+    /// The number of input samples pushed so far.
+    consumed: u64,
+    /// The number of output samples produced so far.
+    produced: u64,
+}
+
+impl Resampler {
+    /// Creates a new [`Resampler`] from `from_hz` to `to_hz`.
+    ///
+    /// # Example
+    ///
+    /// If you want to load a 44.1 kHz impulse response into a 48 kHz
+    /// project:
+    ///
+    /// ```rust
+    /// # use photon::core::dsp::resample::*;
+    /// let mut resampler = Resampler::new(44100.0, 48000.0, Quality::High);
+    /// let resampled = resampler.process(&[1.0, 0.5, 0.25]);
+    /// assert_eq!(resampled.len(), 4);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if either rate is not positive.
+    pub fn new(from_hz: f64, to_hz: f64, quality: Quality) -> Self {
+        assert!(
+            from_hz > 0.0 && to_hz > 0.0,
+            "sample rates must be positive!"
+        );
+        let scale = (to_hz / from_hz).min(1.0);
+        let cutoff = quality.rolloff() * scale;
+        let half = (quality.taps_per_side() as f64 / scale).ceil() as usize;
+        let mut table = vec![0.0; (PHASES + 1) * 2 * half];
+        for (phase, row) in table.chunks_exact_mut(2 * half).enumerate() {
+            let fraction = phase as f64 / PHASES as f64;
+            let taps: Vec<f64> = (0..2 * half)
+                .map(|tap| kernel(fraction + half as f64 - 1.0 - tap as f64, cutoff, half))
+                .collect();
+            // Every offset passes DC at unity gain.
+            let sum: f64 = taps.iter().sum();
+            for (out, tap) in row.iter_mut().zip(taps) {
+                *out = (tap / sum) as f32;
+            }
+        }
+        let mut resampler = Self {
+            step: from_hz / to_hz,
+            half,
+            table,
+            history: vec![],
+            offset: 0,
+            consumed: 0,
+            produced: 0,
+        };
+        resampler.reset();
+        resampler
+    }
+
+    /// The number of output samples per input sample.
+    pub fn ratio(&self) -> f64 {
+        1.0 / self.step
+    }
+
+    /// Clears the history, such that the next input starts a new
+    /// signal.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.history.resize(self.half, 0.0);
+        self.offset = 0;
+        self.consumed = 0;
+        self.produced = 0;
+    }
+
+    /// Resamples a whole signal, returning exactly as many samples as
+    /// it lasts at the new rate, rounded up.
+    ///
+    /// This discards the state of any previous streaming.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        self.reset();
+        let mut output = Vec::with_capacity((input.len() as f64 * self.ratio()).ceil() as usize);
+        self.process_into(input, &mut output);
+        self.flush(&mut output);
+        output
+    }
+
+    /// Pushes a block of `input`, appending the output samples whose
+    /// kernels are covered by it to `output`.
+    ///
+    /// The output is aligned with the input, but lags behind it by
+    /// about `half` input samples until [`flush`] is called.
+    ///
+    /// [`flush`]: Self::flush
+    pub fn process_into(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        self.history.extend_from_slice(input);
+        self.consumed += input.len() as u64;
+        self.drain(output, u64::MAX);
+    }
+
+    /// Appends the remaining output samples to `output`, then starts a
+    /// new signal.
+    pub fn flush(&mut self, output: &mut Vec<f32>) {
+        let len = (self.consumed as f64 * self.ratio()).ceil() as u64;
+        self.history.resize(self.history.len() + self.half, 0.0);
+        self.drain(output, len);
+        self.reset();
+    }
+
+    /// Produces output samples while the history covers their kernels,
+    /// up to a total of `len`, then discards the input that is no
+    /// longer needed.
+    fn drain(&mut self, output: &mut Vec<f32>, len: u64) {
+        let taps = 2 * self.half;
+        while self.produced < len {
+            // Relative to the history, whose first `half` samples are
+            // silence.
+            let time = self.produced as f64 * self.step + self.half as f64 - self.offset as f64;
+            let index = time.floor() as usize;
+            if index + self.half >= self.history.len() {
+                break;
+            }
+            let position = (time - index as f64) * PHASES as f64;
+            let phase = (position as usize).min(PHASES - 1);
+            let blend = (position - phase as f64) as f32;
+            let inputs = &self.history[index + 1 - self.half..=index + self.half];
+            let (lower, upper) = (
+                &self.table[phase * taps..(phase + 1) * taps],
+                &self.table[(phase + 1) * taps..(phase + 2) * taps],
+            );
+            let sample = inputs
+                .iter()
+                .zip(lower.iter().zip(upper.iter()))
+                .map(|(x, (lower, upper))| x * (lower + (upper - lower) * blend))
+                .sum();
+            output.push(sample);
+            self.produced += 1;
+        }
+        let time = self.produced as f64 * self.step + self.half as f64 - self.offset as f64;
+        let start = (time.floor() as usize + 1)
+            .saturating_sub(self.half)
+            .min(self.history.len());
+        self.history.drain(..start);
+        self.offset += start as u64;
+    }
+}
+
+/// Evaluates the windowed sinc at a `distance` in input samples from
+/// its center, which spans `half` samples on each side.
+fn kernel(distance: f64, cutoff: f64, half: usize) -> f64 {
+    let x = distance / half as f64;
+    if x.abs() >= 1.0 {
+        return 0.0;
+    }
+    let window = 0.42 + 0.5 * (PI * x).cos() + 0.08 * (2.0 * PI * x).cos();
+    let argument = PI * cutoff * distance;
+    let sinc = if argument == 0.0 {
+        1.0
+    } else {
+        argument.sin() / argument
+    };
+    cutoff * sinc * window
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{Quality, Resampler};
+
+    fn sine(frequency: f64, sample_rate: f64, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|index| (TAU * frequency * index as f64 / sample_rate).sin() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn frequency_is_preserved() {
+        let input = sine(1000.0, 44100.0, 44100);
+        let output = Resampler::new(44100.0, 48000.0, Quality::Medium).process(&input);
+        assert_eq!(output.len(), 48000);
+        let expected = sine(1000.0, 48000.0, 48000);
+        // Skip the edges, where the kernel runs past the signal.
+        for (out, expected) in output[100..47900].iter().zip(&expected[100..47900]) {
+            assert!((out - expected).abs() < 1e-3, "{} {}", out, expected);
+        }
+    }
+
+    #[test]
+    fn passband_is_flat() {
+        for (from, to) in [(48000.0, 44100.0), (44100.0, 96000.0)] {
+            let input = sine(15000.0, from, from as usize);
+            let output = Resampler::new(from, to, Quality::High).process(&input);
+            let peak = output[1000..output.len() - 1000]
+                .iter()
+                .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+            assert!((peak - 1.0).abs() < 0.01, "{} {} {}", from, to, peak);
+        }
+    }
+
+    #[test]
+    fn streaming_matches_whole() {
+        let input = sine(440.0, 48000.0, 5000);
+        let mut resampler = Resampler::new(48000.0, 22050.0, Quality::Low);
+        let whole = resampler.process(&input);
+        let mut streamed = vec![];
+        for chunk in input.chunks(37) {
+            resampler.process_into(chunk, &mut streamed);
+        }
+        resampler.flush(&mut streamed);
+        assert_eq!(whole, streamed);
+    }
+}
@@ -0,0 +1,200 @@
+//! Glides parameters towards new values to avoid zipper noise.
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The distance from the target at which exponential smoothing snaps
+/// onto it.
+const EPSILON: f32 = 1e-5;
+
+/// The curve followed by a [`SmoothedValue`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Smoothing {
+    /// Moves towards the target in equal steps, arriving after exactly
+    /// the smoothing time.
+    #[default]
+    Linear,
+    /// Closes 63% of the remaining distance every smoothing time, which
+    /// sounds more natural for gains but never quite arrives.
+    Exponential,
+}
+
+/// A parameter that glides towards its target one sample at a time.
+#[derive(Debug, Clone, Copy)]
+pub struct SmoothedValue {
+    /// The curve of the glide.
+    smoothing: Smoothing,
+    /// The value at the current sample.
+    current: f32,
+    /// The value being glided towards.
+    target: f32,
+    /// The length of a linear glide, or the time constant of an
+    /// exponential glide, in samples.
+    samples: usize,
+    /// The increment of a linear glide.
+    step: f32,
+    /// The number of samples left in a linear glide.
+    remaining: usize,
+    /// The smoothing coefficient of an exponential glide.
+    coefficient: f32,
+}
+
+impl SmoothedValue {
+    /// Creates a [`SmoothedValue`] resting at `value`, which applies new
+    /// targets immediately until a smoothing time is set.
+    pub fn new(smoothing: Smoothing, value: f32) -> Self {
+        Self {
+            smoothing,
+            current: value,
+            target: value,
+            samples: 0,
+            step: 0.0,
+            remaining: 0,
+            coefficient: 0.0,
+        }
+    }
+
+    /// Sets the duration of a glide in milliseconds.
+    pub fn set_smoothing_time(&mut self, ms: f32, sample_rate: f64) {
+        let samples = (ms.max(0.0) as f64 * 0.001 * sample_rate).round() as usize;
+        self.set_smoothing_samples(samples);
+    }
+
+    /// Sets the duration of a glide in samples.
+    pub fn set_smoothing_samples(&mut self, samples: usize) {
+        self.samples = samples;
+        self.coefficient = match samples {
+            0 => 0.0,
+            samples => (-1.0 / samples as f64).exp() as f32,
+        };
+    }
+
+    /// Starts gliding towards `target` from the current value.
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target;
+        if self.samples == 0 {
+            self.current = target;
+            self.remaining = 0;
+        } else {
+            self.step = (target - self.current) / self.samples as f32;
+            self.remaining = self.samples;
+        }
+    }
+
+    /// Jumps to `start`, then glides towards `end` over exactly
+    /// `samples`, leaving the smoothing time for later glides as is.
+    ///
+    /// The glide is linear under [`Smoothing::Linear`], while an
+    /// exponential glide keeps its time constant.
+    pub fn set_ramp(&mut self, start: f32, end: f32, samples: usize) {
+        self.set_value(start);
+        if samples == 0 {
+            self.set_value(end);
+        } else {
+            self.target = end;
+            self.step = (end - start) / samples as f32;
+            self.remaining = samples;
+        }
+    }
+
+    /// Jumps to `value` without gliding.
+    pub fn set_value(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+        self.remaining = 0;
+    }
+
+    /// The value at the current sample.
+    pub fn value(&self) -> f32 {
+        self.current
+    }
+
+    /// The value being glided towards.
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Whether the value has yet to reach its target.
+    pub fn is_smoothing(&self) -> bool {
+        self.current != self.target
+    }
+
+    /// Advances the glide by a sample, returning the new value.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> f32 {
+        if !self.is_smoothing() {
+            return self.current;
+        }
+        match self.smoothing {
+            Smoothing::Linear => {
+                self.remaining = self.remaining.saturating_sub(1);
+                self.current = match self.remaining {
+                    0 => self.target,
+                    _ => self.current + self.step,
+                };
+            }
+            Smoothing::Exponential => {
+                self.current = self.target + (self.current - self.target) * self.coefficient;
+                if (self.current - self.target).abs() < EPSILON {
+                    self.current = self.target;
+                }
+            }
+        }
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SmoothedValue, Smoothing};
+
+    #[test]
+    fn linear_ramp_timing() {
+        let mut value = SmoothedValue::new(Smoothing::Linear, 0.0);
+        value.set_smoothing_time(10.0, 44100.0);
+        value.set_target(1.0);
+        let mut count = 0;
+        let mut previous = value.value();
+        while value.is_smoothing() {
+            let next = value.next();
+            assert!(next > previous);
+            previous = next;
+            count += 1;
+        }
+        assert_eq!(count, 441);
+        assert_eq!(value.value(), 1.0);
+    }
+
+    #[test]
+    fn exponential_time_constant() {
+        let mut value = SmoothedValue::new(Smoothing::Exponential, 0.0);
+        value.set_smoothing_samples(100);
+        value.set_target(1.0);
+        for _ in 0..100 {
+            value.next();
+        }
+        assert!((value.value() - (1.0 - (-1.0_f32).exp())).abs() < 1e-3);
+        while value.is_smoothing() {
+            value.next();
+        }
+        assert_eq!(value.value(), 1.0);
+    }
+
+    #[test]
+    fn ramp_keeps_smoothing_time() {
+        let mut value = SmoothedValue::new(Smoothing::Linear, 0.0);
+        value.set_smoothing_samples(10);
+        value.set_ramp(0.5, 1.0, 1000);
+        assert_eq!(value.value(), 0.5);
+        for _ in 0..999 {
+            value.next();
+        }
+        assert!(value.is_smoothing());
+        assert_eq!(value.next(), 1.0);
+
+        value.set_target(0.0);
+        for _ in 0..10 {
+            value.next();
+        }
+        assert_eq!(value.value(), 0.0);
+    }
+}
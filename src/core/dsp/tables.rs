@@ -0,0 +1,183 @@
+//! Lookup tables standing in for transcendental functions on hot paths.
+//!
+//! # Overview
+//!
+//! Each table samples its function at evenly spaced points and linearly
+//! interpolates between them. The error of linear interpolation shrinks
+//! with the square of the spacing, so the default size is far below
+//! [`MAX_ERROR`], while the smallest size accepted by `set_table_size`
+//! still keeps within it.
+use alloc::{vec, vec::Vec};
+use core::f32::consts::TAU;
+
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The largest difference between a table and the function it stands in
+/// for, at any table size and any finite input.
+pub const MAX_ERROR: f32 = 0.001;
+
+/// The number of points sampled by a table by default.
+pub const DEFAULT_TABLE_SIZE: usize = 1024;
+
+/// The fewest points sampled by a table, such that the error stays
+/// below [`MAX_ERROR`].
+pub const MIN_TABLE_SIZE: usize = 128;
+
+/// The input beyond which [`FastTanh`] saturates to `-1.0` or `1.0`,
+/// which is off by less than `1e-4`.
+pub const TANH_RANGE: f32 = 5.0;
+
+/// Samples `function` at `size` evenly spaced points across `start..end`,
+/// with an extra point at `end` so that interpolation never wraps.
+fn tabulate(size: usize, start: f32, end: f32, function: impl Fn(f32) -> f32) -> Vec<f32> {
+    let step = (end - start) / size as f32;
+    let mut table = vec![0.0; size + 1];
+    for (index, point) in table.iter_mut().enumerate() {
+        *point = function(start + step * index as f32);
+    }
+    table
+}
+
+/// Linearly interpolates the `table` at a fractional `position` within
+/// `0.0..=size`.
+fn interpolate(table: &[f32], position: f32) -> f32 {
+    let index = (position as usize).min(table.len() - 2);
+    let fraction = position - index as f32;
+    table[index] + (table[index + 1] - table[index]) * fraction
+}
+
+/// Approximates `tanh` with a lookup table.
+#[derive(Debug, Clone)]
+pub struct FastTanh {
+    /// `tanh` sampled across `-TANH_RANGE..=TANH_RANGE`.
+    table: Vec<f32>,
+}
+
+impl FastTanh {
+    /// Creates a new [`FastTanh`] with a table of [`DEFAULT_TABLE_SIZE`].
+    pub fn new() -> Self {
+        let mut tanh = Self { table: vec![] };
+        tanh.set_table_size(DEFAULT_TABLE_SIZE);
+        tanh
+    }
+
+    /// Resamples the table to `size` points, at least [`MIN_TABLE_SIZE`].
+    ///
+    /// Smaller tables fit better in cache at the cost of accuracy.
+    pub fn set_table_size(&mut self, size: usize) {
+        let size = size.max(MIN_TABLE_SIZE);
+        self.table = tabulate(size, -TANH_RANGE, TANH_RANGE, |x| x.tanh());
+    }
+
+    /// The number of points sampled by the table.
+    pub fn table_size(&self) -> usize {
+        self.table.len() - 1
+    }
+
+    /// Approximates `tanh(x)`.
+    pub fn tanh(&self, x: f32) -> f32 {
+        if x.is_nan() {
+            return x;
+        }
+        if x.abs() >= TANH_RANGE {
+            return x.signum();
+        }
+        let position = (x + TANH_RANGE) / (2.0 * TANH_RANGE) * self.table_size() as f32;
+        interpolate(&self.table, position)
+    }
+}
+
+impl Default for FastTanh {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Approximates `sin` with a lookup table over a single period.
+#[derive(Debug, Clone)]
+pub struct FastSin {
+    /// `sin` sampled across `0.0..=TAU`.
+    table: Vec<f32>,
+}
+
+impl FastSin {
+    /// Creates a new [`FastSin`] with a table of [`DEFAULT_TABLE_SIZE`].
+    pub fn new() -> Self {
+        let mut sin = Self { table: vec![] };
+        sin.set_table_size(DEFAULT_TABLE_SIZE);
+        sin
+    }
+
+    /// Resamples the table to `size` points, at least [`MIN_TABLE_SIZE`].
+    ///
+    /// Smaller tables fit better in cache at the cost of accuracy.
+    pub fn set_table_size(&mut self, size: usize) {
+        let size = size.max(MIN_TABLE_SIZE);
+        self.table = tabulate(size, 0.0, TAU, |x| x.sin());
+    }
+
+    /// The number of points sampled by the table.
+    pub fn table_size(&self) -> usize {
+        self.table.len() - 1
+    }
+
+    /// Approximates `sin(x)` for an `x` in radians.
+    pub fn sin(&self, x: f32) -> f32 {
+        self.sin_turns(x / TAU)
+    }
+
+    /// Approximates `sin(TAU * turns)`, skipping a division for callers
+    /// that already track phase in `0.0..1.0`.
+    pub fn sin_turns(&self, turns: f32) -> f32 {
+        if !turns.is_finite() {
+            return f32::NAN;
+        }
+        let turns = turns - turns.floor();
+        interpolate(&self.table, turns * self.table_size() as f32)
+    }
+}
+
+impl Default for FastSin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FastSin, FastTanh, MAX_ERROR, MIN_TABLE_SIZE};
+
+    fn max_error(table: impl Fn(f32) -> f32, exact: impl Fn(f32) -> f32, range: f32) -> f32 {
+        (0..=100_000)
+            .map(|index| -range + 2.0 * range * index as f32 / 100_000.0)
+            .map(|x| (table(x) - exact(x)).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn tanh_error_is_bounded() {
+        let mut tanh = FastTanh::new();
+        for size in [0, MIN_TABLE_SIZE, 1000, 4096] {
+            tanh.set_table_size(size);
+            let error = max_error(|x| tanh.tanh(x), f32::tanh, 20.0);
+            assert!(error < MAX_ERROR, "{} at {}", error, size);
+        }
+        let precise = FastTanh::new();
+        assert!(max_error(|x| precise.tanh(x), f32::tanh, 8.0) < 1e-4);
+        assert_eq!(tanh.tanh(f32::INFINITY), 1.0);
+        assert!(tanh.tanh(f32::NAN).is_nan());
+    }
+
+    #[test]
+    fn sin_error_is_bounded() {
+        let mut sin = FastSin::new();
+        for size in [0, MIN_TABLE_SIZE, 1000, 4096] {
+            sin.set_table_size(size);
+            let error = max_error(|x| sin.sin(x), f32::sin, 100.0);
+            assert!(error < MAX_ERROR, "{} at {}", error, size);
+        }
+        assert!((sin.sin_turns(0.25) - 1.0).abs() < 1e-6);
+        assert!((sin.sin_turns(-0.25) + 1.0).abs() < 1e-6);
+    }
+}
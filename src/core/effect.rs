@@ -1,6 +1,203 @@
 //! Defines various effects to be applied to samples.
+pub mod autopan;
+pub mod bitcrusher;
+pub mod bypass;
+pub mod chorus;
+pub mod clip_detect;
+pub mod compressor;
+pub mod convolution;
+pub mod crossfeed;
+pub mod dc_blocker;
+pub mod delay;
+pub mod distortion;
+pub mod dry_wet;
+pub mod ducker;
+pub mod eq;
+pub mod expander;
+pub mod filtered_delay;
+pub mod flanger;
+pub mod gain;
+pub mod granular;
+pub mod haas;
+pub mod limiter;
+pub mod mid_side;
+pub mod multiband;
+pub mod noise_gate;
+pub mod parallel_mix;
+pub mod phaser;
+pub mod pingpong;
+pub mod pitch_shift;
 pub mod retrigger;
+pub mod reverb;
+pub mod ringmod;
+pub mod spectral_gate;
+pub mod stutter;
+pub mod tape;
 pub mod trance_gate;
+pub mod tremolo;
+pub mod trim;
+pub mod vibrato;
+pub mod wavefolder;
+pub mod waveshaper;
+pub mod width;
 
+use crate::core::transport::Transport;
+
+pub use autopan::{AutoPan, AutoPanParameters};
+pub use bitcrusher::{Bitcrusher, BitcrusherParameters};
+pub use bypass::Bypass;
+pub use chorus::{Chorus, ChorusParameters};
+pub use clip_detect::ClipDetect;
+pub use compressor::{Compressor, CompressorParameters};
+pub use convolution::{Convolver, ConvolverParameters};
+pub use crossfeed::{Crossfeed, CrossfeedParameters};
+pub use dc_blocker::{DcBlocker, DcBlockerParameters};
+pub use delay::{Delay, DelayParameters};
+pub use distortion::{Distortion, DistortionParameters};
+pub use dry_wet::DryWet;
+pub use ducker::{Ducker, DuckerParameters};
+pub use eq::{EqBand, EqParameters, ParametricEq};
+pub use expander::{Expander, ExpanderParameters};
+pub use filtered_delay::{FilteredDelay, FilteredDelayParameters};
+pub use flanger::{Flanger, FlangerParameters};
+pub use gain::{Gain, GainParameters};
+pub use granular::{Granular, GranularParameters};
+pub use haas::{Haas, HaasParameters};
+pub use limiter::{Limiter, LimiterParameters};
+pub use mid_side::MidSide;
+pub use multiband::{Multiband, MultibandParameters};
+pub use noise_gate::{NoiseGate, NoiseGateParameters};
+pub use parallel_mix::ParallelMix;
+pub use phaser::{Phaser, PhaserParameters};
+pub use pingpong::{PingPongDelay, PingPongParameters};
+pub use pitch_shift::{PitchParameters, PitchShifter};
 pub use retrigger::{Retrigger, RetriggerParameters};
-pub use trance_gate::{TranceGate, TranceGateParameters};
+pub use reverb::{Reverb, ReverbParameters};
+pub use ringmod::{RingMod, RingModParameters};
+pub use spectral_gate::{SpectralGate, SpectralGateParameters};
+pub use stutter::{Stutter, StutterParameters};
+pub use tape::{Tape, TapeParameters};
+pub use trance_gate::{TranceGate, TranceGateImpl, TranceGateParameters};
+pub use tremolo::{Tremolo, TremoloParameters};
+pub use trim::{Trim, TrimParameters};
+pub use vibrato::{Vibrato, VibratoParameters};
+pub use wavefolder::{Wavefolder, WavefolderParameters};
+pub use waveshaper::{Waveshaper, WaveshaperParameters};
+pub use width::{StereoWidth, WidthParameters};
+
+/// The number of frames interleaved at a time by the default
+/// [`Effect::process_planar`].
+pub const PLANAR_BLOCK_FRAMES: usize = 128;
+
+/// The `position` passed to [`Effect::process`] by callers that do not
+/// track the playhead, such that effects fall back to their own
+/// bookkeeping.
+pub const UNKNOWN_POSITION: usize = usize::MAX;
+
+/// The sample rate assumed by the engine when none is provided.
+pub const DEFAULT_SAMPLE_RATE: f64 = 44100.0;
+
+/// The common interface shared by audio effects.
+///
+/// Effects operate in-place over a buffer of interleaved samples. The
+/// [`Parameters`] type and [`set_parameters`] are only available on
+/// concrete types, such that effects can still be stored as a
+/// `Box<dyn Effect>` inside of a chain.
+///
+/// [`Parameters`]: Self::Parameters
+/// [`set_parameters`]: Self::set_parameters
+pub trait Effect {
+    /// The parameters consumed by the effect.
+    type Parameters
+    where
+        Self: Sized;
+
+    /// Applies the effect to the `buffer`, where `position` is the
+    /// absolute frame index of the start of the buffer as tracked by the
+    /// caller, or [`UNKNOWN_POSITION`] if it is not tracked.
+    fn process(&mut self, position: usize, buffer: &mut [f32]);
+
+    /// Applies the effect to the `buffer`, which starts at the playhead
+    /// of the `transport`.
+    ///
+    /// By default, this passes the `sample_pos` of the `transport` to
+    /// [`process`] as the `position`. Effects that sync to the tempo
+    /// should override this.
+    ///
+    /// [`process`]: Self::process
+    fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [f32]) {
+        self.process(transport.sample_pos as usize, buffer);
+    }
+
+    /// Applies the effect to the first `frames` of each of the separate
+    /// `channels`, stopping early at the end of the shortest one.
+    ///
+    /// By default, the first two channels are interleaved a block at a
+    /// time into a buffer on the stack and passed to [`process`] with an
+    /// [`UNKNOWN_POSITION`], where a single channel is processed as dual
+    /// mono and any further channels are left untouched. Effects that
+    /// can work on separate channels directly should override this.
+    ///
+    /// [`process`]: Self::process
+    fn process_planar(&mut self, frames: usize, channels: &mut [&mut [f32]]) {
+        let frames = channels
+            .iter()
+            .map(|channel| channel.len())
+            .fold(frames, usize::min);
+        let mut block = [0.0; 2 * PLANAR_BLOCK_FRAMES];
+        for start in (0..frames).step_by(PLANAR_BLOCK_FRAMES) {
+            let len = PLANAR_BLOCK_FRAMES.min(frames - start);
+            let block = &mut block[..2 * len];
+            match channels {
+                [] => return,
+                [mono] => {
+                    for (frame, &x) in block.chunks_exact_mut(2).zip(&mono[start..start + len]) {
+                        frame.fill(x);
+                    }
+                    self.process(UNKNOWN_POSITION, block);
+                    for (x, frame) in mono[start..start + len]
+                        .iter_mut()
+                        .zip(block.chunks_exact(2))
+                    {
+                        *x = frame[0];
+                    }
+                }
+                [left, right, ..] => {
+                    let (left, right) = (
+                        &mut left[start..start + len],
+                        &mut right[start..start + len],
+                    );
+                    for ((frame, &l), &r) in
+                        block.chunks_exact_mut(2).zip(left.iter()).zip(right.iter())
+                    {
+                        frame[0] = l;
+                        frame[1] = r;
+                    }
+                    self.process(UNKNOWN_POSITION, block);
+                    for ((frame, l), r) in block
+                        .chunks_exact(2)
+                        .zip(left.iter_mut())
+                        .zip(right.iter_mut())
+                    {
+                        *l = frame[0];
+                        *r = frame[1];
+                    }
+                }
+            }
+        }
+    }
+
+    /// Clears the internal state of the effect, keeping its parameters.
+    fn reset(&mut self);
+
+    /// Replaces the parameters of the effect.
+    fn set_parameters(&mut self, parameters: Self::Parameters)
+    where
+        Self: Sized;
+
+    /// The delay introduced by the effect in frames, such that a host
+    /// can line its output up with other signals.
+    fn latency_samples(&self) -> usize {
+        0
+    }
+}
@@ -0,0 +1,175 @@
+//! Lowers the resolution and sample rate of the signal.
+
+use super::Effect;
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The highest bit depth accepted by [`BitcrusherParameters`].
+pub const MAX_BIT_DEPTH: u8 = 24;
+
+/// The parameters consumed by [`Bitcrusher`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitcrusherParameters {
+    /// The number of bits kept per sample, quantizing the signal to
+    /// `2^bit_depth` levels within `-1.0..1.0`, one of which is zero.
+    pub bit_depth: u8,
+    /// The number of frames that each sample is held for.
+    pub sample_rate_reduction: usize,
+    /// Determines how much of the crushed signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl BitcrusherParameters {
+    /// Creates a new [`BitcrusherParameters`], clamping the `bit_depth`
+    /// to `1..=MAX_BIT_DEPTH` and the `sample_rate_reduction` to at
+    /// least `1`.
+    ///
+    /// # Example
+    ///
+    /// If you want a lo-fi 8-bit sound at around 11 kHz:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::bitcrusher::*;
+    /// let _ = BitcrusherParameters::new(8, 4, 1.0);
+    /// ```
+    pub fn new(bit_depth: u8, sample_rate_reduction: usize, mix: f32) -> Self {
+        Self {
+            bit_depth: bit_depth.clamp(1, MAX_BIT_DEPTH),
+            sample_rate_reduction: sample_rate_reduction.max(1),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Quantizes a sample to the bit depth, rounding to the nearest
+    /// level such that silence stays silent.
+    pub fn quantize(&self, x: f32) -> f32 {
+        let half = (1_u32 << (self.bit_depth.clamp(1, MAX_BIT_DEPTH) - 1)) as f32;
+        (x * half).round().clamp(-half, half - 1.0) / half
+    }
+}
+
+/// The bitcrusher DSP and its internal state.
+#[derive(Debug)]
+pub struct Bitcrusher {
+    /// The parameters for the effect.
+    parameters: Option<BitcrusherParameters>,
+    /// The frame being held.
+    held: [f32; 2],
+    /// The number of frames that the current frame has been held for.
+    counter: usize,
+}
+
+impl Bitcrusher {
+    pub fn new() -> Self {
+        Self {
+            parameters: None,
+            held: [0.0; 2],
+            counter: 0,
+        }
+    }
+}
+
+impl Default for Bitcrusher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bitcrusher {
+    /// Initializes the [`Bitcrusher`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: BitcrusherParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Bitcrusher`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Bitcrusher {
+    type Parameters = BitcrusherParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let reduction = parameters.sample_rate_reduction.max(1);
+        for frame in buffer.chunks_exact_mut(2) {
+            if self.counter == 0 {
+                self.held = [parameters.quantize(frame[0]), parameters.quantize(frame[1])];
+            }
+            self.counter = (self.counter + 1) % reduction;
+            for (sample, held) in frame.iter_mut().zip(self.held) {
+                *sample = *sample * (1.0 - parameters.mix) + held * parameters.mix;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.held = [0.0; 2];
+        self.counter = 0;
+    }
+
+    fn set_parameters(&mut self, parameters: BitcrusherParameters) {
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bitcrusher, BitcrusherParameters, MAX_BIT_DEPTH};
+    use crate::core::effect::Effect;
+
+    fn ramp(frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|index| {
+                let x = index as f32 / (frames - 1) as f32 * 2.0 - 1.0;
+                [x, x]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ramp_levels() {
+        for bit_depth in [1, 3, 6] {
+            let mut bitcrusher = Bitcrusher::new();
+            bitcrusher.initialize(BitcrusherParameters::new(bit_depth, 1, 1.0));
+            let mut buffer = ramp(10000);
+            bitcrusher.process(0, &mut buffer);
+
+            let mut levels = buffer.clone();
+            levels.sort_unstable_by(f32::total_cmp);
+            // Both signs of zero count as the same level.
+            levels.dedup();
+            assert_eq!(levels.len(), 1 << bit_depth);
+        }
+    }
+
+    #[test]
+    fn silence_stays_silent() {
+        for bit_depth in 1..=MAX_BIT_DEPTH {
+            let mut bitcrusher = Bitcrusher::new();
+            bitcrusher.initialize(BitcrusherParameters::new(bit_depth, 1, 1.0));
+            let mut buffer = vec![0.0; 2 * 64];
+            bitcrusher.process(0, &mut buffer);
+            assert!(buffer.iter().all(|&sample| sample == 0.0), "{}", bit_depth);
+        }
+    }
+
+    #[test]
+    fn sample_and_hold() {
+        let mut bitcrusher = Bitcrusher::new();
+        bitcrusher.initialize(BitcrusherParameters::new(0, 4, 1.0));
+        let mut buffer = ramp(16);
+        bitcrusher.process(0, &mut buffer);
+        for frames in buffer.chunks_exact(8) {
+            assert!(frames.iter().all(|&sample| sample == frames[0]));
+        }
+    }
+}
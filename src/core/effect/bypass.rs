@@ -0,0 +1,163 @@
+//! Toggles an effect on and off without clicks.
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::dsp::{SmoothedValue, Smoothing};
+use crate::core::transport::Transport;
+
+/// The number of frames taken to crossfade by default, about 10ms at
+/// 44.1 kHz.
+pub const DEFAULT_CROSSFADE_SAMPLES: usize = 441;
+
+/// Crossfades between the dry input and the output of an inner
+/// [`Effect`] whenever it is bypassed or enabled.
+#[derive(Debug)]
+pub struct Bypass<E: Effect> {
+    /// The effect being bypassed.
+    effect: E,
+    /// The amount of processed signal, from `0.0` while bypassed to
+    /// `1.0` while enabled.
+    wet: SmoothedValue,
+    /// A copy of the dry input, reused across calls to `process`.
+    scratch: Vec<f32>,
+}
+
+impl<E: Effect> Bypass<E> {
+    /// Creates a new, enabled [`Bypass`] wrapping an `effect`.
+    pub fn new(effect: E, crossfade_samples: usize) -> Self {
+        let mut wet = SmoothedValue::new(Smoothing::Linear, 1.0);
+        wet.set_smoothing_samples(crossfade_samples);
+        Self {
+            effect,
+            wet,
+            scratch: vec![],
+        }
+    }
+
+    /// Preallocates the scratch buffer for blocks of up to `len`
+    /// samples, such that `process` does not allocate.
+    pub fn reserve(&mut self, len: usize) {
+        if self.scratch.len() < len {
+            self.scratch.resize(len, 0.0);
+        }
+    }
+
+    /// Starts crossfading towards the dry input if `bypassed`, or
+    /// towards the processed output otherwise.
+    pub fn set_bypassed(&mut self, bypassed: bool) {
+        let target = if bypassed { 0.0 } else { 1.0 };
+        if target != self.wet.target() {
+            // The inner effect was idle while bypassed, so its state is
+            // stale from before.
+            if !bypassed && !self.wet.is_smoothing() {
+                self.effect.reset();
+            }
+            self.wet.set_target(target);
+        }
+    }
+
+    /// Whether the effect is bypassed, or is crossfading towards it.
+    pub fn is_bypassed(&self) -> bool {
+        self.wet.target() == 0.0
+    }
+
+    /// Runs the inner effect over the `buffer` with `process`, then
+    /// crossfades it with the dry input while toggling.
+    fn crossfade_with(&mut self, buffer: &mut [f32], process: impl FnOnce(&mut E, &mut [f32])) {
+        if !self.wet.is_smoothing() {
+            if self.wet.value() == 1.0 {
+                process(&mut self.effect, buffer);
+            }
+            return;
+        }
+        self.reserve(buffer.len());
+        let dry = &mut self.scratch[..buffer.len()];
+        dry.copy_from_slice(buffer);
+        process(&mut self.effect, buffer);
+        for (wet, dry) in buffer.chunks_exact_mut(2).zip(dry.chunks_exact(2)) {
+            let mix = self.wet.next();
+            for (wet, dry) in wet.iter_mut().zip(dry.iter()) {
+                *wet = dry * (1.0 - mix) + *wet * mix;
+            }
+        }
+    }
+
+    /// A reference to the inner effect.
+    pub fn inner(&self) -> &E {
+        &self.effect
+    }
+
+    /// A mutable reference to the inner effect.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.effect
+    }
+}
+
+impl<E: Effect> Effect for Bypass<E> {
+    type Parameters = E::Parameters;
+
+    /// Applies the inner effect to the `buffer`, crossfading with the
+    /// dry input while toggling.
+    ///
+    /// The inner effect does not run at all while fully bypassed.
+    fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        self.crossfade_with(buffer, |effect, buffer| effect.process(position, buffer));
+    }
+
+    fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [f32]) {
+        self.crossfade_with(buffer, |effect, buffer| {
+            effect.process_with_transport(transport, buffer)
+        });
+    }
+
+    fn reset(&mut self) {
+        self.effect.reset();
+    }
+
+    fn set_parameters(&mut self, parameters: E::Parameters) {
+        self.effect.set_parameters(parameters);
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.effect.latency_samples()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bypass;
+    use crate::core::effect::{trance_gate::GateShape, Effect, TranceGate, TranceGateParameters};
+
+    #[test]
+    fn toggling_is_continuous() {
+        // A gate that is fully closed for the second half of each cycle.
+        let parameters = TranceGateParameters {
+            shape: GateShape::Square,
+            floor: 0.0,
+            ..TranceGateParameters::new(1.0, 1.0, 44100.0)
+        };
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        let mut bypass = Bypass::new(gate, 256);
+
+        let input: Vec<f32> = (0..44100)
+            .flat_map(|index| {
+                let x = (index as f32 * 0.01).sin();
+                [x, x]
+            })
+            .collect();
+        let mut buffer = input.clone();
+        let (half, quarter) = (44100, 44100 / 2);
+        bypass.process(0, &mut buffer[..half + quarter]);
+        bypass.set_bypassed(true);
+        bypass.process((half + quarter) / 2, &mut buffer[half + quarter..]);
+
+        // Skip the jump of the gate itself at its midpoint.
+        let toggled = &buffer[half + quarter - 2..];
+        for pair in toggled.chunks_exact(2).collect::<Vec<_>>().windows(2) {
+            assert!((pair[1][0] - pair[0][0]).abs() < 0.015);
+        }
+        let settled = half + quarter + 256 * 2;
+        assert_eq!(buffer[settled..], input[settled..]);
+    }
+}
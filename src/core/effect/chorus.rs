@@ -0,0 +1,205 @@
+//! Thickens the signal by mixing in several modulated copies of it.
+use super::Effect;
+use crate::core::dsp::{DelayLine, Lfo, Waveform};
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The largest number of voices accepted by [`ChorusParameters`].
+pub const MAX_VOICES: usize = 8;
+
+/// The largest modulation depth accepted by [`ChorusParameters`].
+pub const MAX_DEPTH_MS: f32 = 20.0;
+
+/// The delay of each voice when the modulation is at its lowest.
+pub const BASE_DELAY_MS: f32 = 7.0;
+
+/// The parameters consumed by [`Chorus`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChorusParameters {
+    /// The frequency of the modulation in Hz.
+    pub rate_hz: f32,
+    /// The range swept by the delay of each voice.
+    pub depth_ms: f32,
+    /// The number of modulated copies, from `1` to [`MAX_VOICES`].
+    pub voices: usize,
+    /// Determines how much of the modulated copies are mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl ChorusParameters {
+    /// Creates a new [`ChorusParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a lush, slowly moving chorus:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::chorus::*;
+    /// let _ = ChorusParameters::new(0.8, 4.0, 3, 0.5);
+    /// ```
+    pub fn new(rate_hz: f32, depth_ms: f32, voices: usize, mix: f32) -> Self {
+        Self {
+            rate_hz: rate_hz.max(0.0),
+            depth_ms: depth_ms.clamp(0.0, MAX_DEPTH_MS),
+            voices: voices.clamp(1, MAX_VOICES),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// The chorus DSP and its internal state.
+#[derive(Debug)]
+pub struct Chorus {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<ChorusParameters>,
+    /// The delay line of each channel, shared across voices.
+    lines: [DelayLine; 2],
+    /// The modulation of each voice.
+    lfos: [Lfo; MAX_VOICES],
+}
+
+impl Chorus {
+    /// Creates a new [`Chorus`], allocating enough delay for the
+    /// deepest modulation up front.
+    pub fn new(sample_rate: f64) -> Self {
+        let len = ((BASE_DELAY_MS + MAX_DEPTH_MS) as f64 * 0.001 * sample_rate) as usize + 4;
+        let line = DelayLine::new(len);
+        let lfo = Lfo::new(Waveform::Sine, 0.0, sample_rate);
+        Self {
+            sample_rate,
+            parameters: None,
+            lines: [line.clone(), line],
+            lfos: [(); MAX_VOICES].map(|_| lfo.clone()),
+        }
+    }
+}
+
+impl Chorus {
+    /// Initializes the [`Chorus`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: ChorusParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Chorus`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Chorus {
+    type Parameters = ChorusParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let samples_per_ms = (self.sample_rate * 0.001) as f32;
+        let base = BASE_DELAY_MS * samples_per_ms;
+        let depth = parameters.depth_ms.clamp(0.0, MAX_DEPTH_MS) * samples_per_ms;
+        let voices = parameters.voices.clamp(1, MAX_VOICES);
+        for frame in buffer.chunks_exact_mut(2) {
+            self.lines[0].write(frame[0]);
+            self.lines[1].write(frame[1]);
+            let mut wet = [0.0; 2];
+            for lfo in self.lfos[..voices].iter_mut() {
+                // The right channel runs a quarter cycle ahead.
+                let right = Waveform::Sine.evaluate((lfo.phase() + 0.25).fract());
+                let left = lfo.next();
+                for (channel, modulation) in [left, right].into_iter().enumerate() {
+                    let delay = base + depth * 0.5 * (1.0 + modulation);
+                    wet[channel] += self.lines[channel].read_cubic(delay);
+                }
+            }
+            for (sample, wet) in frame.iter_mut().zip(wet) {
+                let wet = wet / voices as f32;
+                *sample = *sample * (1.0 - parameters.mix) + wet * parameters.mix;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.clear();
+        }
+        let voices = self.parameters.map_or(1, |parameters| parameters.voices);
+        for (voice, lfo) in self.lfos.iter_mut().enumerate() {
+            lfo.set_phase(voice as f64 / voices as f64);
+        }
+    }
+
+    /// Replaces the parameters of the effect, spreading the phase of
+    /// the voices if their count changes.
+    fn set_parameters(&mut self, parameters: ChorusParameters) {
+        for lfo in self.lfos.iter_mut() {
+            lfo.set_frequency(parameters.rate_hz as f64);
+        }
+        let voices = parameters.voices.clamp(1, MAX_VOICES);
+        if self.parameters.map(|previous| previous.voices) != Some(voices) {
+            let phase = self.lfos[0].phase();
+            for (voice, lfo) in self.lfos.iter_mut().enumerate() {
+                lfo.set_phase(phase + voice as f64 / voices as f64);
+            }
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{Chorus, ChorusParameters};
+    use crate::core::{effect::Effect, testing::assert_no_alloc};
+
+    /// Measures the magnitude of a `frequency` in the left channel.
+    fn goertzel(buffer: &[f32], frequency: f64, sample_rate: f64) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (index, frame) in buffer.chunks_exact(2).enumerate() {
+            let phase = TAU * frequency * index as f64 / sample_rate;
+            re += frame[0] as f64 * phase.cos();
+            im -= frame[0] as f64 * phase.sin();
+        }
+        re.hypot(im) / (buffer.len() / 2) as f64
+    }
+
+    #[test]
+    fn modulation_sidebands() {
+        let sample_rate = 44100.0;
+        let input: Vec<f32> = (0..44100 * 2)
+            .flat_map(|index| {
+                let x = (TAU * 1000.0 * index as f64 / sample_rate).sin() as f32;
+                [x, x]
+            })
+            .collect();
+        let mut chorus = Chorus::new(sample_rate);
+        chorus.initialize(ChorusParameters::new(5.0, 0.1, 1, 1.0));
+        let mut buffer = input.clone();
+        chorus.process(0, &mut buffer);
+
+        let window = 44100 * 2;
+        let dry = goertzel(&input[window..], 1005.0, sample_rate);
+        let wet = goertzel(&buffer[window..], 1005.0, sample_rate);
+        assert!(dry < 0.005);
+        assert!(wet > 0.03);
+    }
+
+    #[test]
+    fn voices_do_not_reallocate() {
+        let mut chorus = Chorus::new(44100.0);
+        chorus.initialize(ChorusParameters::new(1.0, 20.0, 1, 0.5));
+        let mut buffer = vec![1.0; 2 * 4096];
+        chorus.process(0, &mut buffer);
+        assert_no_alloc(|| {
+            chorus.set_parameters(ChorusParameters::new(1.0, 20.0, 8, 0.5));
+            chorus.process(0, &mut buffer);
+        });
+        assert!(buffer.iter().all(|sample| sample.is_finite()));
+    }
+}
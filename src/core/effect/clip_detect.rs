@@ -0,0 +1,129 @@
+//! Counts the samples that an effect drives out of range.
+use super::Effect;
+use crate::core::transport::Transport;
+
+/// Observes the output of an inner [`Effect`], counting every sample
+/// that lies outside of `-1.0..=1.0` without modifying it.
+///
+/// Non-finite samples are counted as well, as they are out of range for
+/// any output format.
+#[derive(Debug)]
+pub struct ClipDetect<E: Effect> {
+    /// The effect being observed.
+    effect: E,
+    /// The number of out of range samples since the last reset.
+    clipped: u64,
+}
+
+impl<E: Effect> ClipDetect<E> {
+    /// Creates a new [`ClipDetect`] wrapping an `effect`.
+    pub fn new(effect: E) -> Self {
+        Self { effect, clipped: 0 }
+    }
+
+    /// The number of samples outside of `-1.0..=1.0` produced since the
+    /// detector was created or last reset, across all channels.
+    pub fn clipped_samples(&self) -> u64 {
+        self.clipped
+    }
+
+    /// Whether any sample was out of range since the last reset.
+    pub fn has_clipped(&self) -> bool {
+        self.clipped > 0
+    }
+
+    /// Clears the count of out of range samples.
+    ///
+    /// This is separate from [`reset`], such that clearing the state of
+    /// the inner effect does not hide an over.
+    ///
+    /// [`reset`]: Effect::reset
+    pub fn reset_clip(&mut self) {
+        self.clipped = 0;
+    }
+
+    /// Adds the out of range samples within `samples` to the count.
+    fn count(&mut self, samples: &[f32]) {
+        let clipped = samples
+            .iter()
+            .filter(|x| x.abs() > 1.0 || x.is_nan())
+            .count();
+        self.clipped += clipped as u64;
+    }
+
+    /// A reference to the inner effect.
+    pub fn inner(&self) -> &E {
+        &self.effect
+    }
+
+    /// A mutable reference to the inner effect.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.effect
+    }
+}
+
+impl<E: Effect> Effect for ClipDetect<E> {
+    type Parameters = E::Parameters;
+
+    fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        self.effect.process(position, buffer);
+        self.count(buffer);
+    }
+
+    fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [f32]) {
+        self.effect.process_with_transport(transport, buffer);
+        self.count(buffer);
+    }
+
+    fn process_planar(&mut self, frames: usize, channels: &mut [&mut [f32]]) {
+        self.effect.process_planar(frames, channels);
+        for channel in channels.iter() {
+            self.count(&channel[..frames.min(channel.len())]);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.effect.reset();
+    }
+
+    fn set_parameters(&mut self, parameters: E::Parameters) {
+        self.effect.set_parameters(parameters);
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.effect.latency_samples()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClipDetect;
+    use crate::core::effect::{Effect, Gain, GainParameters};
+
+    #[test]
+    fn single_over_is_counted() {
+        let mut gain = Gain::new(44100.0);
+        gain.initialize(GainParameters::new(0.0));
+        let mut detect = ClipDetect::new(gain);
+        let input = vec![0.5, -1.0, 1.0, 0.25, -1.0001, 0.0];
+        let mut buffer = input.clone();
+        detect.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+        assert_eq!(detect.clipped_samples(), 1);
+
+        detect.reset();
+        assert_eq!(detect.clipped_samples(), 1);
+        detect.reset_clip();
+        assert!(!detect.has_clipped());
+    }
+
+    #[test]
+    fn planar_channels_are_observed() {
+        let mut gain = Gain::new(44100.0);
+        gain.initialize(GainParameters::new(0.0));
+        let mut detect = ClipDetect::new(gain);
+        let (mut left, mut right) = ([0.0, 2.0, 0.0], [f32::NAN, 0.0, -3.0]);
+        detect.process_planar(3, &mut [&mut left, &mut right]);
+        assert_eq!(detect.clipped_samples(), 3);
+    }
+}
@@ -0,0 +1,379 @@
+//! Reduces the dynamic range of loud passages.
+use super::Effect;
+use crate::core::dsp::{
+    decibel::{db_to_gain, gain_to_db},
+    envelope::{time_coefficient, EnvelopeFollower, Mode},
+};
+
+/// The release time after a brief transient while `auto_release` is
+/// enabled.
+pub const AUTO_RELEASE_FAST_MS: f32 = 40.0;
+
+/// The release time after a sustained loud passage while
+/// `auto_release` is enabled.
+pub const AUTO_RELEASE_SLOW_MS: f32 = 800.0;
+
+/// The time spent above the threshold before the release of a
+/// sustained passage is fully slowed down.
+pub const AUTO_RELEASE_SUSTAIN_MS: f32 = 300.0;
+
+/// Determines whether the channels of the [`Compressor`] share their
+/// gain reduction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StereoLink {
+    /// Detects the louder of both channels and applies the same gain to
+    /// each, preserving the stereo image.
+    #[default]
+    Linked,
+    /// Detects and compresses each channel on its own as dual mono,
+    /// such that a loud channel is turned down more than a quiet one.
+    Unlinked,
+}
+
+/// The parameters consumed by [`Compressor`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CompressorParameters {
+    /// The level above which gain reduction is applied, in dB.
+    pub threshold_db: f32,
+    /// The amount of input level above the threshold needed for a 1 dB
+    /// increase in output level, at least `1.0`.
+    pub ratio: f32,
+    /// The time taken for the detector to react to rising levels.
+    pub attack_ms: f32,
+    /// The time taken for the detector to react to falling levels,
+    /// unless the `auto_release` is enabled.
+    pub release_ms: f32,
+    /// The gain applied after compression, in dB.
+    pub makeup_db: f32,
+    /// Determines whether the channels share their gain reduction.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub link: StereoLink,
+    /// Determines if the release time depends on the material, such
+    /// that brief transients recover quickly while sustained passages
+    /// release slowly to avoid pumping.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub auto_release: bool,
+    /// The width of the region around the threshold where the
+    /// compression is eased in, in dB, where `0.0` is a hard knee.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub knee_db: f32,
+}
+
+impl CompressorParameters {
+    /// Creates a new [`CompressorParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to gently tame a mix bus:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::compressor::*;
+    /// let _ = CompressorParameters::new(-18.0, 2.0, 10.0, 100.0, 3.0);
+    /// ```
+    pub fn new(
+        threshold_db: f32,
+        ratio: f32,
+        attack_ms: f32,
+        release_ms: f32,
+        makeup_db: f32,
+    ) -> Self {
+        Self {
+            threshold_db,
+            ratio: ratio.max(1.0),
+            attack_ms: attack_ms.max(0.0),
+            release_ms: release_ms.max(0.0),
+            makeup_db,
+            link: StereoLink::Linked,
+            auto_release: false,
+            knee_db: 0.0,
+        }
+    }
+
+    /// Compute the static gain in dB applied to a detected `level_db`,
+    /// excluding the makeup gain.
+    pub fn gain_db(&self, level_db: f32) -> f32 {
+        let slope = 1.0 - 1.0 / self.ratio.max(1.0);
+        let knee = self.knee_db.max(0.0);
+        let over = level_db - self.threshold_db;
+        if 2.0 * over > knee {
+            -slope * over
+        } else if 2.0 * over >= -knee && knee > 0.0 {
+            // A quadratic joining both slopes without a corner.
+            let x = over + knee / 2.0;
+            -slope * x * x / (2.0 * knee)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The per-sample coefficients of the `auto_release`.
+#[derive(Debug, Clone, Copy)]
+struct AutoRelease {
+    /// The release coefficient after a transient.
+    fast: f32,
+    /// The release coefficient after a sustained passage.
+    slow: f32,
+    /// The increase of the `sustain` per sample above the threshold.
+    charge: f32,
+    /// The decay of the `sustain` per sample below the threshold.
+    discharge: f32,
+}
+
+impl AutoRelease {
+    fn new(sample_rate: f64) -> Self {
+        Self {
+            fast: time_coefficient(AUTO_RELEASE_FAST_MS, sample_rate),
+            slow: time_coefficient(AUTO_RELEASE_SLOW_MS, sample_rate),
+            charge: (1.0 / (AUTO_RELEASE_SUSTAIN_MS as f64 * 0.001 * sample_rate)) as f32,
+            discharge: time_coefficient(AUTO_RELEASE_SLOW_MS, sample_rate),
+        }
+    }
+}
+
+/// A level detector of the [`Compressor`].
+#[derive(Debug, Clone)]
+struct Detector {
+    envelope: EnvelopeFollower,
+    /// How long the level has stayed above the threshold, from `0.0`
+    /// after a transient to `1.0` during a sustained passage.
+    sustain: f32,
+}
+
+impl Detector {
+    /// Feeds a `level` to the detector, returning the updated envelope.
+    fn process(
+        &mut self,
+        level: f32,
+        parameters: &CompressorParameters,
+        auto: &AutoRelease,
+    ) -> f32 {
+        if !parameters.auto_release {
+            return self.envelope.process(level);
+        }
+        let release = auto.fast + (auto.slow - auto.fast) * self.sustain;
+        let envelope = self.envelope.process_with_release(level, release);
+        if gain_to_db(envelope) > parameters.threshold_db {
+            self.sustain = (self.sustain + auto.charge).min(1.0);
+        } else {
+            self.sustain *= auto.discharge;
+        }
+        envelope
+    }
+
+    fn reset(&mut self) {
+        self.envelope.reset();
+        self.sustain = 0.0;
+    }
+}
+
+/// The compressor DSP and its internal state.
+#[derive(Debug)]
+pub struct Compressor {
+    /// The parameters for the effect.
+    parameters: Option<CompressorParameters>,
+    /// The level detector of each channel, where only the first is
+    /// used while the channels are linked.
+    detectors: [Detector; 2],
+    /// The coefficients of the `auto_release`.
+    auto_release: AutoRelease,
+}
+
+impl Compressor {
+    pub fn new(sample_rate: f64) -> Self {
+        let detector = Detector {
+            envelope: EnvelopeFollower::new(Mode::Peak, sample_rate),
+            sustain: 0.0,
+        };
+        Self {
+            parameters: None,
+            detectors: [detector.clone(), detector],
+            auto_release: AutoRelease::new(sample_rate),
+        }
+    }
+}
+
+impl Compressor {
+    /// Initializes the [`Compressor`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: CompressorParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Compressor`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Compressor {
+    type Parameters = CompressorParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let gain = |envelope: f32| {
+            db_to_gain(parameters.gain_db(gain_to_db(envelope)) + parameters.makeup_db)
+        };
+        let auto = &self.auto_release;
+        for frame in buffer.chunks_exact_mut(2) {
+            match parameters.link {
+                StereoLink::Linked => {
+                    let level = frame[0].abs().max(frame[1].abs());
+                    let envelope = self.detectors[0].process(level, &parameters, auto);
+                    let gain = gain(envelope);
+                    frame[0] *= gain;
+                    frame[1] *= gain;
+                }
+                StereoLink::Unlinked => {
+                    for (sample, detector) in frame.iter_mut().zip(self.detectors.iter_mut()) {
+                        *sample *= gain(detector.process(sample.abs(), &parameters, auto));
+                    }
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.detectors.iter_mut().for_each(Detector::reset);
+    }
+
+    fn set_parameters(&mut self, parameters: CompressorParameters) {
+        for detector in self.detectors.iter_mut() {
+            detector.envelope.set_attack_ms(parameters.attack_ms);
+            detector.envelope.set_release_ms(parameters.release_ms);
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::{Compressor, CompressorParameters, StereoLink};
+    use crate::core::{dsp::decibel::gain_to_db, effect::Effect};
+
+    #[test]
+    fn sustained_tone_follows_static_curve() {
+        let (threshold_db, ratio, input_db) = (-20.0, 4.0, -6.0);
+        let mut compressor = Compressor::new(44100.0);
+        compressor.initialize(CompressorParameters::new(
+            threshold_db,
+            ratio,
+            1.0,
+            200.0,
+            0.0,
+        ));
+
+        let amplitude = 10.0_f32.powf(input_db / 20.0);
+        let mut buffer: Vec<f32> = (0..44100)
+            .flat_map(|index| {
+                let x = amplitude * (TAU * 440.0 * index as f32 / 44100.0).sin();
+                [x, x]
+            })
+            .collect();
+        compressor.process(0, &mut buffer);
+
+        let peak = buffer[44100..]
+            .iter()
+            .fold(0.0, |peak: f32, sample| peak.max(sample.abs()));
+        let expected_db = threshold_db + (input_db - threshold_db) / ratio;
+        assert!((gain_to_db(peak) - expected_db).abs() < 0.5);
+    }
+
+    #[test]
+    fn stereo_shares_gain() {
+        let mut compressor = Compressor::new(44100.0);
+        compressor.initialize(CompressorParameters::new(-20.0, 8.0, 1.0, 50.0, 0.0));
+        let mut buffer: Vec<f32> = (0..4410).flat_map(|_| [1.0, 0.25]).collect();
+        compressor.process(0, &mut buffer);
+        for frame in buffer.chunks_exact(2) {
+            assert!((frame[0] / frame[1] - 4.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn unlinked_channels_compress_separately() {
+        let gains = |link| {
+            let mut compressor = Compressor::new(44100.0);
+            compressor.initialize(CompressorParameters {
+                link,
+                ..CompressorParameters::new(-20.0, 8.0, 1.0, 50.0, 0.0)
+            });
+            let mut buffer: Vec<f32> = (0..4410).flat_map(|_| [1.0, 0.05]).collect();
+            compressor.process(0, &mut buffer);
+            let frame = &buffer[buffer.len() - 2..];
+            (frame[0], frame[1] / 0.05)
+        };
+
+        let (left, right) = gains(StereoLink::Linked);
+        assert!((left - right).abs() < 1e-5);
+        let (left, right) = gains(StereoLink::Unlinked);
+        // The right channel sits below the threshold and is untouched.
+        assert!(left < 0.5);
+        assert!((right - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn auto_release_depends_on_material() {
+        // The frames taken to recover to within 1 dB of unity after a
+        // loud burst of `burst` frames.
+        let recovery = |auto_release, burst: usize| {
+            let mut compressor = Compressor::new(44100.0);
+            compressor.initialize(CompressorParameters {
+                auto_release,
+                ..CompressorParameters::new(-20.0, 8.0, 1.0, 200.0, 0.0)
+            });
+            let mut buffer: Vec<f32> = (0..burst + 88200)
+                .flat_map(|index| if index < burst { [1.0; 2] } else { [0.01; 2] })
+                .collect();
+            compressor.process(0, &mut buffer);
+            buffer[2 * burst..]
+                .chunks_exact(2)
+                .position(|frame| gain_to_db(frame[0] / 0.01) > -1.0)
+                .unwrap()
+        };
+
+        let (transient, sustained) = (recovery(true, 441), recovery(true, 44100));
+        assert!(sustained > 4 * transient, "{} {}", transient, sustained);
+        assert!(transient < recovery(false, 441));
+        assert!(sustained > recovery(false, 44100));
+        assert_eq!(recovery(false, 441), recovery(false, 44100));
+    }
+
+    #[test]
+    fn knee_is_smooth() {
+        let parameters = CompressorParameters {
+            knee_db: 10.0,
+            ..CompressorParameters::new(-20.0, 4.0, 1.0, 100.0, 0.0)
+        };
+        let step = 0.01;
+        let mut previous = parameters.gain_db(-40.0);
+        let mut previous_slope = 0.0;
+        for index in 1..=3000 {
+            let gain = parameters.gain_db(-40.0 + index as f32 * step);
+            let slope = (gain - previous) / step;
+            assert!(slope <= previous_slope + 1e-2);
+            assert!((slope - previous_slope).abs() < 0.05);
+            previous = gain;
+            previous_slope = slope;
+        }
+        assert!((previous_slope + 0.75).abs() < 1e-2);
+
+        // The knee only eases in the curve within its width.
+        let hard = CompressorParameters {
+            knee_db: 0.0,
+            ..parameters
+        };
+        for level_db in [-40.0, -25.0, -15.0, 0.0] {
+            assert_eq!(parameters.gain_db(level_db), hard.gain_db(level_db));
+        }
+        assert!(parameters.gain_db(-20.0) < hard.gain_db(-20.0));
+    }
+}
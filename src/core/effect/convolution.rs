@@ -0,0 +1,282 @@
+//! Convolves the signal with a recorded impulse response.
+//!
+//! # Overview
+//!
+//! The impulse response is split into partitions of `block_size` frames,
+//! each transformed once up front. Every block of input is transformed,
+//! stored in a frequency-domain delay line, and multiplied against all of
+//! the partitions, such that the work per block grows linearly with the
+//! length of the impulse response rather than with its square. The
+//! output lags the input by exactly one block.
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::dsp::fft::{Complex, Fft};
+
+/// The parameters consumed by [`Convolver`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConvolverParameters {
+    /// Determines how much of the convolved signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl ConvolverParameters {
+    /// Creates a new [`ConvolverParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a fully wet convolution:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::convolution::*;
+    /// let _ = ConvolverParameters::new(1.0);
+    /// ```
+    pub fn new(mix: f32) -> Self {
+        Self {
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// The per-channel state of the [`Convolver`].
+#[derive(Debug, Clone)]
+struct Channel {
+    /// The spectra of the impulse response partitions, back to back.
+    partitions: Vec<Complex<f32>>,
+    /// The spectra of the most recent input blocks, back to back.
+    history: Vec<Complex<f32>>,
+    /// The input block being collected.
+    input: Vec<f32>,
+    /// The previous input block, lined up with the output for mixing.
+    dry: Vec<f32>,
+    /// The output block being played back.
+    output: Vec<f32>,
+    /// The second half of the previous convolved block.
+    overlap: Vec<f32>,
+}
+
+impl Channel {
+    fn new(fft: &Fft, ir: &[f32], block_size: usize) -> Self {
+        let size = block_size * 2;
+        let count = ir.len().div_ceil(block_size).max(1);
+        let mut partitions = vec![Complex::ZERO; count * size];
+        for (index, chunk) in ir.chunks(block_size).enumerate() {
+            let partition = &mut partitions[index * size..(index + 1) * size];
+            for (bin, &x) in partition.iter_mut().zip(chunk.iter()) {
+                bin.re = x;
+            }
+            fft.forward(partition);
+        }
+        Self {
+            partitions,
+            history: vec![Complex::ZERO; count * size],
+            input: vec![0.0; block_size],
+            dry: vec![0.0; block_size],
+            output: vec![0.0; block_size],
+            overlap: vec![0.0; block_size],
+        }
+    }
+
+    /// Convolves the collected input block, where `slot` is the index of
+    /// the block within the history.
+    fn convolve(&mut self, fft: &Fft, slot: usize, scratch: &mut [Complex<f32>]) {
+        let size = scratch.len();
+        let block_size = size / 2;
+        let count = self.partitions.len() / size;
+
+        let spectrum = &mut self.history[slot * size..(slot + 1) * size];
+        for (bin, &x) in spectrum.iter_mut().zip(self.input.iter()) {
+            *bin = Complex::new(x, 0.0);
+        }
+        spectrum[block_size..].fill(Complex::ZERO);
+        fft.forward(spectrum);
+
+        scratch.fill(Complex::ZERO);
+        for partition in 0..count {
+            let block = (slot + count - partition) % count;
+            let x = &self.history[block * size..(block + 1) * size];
+            let h = &self.partitions[partition * size..(partition + 1) * size];
+            for ((y, &x), &h) in scratch.iter_mut().zip(x.iter()).zip(h.iter()) {
+                *y += x * h;
+            }
+        }
+        fft.inverse(scratch);
+
+        for (index, output) in self.output.iter_mut().enumerate() {
+            *output = scratch[index].re + self.overlap[index];
+        }
+        for (overlap, y) in self.overlap.iter_mut().zip(scratch[block_size..].iter()) {
+            *overlap = y.re;
+        }
+        self.dry.copy_from_slice(&self.input);
+    }
+
+    fn clear(&mut self) {
+        self.history.fill(Complex::ZERO);
+        for buffer in [
+            &mut self.input,
+            &mut self.dry,
+            &mut self.output,
+            &mut self.overlap,
+        ] {
+            buffer.fill(0.0);
+        }
+    }
+}
+
+/// The convolution DSP and its internal state.
+#[derive(Debug)]
+pub struct Convolver {
+    /// The parameters for the effect.
+    parameters: Option<ConvolverParameters>,
+    /// The transform shared by both channels.
+    fft: Fft,
+    /// The state of each channel.
+    channels: [Channel; 2],
+    /// The position within the current block.
+    index: usize,
+    /// The slot of the current block within the history.
+    slot: usize,
+    /// The work buffer for the accumulated spectrum.
+    scratch: Vec<Complex<f32>>,
+}
+
+impl Convolver {
+    /// Creates a [`Convolver`] applying a mono impulse response to both
+    /// channels, processing in blocks of at least `block_size` frames
+    /// rounded up to a power of two.
+    pub fn new(ir: &[f32], block_size: usize) -> Self {
+        Self::stereo(ir, ir, block_size)
+    }
+
+    /// Creates a [`Convolver`] with a separate impulse response for
+    /// each channel.
+    pub fn stereo(left: &[f32], right: &[f32], block_size: usize) -> Self {
+        let block_size = block_size.max(1).next_power_of_two();
+        let fft = Fft::new(block_size * 2);
+        // Both channels share the same history length.
+        let len = left.len().max(right.len());
+        let pad = |ir: &[f32]| {
+            let mut padded = ir.to_vec();
+            padded.resize(len, 0.0);
+            padded
+        };
+        let channels = [
+            Channel::new(&fft, &pad(left), block_size),
+            Channel::new(&fft, &pad(right), block_size),
+        ];
+        Self {
+            parameters: None,
+            fft,
+            channels,
+            index: 0,
+            slot: 0,
+            scratch: vec![Complex::ZERO; block_size * 2],
+        }
+    }
+}
+
+impl Convolver {
+    /// Initializes the [`Convolver`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: ConvolverParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Convolver`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Convolver {
+    type Parameters = ConvolverParameters;
+
+    /// The delay introduced by the block processing, in frames.
+    fn latency_samples(&self) -> usize {
+        self.channels[0].input.len()
+    }
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let block_size = self.latency_samples();
+        let count = self.channels[0].partitions.len() / (block_size * 2);
+        for frame in buffer.chunks_exact_mut(2) {
+            for (sample, channel) in frame.iter_mut().zip(self.channels.iter_mut()) {
+                let wet = channel.output[self.index];
+                channel.input[self.index] = *sample;
+                *sample = channel.dry[self.index] * (1.0 - parameters.mix) + wet * parameters.mix;
+            }
+            self.index += 1;
+            if self.index == block_size {
+                for channel in self.channels.iter_mut() {
+                    channel.convolve(&self.fft, self.slot, &mut self.scratch);
+                }
+                self.index = 0;
+                self.slot = (self.slot + 1) % count;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.channels.iter_mut().for_each(Channel::clear);
+        self.index = 0;
+        self.slot = 0;
+    }
+
+    fn set_parameters(&mut self, parameters: ConvolverParameters) {
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Convolver, ConvolverParameters};
+    use crate::core::effect::Effect;
+
+    #[test]
+    fn impulse_yields_ir() {
+        let ir: Vec<f32> = (0..1000)
+            .map(|index| (index as f32 * 0.37).sin() * 0.999_f32.powi(index))
+            .collect();
+        let mut convolver = Convolver::new(&ir, 64);
+        convolver.initialize(ConvolverParameters::new(1.0));
+        let latency = convolver.latency_samples();
+        assert_eq!(latency, 64);
+
+        let mut buffer = vec![0.0; (latency + ir.len() + 100) * 2];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        // Uneven buffer sizes should not matter.
+        for chunk in buffer.chunks_mut(2 * 37) {
+            convolver.process(0, chunk);
+        }
+
+        assert!(buffer[..latency * 2].iter().all(|&x| x == 0.0));
+        for (frame, &expected) in buffer[latency * 2..].chunks_exact(2).zip(ir.iter()) {
+            assert!((frame[0] - expected).abs() < 1e-4);
+            assert!((frame[1] - expected).abs() < 1e-4);
+        }
+        let tail = &buffer[(latency + ir.len()) * 2..];
+        assert!(tail.iter().all(|&x| x.abs() < 1e-4));
+    }
+
+    #[test]
+    fn stereo_irs_are_separate() {
+        let mut convolver = Convolver::stereo(&[1.0], &[0.0, 0.5], 16);
+        convolver.initialize(ConvolverParameters::new(1.0));
+        let mut buffer = vec![0.0; 64];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        convolver.process(0, &mut buffer);
+        assert!((buffer[32] - 1.0).abs() < 1e-5);
+        assert!(buffer[33].abs() < 1e-5);
+        assert!((buffer[35] - 0.5).abs() < 1e-5);
+    }
+}
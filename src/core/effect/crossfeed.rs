@@ -0,0 +1,178 @@
+//! Bleeds each channel into the other for headphone listening.
+//!
+//! # Overview
+//!
+//! On speakers, each ear also hears the opposite speaker, slightly later
+//! and with its highs shadowed by the head. Headphones remove that path,
+//! so hard-panned sources sound unnaturally wide. [`Crossfeed`] restores
+//! it by mixing a low-passed, delayed copy of each channel into the
+//! other, then scaling the result such that low frequencies shared by
+//! both channels keep their level.
+use super::Effect;
+use crate::core::dsp::{DelayLine, OnePole};
+
+/// The largest amount accepted by [`CrossfeedParameters`], which keeps
+/// the bleed well below the direct signal.
+pub const MAX_AMOUNT: f32 = 0.5;
+
+/// The longest delay accepted by [`CrossfeedParameters`], around the
+/// time taken by sound to travel around the head.
+pub const MAX_DELAY_MS: f32 = 1.0;
+
+/// The lowest cutoff accepted by [`CrossfeedParameters`].
+pub const MIN_LOWPASS_HZ: f32 = 100.0;
+
+/// The parameters consumed by [`Crossfeed`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrossfeedParameters {
+    /// The gain of the bleed relative to the direct signal, clamped to
+    /// `0.0..=MAX_AMOUNT`, where `0.0` is transparent.
+    pub amount: f32,
+    /// The delay of the bleed, clamped to `0.0..=MAX_DELAY_MS`.
+    pub delay_ms: f32,
+    /// The cutoff of the low-pass applied to the bleed, in Hz.
+    pub lowpass_hz: f32,
+}
+
+impl CrossfeedParameters {
+    /// Creates a new [`CrossfeedParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a moderate crossfeed, close to the defaults of BS2B:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::crossfeed::*;
+    /// let _ = CrossfeedParameters::new(0.3, 0.3, 700.0);
+    /// ```
+    pub fn new(amount: f32, delay_ms: f32, lowpass_hz: f32) -> Self {
+        Self {
+            amount: amount.clamp(0.0, MAX_AMOUNT),
+            delay_ms: delay_ms.clamp(0.0, MAX_DELAY_MS),
+            lowpass_hz: lowpass_hz.max(MIN_LOWPASS_HZ),
+        }
+    }
+}
+
+/// The crossfeed DSP and its internal state.
+#[derive(Debug)]
+pub struct Crossfeed {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<CrossfeedParameters>,
+    /// The delay line of the bleed from each channel.
+    lines: [DelayLine; 2],
+    /// The low-pass of the bleed from each channel.
+    filters: [OnePole; 2],
+}
+
+impl Crossfeed {
+    /// Creates a new [`Crossfeed`], allocating enough delay for the
+    /// longest delay up front.
+    pub fn new(sample_rate: f64) -> Self {
+        let len = (MAX_DELAY_MS as f64 * 0.001 * sample_rate) as usize + 2;
+        let line = DelayLine::new(len);
+        let filter = OnePole::new(MIN_LOWPASS_HZ as f64, sample_rate);
+        Self {
+            sample_rate,
+            parameters: None,
+            lines: [line.clone(), line],
+            filters: [filter; 2],
+        }
+    }
+}
+
+impl Crossfeed {
+    /// Initializes the [`Crossfeed`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: CrossfeedParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Crossfeed`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Crossfeed {
+    type Parameters = CrossfeedParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let amount = parameters.amount.clamp(0.0, MAX_AMOUNT);
+        let delay =
+            parameters.delay_ms.clamp(0.0, MAX_DELAY_MS) * (self.sample_rate * 0.001) as f32;
+        // Shared low frequencies add up to `1.0 + amount`.
+        let normalize = 1.0 / (1.0 + amount);
+        for frame in buffer.chunks_exact_mut(2) {
+            let mut bleed = [0.0; 2];
+            for (channel, bleed) in bleed.iter_mut().enumerate() {
+                let line = &mut self.lines[channel];
+                line.write(self.filters[channel].process_lowpass(frame[channel]));
+                *bleed = line.read(delay);
+            }
+            frame[0] = (frame[0] + bleed[1] * amount) * normalize;
+            frame[1] = (frame[1] + bleed[0] * amount) * normalize;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.lines.iter_mut().for_each(DelayLine::clear);
+        self.filters.iter_mut().for_each(OnePole::reset);
+    }
+
+    fn set_parameters(&mut self, parameters: CrossfeedParameters) {
+        let cutoff = parameters.lowpass_hz.max(MIN_LOWPASS_HZ) as f64;
+        for filter in self.filters.iter_mut() {
+            filter.set_cutoff(cutoff, self.sample_rate);
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Crossfeed, CrossfeedParameters};
+    use crate::core::effect::Effect;
+
+    /// A low sine panned hard to the left.
+    fn left_only() -> Vec<f32> {
+        (0..44100)
+            .flat_map(|index| [(index as f32 * 0.02).sin(), 0.0])
+            .collect()
+    }
+
+    fn energy(buffer: &[f32], channel: usize) -> f32 {
+        buffer.iter().skip(channel).step_by(2).map(|x| x * x).sum()
+    }
+
+    #[test]
+    fn hard_pan_bleeds_into_other_channel() {
+        let mut crossfeed = Crossfeed::new(44100.0);
+        crossfeed.initialize(CrossfeedParameters::new(0.3, 0.3, 700.0));
+        let mut buffer = left_only();
+        crossfeed.process(0, &mut buffer);
+
+        let (left, right) = (energy(&buffer, 0), energy(&buffer, 1));
+        assert!(right > 0.0);
+        // The image is narrowed rather than collapsed to mono.
+        assert!(right < left * 0.25, "{} {}", left, right);
+    }
+
+    #[test]
+    fn zero_amount_is_transparent() {
+        let mut crossfeed = Crossfeed::new(44100.0);
+        crossfeed.initialize(CrossfeedParameters::new(0.0, 0.3, 700.0));
+        let input = left_only();
+        let mut buffer = input.clone();
+        crossfeed.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+    }
+}
@@ -0,0 +1,169 @@
+//! Removes constant offsets from the signal.
+use super::Effect;
+use crate::core::dsp::denormal::flush;
+
+/// The pole of the filter used by [`DcBlockerParameters::default`].
+pub const DEFAULT_COEFFICIENT: f32 = 0.995;
+
+/// The largest pole accepted by [`DcBlockerParameters`], keeping the
+/// filter stable.
+pub const MAX_COEFFICIENT: f32 = 0.9999;
+
+/// The parameters consumed by [`DcBlocker`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DcBlockerParameters {
+    /// The pole of the filter, clamped to `0.0..=MAX_COEFFICIENT`,
+    /// where values closer to `1.0` keep more of the bass at the cost
+    /// of a longer settling time.
+    pub coefficient: f32,
+}
+
+impl DcBlockerParameters {
+    /// Creates a new [`DcBlockerParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to keep more of the sub-bass:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::dc_blocker::*;
+    /// let _ = DcBlockerParameters::new(0.999);
+    /// ```
+    pub fn new(coefficient: f32) -> Self {
+        Self {
+            coefficient: coefficient.clamp(0.0, MAX_COEFFICIENT),
+        }
+    }
+}
+
+impl Default for DcBlockerParameters {
+    fn default() -> Self {
+        Self::new(DEFAULT_COEFFICIENT)
+    }
+}
+
+/// The DC blocker DSP and its internal state, applying
+/// `y[n] = x[n] - x[n - 1] + R * y[n - 1]` to each channel.
+#[derive(Debug)]
+pub struct DcBlocker {
+    /// The parameters for the effect.
+    parameters: Option<DcBlockerParameters>,
+    /// The previous input of each channel.
+    x1: [f32; 2],
+    /// The previous output of each channel.
+    y1: [f32; 2],
+}
+
+impl DcBlocker {
+    pub fn new() -> Self {
+        Self {
+            parameters: None,
+            x1: [0.0; 2],
+            y1: [0.0; 2],
+        }
+    }
+}
+
+impl Default for DcBlocker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DcBlocker {
+    /// Initializes the [`DcBlocker`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: DcBlockerParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`DcBlocker`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for DcBlocker {
+    type Parameters = DcBlockerParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let r = parameters.coefficient.clamp(0.0, MAX_COEFFICIENT);
+        for frame in buffer.chunks_exact_mut(2) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let y = flush(*sample - self.x1[channel] + r * self.y1[channel]);
+                self.x1[channel] = *sample;
+                self.y1[channel] = y;
+                *sample = y;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.x1 = [0.0; 2];
+        self.y1 = [0.0; 2];
+    }
+
+    fn set_parameters(&mut self, parameters: DcBlockerParameters) {
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::{DcBlocker, DcBlockerParameters};
+    use crate::core::effect::Effect;
+
+    #[test]
+    fn tail_flushes_to_zero() {
+        let mut blocker = DcBlocker::new();
+        blocker.initialize(DcBlockerParameters::new(0.995));
+        let mut buffer = vec![0.0; 2 * 44100];
+        buffer[0] = 1.0;
+        blocker.process(0, &mut buffer);
+        assert!(buffer.iter().all(|sample| !sample.is_subnormal()));
+        assert_eq!(blocker.y1, [0.0; 2]);
+    }
+
+    fn offset_tone(frequency: f32, offset: f32) -> Vec<f32> {
+        (0..44100)
+            .flat_map(|index| {
+                let x = 0.5 * (TAU * frequency * index as f32 / 44100.0).sin() + offset;
+                [x, -x]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn offset_is_removed() {
+        let mut blocker = DcBlocker::new();
+        blocker.initialize(DcBlockerParameters::default());
+        let mut buffer = offset_tone(441.0, 0.3);
+        blocker.process(0, &mut buffer);
+        // A window of whole cycles, starting after about 10 time constants.
+        let window = &buffer[2 * 4000..2 * 44000];
+        for channel in 0..2 {
+            let mean = window.iter().skip(channel).step_by(2).sum::<f32>() / 40000.0;
+            assert!(mean.abs() < 1e-3, "{}", mean);
+        }
+    }
+
+    #[test]
+    fn bass_is_kept() {
+        let mut blocker = DcBlocker::new();
+        blocker.initialize(DcBlockerParameters::default());
+        let mut buffer = offset_tone(100.0, 0.0);
+        blocker.process(0, &mut buffer);
+        let peak = buffer[2 * 22050..]
+            .iter()
+            .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+        assert!(peak > 0.45, "{}", peak);
+    }
+}
@@ -0,0 +1,206 @@
+//! Repeats the input after a fixed number of frames, feeding the
+//! repetitions back into themselves.
+
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::dsp::denormal::flush;
+
+/// The largest feedback amount accepted by [`DelayParameters`].
+pub const MAX_FEEDBACK: f32 = 0.99;
+
+/// The parameters consumed by [`Delay`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DelayParameters {
+    /// The number of frames between the input and its first echo.
+    pub delay_samples: usize,
+    /// Determines how much of each echo is fed back into the delay
+    /// line, clamped to `0.0..=MAX_FEEDBACK` to avoid runaway.
+    pub feedback: f32,
+    /// Determines how much of the echoes are mixed with the original
+    /// audio.
+    pub mix: f32,
+}
+
+impl DelayParameters {
+    /// Creates a new [`DelayParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a dotted 8th note echo in a 128 BPM track:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::delay::*;
+    /// let delay_samples = (60.0 / 128.0 * 0.75 * 44100.0) as usize;
+    /// let _ = DelayParameters::new(delay_samples, 0.5, 0.4);
+    /// ```
+    pub fn new(delay_samples: usize, feedback: f32, mix: f32) -> Self {
+        Self {
+            delay_samples,
+            feedback: feedback.clamp(0.0, MAX_FEEDBACK),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// The delay DSP and its internal state.
+#[derive(Debug)]
+pub struct Delay {
+    /// The parameters for the effect.
+    parameters: Option<DelayParameters>,
+    /// The interleaved stereo delay line.
+    line: Vec<f32>,
+    /// The frame in the delay line that is read and written next.
+    index: usize,
+}
+
+impl Delay {
+    pub fn new() -> Self {
+        Self {
+            parameters: None,
+            line: vec![],
+            index: 0,
+        }
+    }
+}
+
+impl Default for Delay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Delay {
+    /// Initializes the [`Delay`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: DelayParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Delay`] i.e. turning it off, freeing the
+    /// delay line.
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.line = vec![];
+        self.index = 0;
+    }
+}
+
+impl Effect for Delay {
+    type Parameters = DelayParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        if parameters.delay_samples == 0 {
+            return;
+        }
+        let feedback = parameters.feedback.clamp(0.0, MAX_FEEDBACK);
+        let mix = parameters.mix.clamp(0.0, 1.0);
+        for frame in buffer.chunks_exact_mut(2) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let slot = &mut self.line[self.index * 2 + channel];
+                let delayed = *slot;
+                *slot = flush(*sample + delayed * feedback);
+                *sample = *sample * (1.0 - mix) + delayed * mix;
+            }
+            self.index = (self.index + 1) % parameters.delay_samples;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.line.fill(0.0);
+        self.index = 0;
+    }
+
+    /// Replaces the parameters of the effect, reallocating and clearing
+    /// the delay line if `delay_samples` changes.
+    fn set_parameters(&mut self, parameters: DelayParameters) {
+        if self.line.len() != parameters.delay_samples * 2 {
+            self.line = vec![0.0; parameters.delay_samples * 2];
+            self.index = 0;
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Delay, DelayParameters};
+    use crate::core::effect::Effect;
+
+    #[test]
+    fn impulse_echoes() {
+        let mut delay = Delay::new();
+        delay.initialize(DelayParameters::new(100, 0.5, 0.5));
+        let mut buffer = vec![0.0; 2 * 350];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        delay.process(0, &mut buffer);
+
+        for (frame, expected) in [(0, 0.5), (100, 0.5), (200, 0.25), (300, 0.125)] {
+            assert_eq!(buffer[frame * 2], expected);
+            assert_eq!(buffer[frame * 2 + 1], expected);
+        }
+        let echoes = buffer.iter().filter(|&&sample| sample != 0.0).count();
+        assert_eq!(echoes, 8);
+    }
+
+    #[test]
+    fn mix_is_clamped() {
+        let mut delay = Delay::new();
+        delay.initialize(DelayParameters {
+            mix: 2.0,
+            ..DelayParameters::new(100, 0.5, 1.0)
+        });
+        let mut buffer = vec![0.0; 2 * 150];
+        buffer[0] = 1.0;
+        delay.process(0, &mut buffer);
+        assert_eq!(buffer[0], 0.0);
+        assert_eq!(buffer[200], 1.0);
+    }
+
+    #[test]
+    fn tail_flushes_to_zero() {
+        let mut delay = Delay::new();
+        delay.initialize(DelayParameters::new(10, 0.5, 1.0));
+        let mut buffer = vec![0.0; 2 * 10 * 200];
+        buffer[0] = 1.0;
+        delay.process(0, &mut buffer);
+        assert!(buffer.iter().all(|sample| !sample.is_subnormal()));
+        assert!(delay.line.iter().all(|&sample| sample == 0.0));
+    }
+
+    #[test]
+    fn planar_matches_interleaved() {
+        let mut interleaved = Delay::new();
+        let mut planar = Delay::new();
+        interleaved.initialize(DelayParameters::new(150, 0.5, 0.5));
+        planar.initialize(DelayParameters::new(150, 0.5, 0.5));
+        let mut buffer: Vec<f32> = (0..2 * 1000)
+            .map(|index| (index as f32 * 0.1).sin())
+            .collect();
+        let mut left: Vec<f32> = buffer.iter().step_by(2).copied().collect();
+        let mut right: Vec<f32> = buffer.iter().skip(1).step_by(2).copied().collect();
+        interleaved.process(0, &mut buffer);
+        planar.process_planar(1000, &mut [&mut left, &mut right]);
+        for (frame, (l, r)) in buffer.chunks_exact(2).zip(left.iter().zip(right.iter())) {
+            assert_eq!(frame[0], *l);
+            assert_eq!(frame[1], *r);
+        }
+    }
+
+    #[test]
+    fn resize_while_running() {
+        let mut delay = Delay::new();
+        delay.initialize(DelayParameters::new(100, 0.5, 0.5));
+        delay.process(0, &mut vec![1.0; 2 * 75]);
+        delay.set_parameters(DelayParameters::new(10, 0.5, 0.5));
+        delay.process(0, &mut vec![1.0; 2 * 75]);
+        delay.deinitialize();
+        delay.process(0, &mut vec![1.0; 2 * 75]);
+    }
+}
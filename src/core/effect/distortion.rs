@@ -0,0 +1,245 @@
+//! Saturates the signal with a soft clipper.
+//!
+//! # Overview
+//!
+//! The shaper is `tanh(drive * x)`, which adds harmonics that can reach
+//! past Nyquist and fold back into the audible range. Running the shaper
+//! inside an [`Oversampler`] at 2x or 4x the sample rate filters those
+//! harmonics out before they alias, at the cost of some latency.
+use super::{Effect, UNKNOWN_POSITION};
+use crate::core::dsp::{delay_comp::DelayCompensator, oversample, OnePole, Oversampler};
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The highest oversampling factor accepted by [`DistortionParameters`].
+pub const MAX_OVERSAMPLE: usize = oversample::MAX_FACTOR;
+
+/// The parameters consumed by [`Distortion`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DistortionParameters {
+    /// The gain applied before the shaper, at least `1.0`.
+    pub drive: f32,
+    /// The brightness of the output from `0.0` to `1.0`, sweeping the
+    /// post-shaper low-pass from 200 Hz to 20 kHz.
+    pub tone: f32,
+    /// Determines how much of the distorted signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+    /// The oversampling factor, one of `1`, `2`, or `4`.
+    pub oversample: usize,
+}
+
+impl DistortionParameters {
+    /// Creates a new [`DistortionParameters`], rounding the
+    /// `oversample` factor down to a supported value.
+    ///
+    /// # Example
+    ///
+    /// If you want a warm overdrive with little aliasing:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::distortion::*;
+    /// let _ = DistortionParameters::new(4.0, 0.6, 1.0, 4);
+    /// ```
+    pub fn new(drive: f32, tone: f32, mix: f32, oversample: usize) -> Self {
+        Self {
+            drive: drive.max(1.0),
+            tone: tone.clamp(0.0, 1.0),
+            mix: mix.clamp(0.0, 1.0),
+            oversample: oversample::supported_factor(oversample),
+        }
+    }
+
+    /// The cutoff of the tone control in Hz.
+    pub fn tone_cutoff(&self) -> f64 {
+        200.0 * 100.0_f64.powf(self.tone as f64)
+    }
+}
+
+/// The number of frames handed to the [`Oversampler`] at a time, such
+/// that the dry copy fits on the stack.
+const BLOCK_FRAMES: usize = 64;
+
+/// Applies the soft clipper to every sample, at whichever rate the
+/// [`Oversampler`] runs it.
+#[derive(Debug)]
+struct Saturator {
+    /// The gain applied before the shaper.
+    drive: f32,
+}
+
+impl Effect for Saturator {
+    type Parameters = f32;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = (self.drive * *sample).tanh();
+        }
+    }
+
+    fn reset(&mut self) {}
+
+    fn set_parameters(&mut self, drive: f32) {
+        self.drive = drive;
+    }
+}
+
+/// The distortion DSP and its internal state.
+#[derive(Debug)]
+pub struct Distortion {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<DistortionParameters>,
+    /// Runs the soft clipper at the oversampled rate.
+    oversampler: Oversampler<Saturator>,
+    /// The tone control of each channel, at the original rate.
+    tone: [OnePole; 2],
+    /// Delays the dry signal of each channel to line up with the
+    /// oversampled path.
+    dry: [DelayCompensator; 2],
+}
+
+impl Distortion {
+    pub fn new(sample_rate: f64) -> Self {
+        let dry = DelayCompensator::new(0);
+        Self {
+            sample_rate,
+            parameters: None,
+            oversampler: Oversampler::new(Saturator { drive: 1.0 }, 1),
+            // Tuned by `set_parameters` before anything is processed.
+            tone: [OnePole::new(20000.0, sample_rate); 2],
+            dry: [dry.clone(), dry],
+        }
+    }
+}
+
+impl Distortion {
+    /// Initializes the [`Distortion`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: DistortionParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Distortion`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Distortion {
+    type Parameters = DistortionParameters;
+
+    /// The delay introduced by oversampling, in frames.
+    fn latency_samples(&self) -> usize {
+        match self.parameters {
+            Some(_) => self.oversampler.latency_samples(),
+            None => 0,
+        }
+    }
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let mut dry = [0.0; 2 * BLOCK_FRAMES];
+        for block in buffer.chunks_mut(2 * BLOCK_FRAMES) {
+            let dry = &mut dry[..block.len()];
+            dry.copy_from_slice(block);
+            // The saturator keeps no state over time, so the position is
+            // moot.
+            self.oversampler.process(UNKNOWN_POSITION, block);
+            for (wet, dry) in block.chunks_exact_mut(2).zip(dry.chunks_exact(2)) {
+                for (((wet, &dry), tone), delay) in wet
+                    .iter_mut()
+                    .zip(dry.iter())
+                    .zip(self.tone.iter_mut())
+                    .zip(self.dry.iter_mut())
+                {
+                    let shaped = tone.process_lowpass(*wet);
+                    *wet = delay.process(dry) * (1.0 - parameters.mix) + shaped * parameters.mix;
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.oversampler.reset();
+        self.tone.iter_mut().for_each(OnePole::reset);
+        self.dry.iter_mut().for_each(DelayCompensator::reset);
+    }
+
+    /// Replaces the parameters of the effect, reallocating the
+    /// oversampling filters if the factor changes.
+    fn set_parameters(&mut self, parameters: DistortionParameters) {
+        self.oversampler.set_factor(parameters.oversample);
+        self.oversampler.reserve(2 * BLOCK_FRAMES);
+        self.oversampler.set_parameters(parameters.drive);
+        let latency = self.oversampler.latency_samples();
+        for dry in self.dry.iter_mut() {
+            if dry.delay() != latency {
+                dry.set_delay(latency);
+            }
+        }
+        let cutoff = parameters.tone_cutoff();
+        for tone in self.tone.iter_mut() {
+            tone.set_cutoff(cutoff, self.sample_rate);
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{Distortion, DistortionParameters};
+    use crate::core::effect::Effect;
+
+    /// Measures the magnitude of a `frequency` in the left channel.
+    fn goertzel(buffer: &[f32], frequency: f64, sample_rate: f64) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        let frames = buffer.len() / 2;
+        for (index, frame) in buffer.chunks_exact(2).enumerate() {
+            let phase = TAU * frequency * index as f64 / sample_rate;
+            re += frame[0] as f64 * phase.cos();
+            im -= frame[0] as f64 * phase.sin();
+        }
+        re.hypot(im) / frames as f64
+    }
+
+    fn aliasing(oversample: usize) -> f64 {
+        let sample_rate = 44100.0;
+        let mut distortion = Distortion::new(sample_rate);
+        distortion.initialize(DistortionParameters::new(10.0, 1.0, 1.0, oversample));
+        let mut buffer: Vec<f32> = (0..44100)
+            .flat_map(|index| {
+                let x = (TAU * 15000.0 * index as f64 / sample_rate).sin() as f32 * 0.8;
+                [x, x]
+            })
+            .collect();
+        distortion.process(0, &mut buffer);
+        // The third harmonic at 45 kHz folds back to 900 Hz.
+        goertzel(&buffer[4410..], 900.0, sample_rate)
+    }
+
+    #[test]
+    fn oversampling_reduces_aliasing() {
+        assert!(aliasing(4) * 10.0 < aliasing(1));
+    }
+
+    #[test]
+    fn dry_is_aligned() {
+        let mut distortion = Distortion::new(44100.0);
+        distortion.initialize(DistortionParameters::new(1.0, 1.0, 0.0, 2));
+        let latency = distortion.latency_samples();
+        assert!(latency > 0);
+        let input: Vec<f32> = (0..256).map(|index| index as f32).collect();
+        let mut buffer = input.clone();
+        distortion.process(0, &mut buffer);
+        assert_eq!(buffer[latency * 2..], input[..input.len() - latency * 2]);
+    }
+}
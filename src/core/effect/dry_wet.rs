@@ -0,0 +1,110 @@
+//! Blends the output of an effect with its unprocessed input.
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::transport::Transport;
+
+/// Mixes the dry input with the wet output of an inner [`Effect`].
+#[derive(Debug)]
+pub struct DryWet<E: Effect> {
+    /// The effect producing the wet signal.
+    effect: E,
+    /// Determines how much of the wet signal is mixed in, from `0.0`
+    /// for fully dry to `1.0` for fully wet.
+    mix: f32,
+    /// A copy of the dry input, reused across calls to `process`.
+    scratch: Vec<f32>,
+}
+
+impl<E: Effect> DryWet<E> {
+    /// Creates a new [`DryWet`] wrapping an `effect`.
+    pub fn new(effect: E, mix: f32) -> Self {
+        Self {
+            effect,
+            mix: mix.clamp(0.0, 1.0),
+            scratch: vec![],
+        }
+    }
+
+    /// Preallocates the scratch buffer for blocks of up to `len`
+    /// samples, such that `process` does not allocate.
+    pub fn reserve(&mut self, len: usize) {
+        if self.scratch.len() < len {
+            self.scratch.resize(len, 0.0);
+        }
+    }
+
+    /// Sets the amount of wet signal that is mixed in.
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    /// The amount of wet signal that is mixed in.
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+
+    /// Runs the inner effect over the `buffer` with `process`, then
+    /// mixes the dry input back in.
+    fn mix_with(&mut self, buffer: &mut [f32], process: impl FnOnce(&mut E, &mut [f32])) {
+        self.reserve(buffer.len());
+        let dry = &mut self.scratch[..buffer.len()];
+        dry.copy_from_slice(buffer);
+        process(&mut self.effect, buffer);
+        for (wet, dry) in buffer.iter_mut().zip(dry.iter()) {
+            *wet = dry * (1.0 - self.mix) + *wet * self.mix;
+        }
+    }
+
+    /// A reference to the inner effect.
+    pub fn inner(&self) -> &E {
+        &self.effect
+    }
+
+    /// A mutable reference to the inner effect.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.effect
+    }
+}
+
+impl<E: Effect> Effect for DryWet<E> {
+    type Parameters = E::Parameters;
+
+    fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        self.mix_with(buffer, |effect, buffer| effect.process(position, buffer));
+    }
+
+    fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [f32]) {
+        self.mix_with(buffer, |effect, buffer| {
+            effect.process_with_transport(transport, buffer)
+        });
+    }
+
+    fn reset(&mut self) {
+        self.effect.reset();
+    }
+
+    fn set_parameters(&mut self, parameters: E::Parameters) {
+        self.effect.set_parameters(parameters);
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.effect.latency_samples()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DryWet;
+    use crate::core::effect::{Effect, TranceGate, TranceGateParameters};
+
+    #[test]
+    fn fully_dry_passes_through() {
+        let mut dry_wet = DryWet::new(TranceGate::new(), 0.0);
+        dry_wet.set_parameters(TranceGateParameters::new(0.01, 1.0, 44100.0));
+        let input: Vec<f32> = (0..1024).map(|index| (index as f32 * 0.1).sin()).collect();
+        let mut buffer = input.clone();
+        dry_wet.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+    }
+}
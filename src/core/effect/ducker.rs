@@ -0,0 +1,206 @@
+//! Attenuates the signal while a sidechain is loud.
+use super::Effect;
+use crate::core::dsp::{
+    decibel::{db_to_gain, gain_to_db},
+    envelope::{EnvelopeFollower, Mode},
+};
+
+/// The parameters consumed by [`Ducker`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DuckerParameters {
+    /// The sidechain level above which gain reduction is applied, in dB.
+    pub threshold_db: f32,
+    /// The amount of sidechain level above the threshold needed for a
+    /// 1 dB increase in output level, at least `1.0`.
+    pub ratio: f32,
+    /// The time taken for the detector to react to a rising sidechain.
+    pub attack_ms: f32,
+    /// The time taken for the detector to react to a falling sidechain.
+    pub release_ms: f32,
+}
+
+impl DuckerParameters {
+    /// Creates a new [`DuckerParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a pad to pump along with a kick drum:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::ducker::*;
+    /// let _ = DuckerParameters::new(-24.0, 8.0, 2.0, 150.0);
+    /// ```
+    pub fn new(threshold_db: f32, ratio: f32, attack_ms: f32, release_ms: f32) -> Self {
+        Self {
+            threshold_db,
+            ratio: ratio.max(1.0),
+            attack_ms: attack_ms.max(0.0),
+            release_ms: release_ms.max(0.0),
+        }
+    }
+
+    /// Compute the static gain in dB applied for a detected sidechain
+    /// `level_db`.
+    pub fn gain_db(&self, level_db: f32) -> f32 {
+        let over = level_db - self.threshold_db;
+        if over > 0.0 {
+            -over * (1.0 - 1.0 / self.ratio.max(1.0))
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The ducker DSP and its internal state.
+#[derive(Debug)]
+pub struct Ducker {
+    /// The parameters for the effect.
+    parameters: Option<DuckerParameters>,
+    /// The level detector for both channels of the sidechain.
+    detector: EnvelopeFollower,
+}
+
+impl Ducker {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            parameters: None,
+            detector: EnvelopeFollower::new(Mode::Peak, sample_rate),
+        }
+    }
+
+    /// Applies the effect to a stereo `buffer`, attenuating both
+    /// channels by the same amount as an interleaved stereo `sidechain`
+    /// rises above the threshold.
+    ///
+    /// Processes at most `frames` frames, stopping early at the end of
+    /// the shorter of the `buffer` and the `sidechain`. Returns the
+    /// number of frames processed, leaving the rest of the `buffer`
+    /// untouched.
+    ///
+    /// This is a no-op if the [`Ducker`] is deinitialized.
+    pub fn process_sidechain(
+        &mut self,
+        frames: usize,
+        buffer: &mut [f32],
+        sidechain: &[f32],
+    ) -> usize {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return 0,
+        };
+        let frames = frames.min(buffer.len() / 2).min(sidechain.len() / 2);
+        for (frame, key) in buffer
+            .chunks_exact_mut(2)
+            .zip(sidechain.chunks_exact(2))
+            .take(frames)
+        {
+            let envelope = self.detector.process(key[0].abs().max(key[1].abs()));
+            let gain = db_to_gain(parameters.gain_db(gain_to_db(envelope)));
+            frame[0] *= gain;
+            frame[1] *= gain;
+        }
+        frames
+    }
+}
+
+impl Ducker {
+    /// Initializes the [`Ducker`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: DuckerParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Ducker`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Ducker {
+    type Parameters = DuckerParameters;
+
+    /// Applies the effect to the `buffer` with a silent sidechain,
+    /// releasing any gain reduction left over from
+    /// [`Ducker::process_sidechain`].
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        for frame in buffer.chunks_exact_mut(2) {
+            let envelope = self.detector.process(0.0);
+            let gain = db_to_gain(parameters.gain_db(gain_to_db(envelope)));
+            frame[0] *= gain;
+            frame[1] *= gain;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.detector.reset();
+    }
+
+    fn set_parameters(&mut self, parameters: DuckerParameters) {
+        self.detector.set_attack_ms(parameters.attack_ms);
+        self.detector.set_release_ms(parameters.release_ms);
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ducker, DuckerParameters};
+
+    #[test]
+    fn dips_with_sidechain() {
+        let mut ducker = Ducker::new(44100.0);
+        ducker.initialize(DuckerParameters::new(-20.0, 10.0, 1.0, 20.0));
+        // The sidechain alternates between loud and silent every 100 ms.
+        let sidechain: Vec<f32> = (0..44100)
+            .flat_map(|index| {
+                let x = if index / 4410 % 2 == 0 { 1.0 } else { 0.0 };
+                [x, x]
+            })
+            .collect();
+        let mut buffer = vec![0.5; sidechain.len()];
+        assert_eq!(
+            ducker.process_sidechain(44100, &mut buffer, &sidechain),
+            44100
+        );
+
+        for section in 0..10 {
+            let end = (section + 1) * 4410;
+            let level = buffer[2 * (end - 1)];
+            if section % 2 == 0 {
+                // 20 dB over the threshold is reduced by 18 dB.
+                assert!((level - 0.5 * 0.126).abs() < 1e-3, "{}", level);
+            } else {
+                assert!(level > 0.49, "{}", level);
+            }
+        }
+    }
+
+    #[test]
+    fn stereo_shares_gain() {
+        let mut ducker = Ducker::new(44100.0);
+        ducker.initialize(DuckerParameters::new(-20.0, 8.0, 1.0, 50.0));
+        let mut buffer: Vec<f32> = (0..4410).flat_map(|_| [1.0, 0.25]).collect();
+        let sidechain: Vec<f32> = (0..4410).flat_map(|_| [0.0, 1.0]).collect();
+        ducker.process_sidechain(4410, &mut buffer, &sidechain);
+        assert!(buffer[2 * 4409] < 0.5);
+        for frame in buffer.chunks_exact(2) {
+            assert!((frame[0] / frame[1] - 4.0).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn truncates_to_shorter_buffer() {
+        let mut ducker = Ducker::new(44100.0);
+        ducker.initialize(DuckerParameters::new(-20.0, 8.0, 1.0, 50.0));
+        let mut buffer = vec![1.0; 2 * 100];
+        let sidechain = vec![1.0; 2 * 40 + 1];
+        assert_eq!(ducker.process_sidechain(100, &mut buffer, &sidechain), 40);
+        assert!(buffer[2 * 40..].iter().all(|sample| *sample == 1.0));
+    }
+}
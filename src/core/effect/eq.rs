@@ -0,0 +1,267 @@
+//! A three band parametric equalizer.
+use super::Effect;
+use crate::core::dsp::{Biquad, BiquadCoefficients};
+
+/// The number of frames over which coefficient changes are ramped.
+pub const RAMP_FRAMES: usize = 64;
+
+/// A single band of the [`ParametricEq`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EqBand {
+    /// The corner frequency for shelves, or the center frequency for
+    /// peaks, in Hz.
+    pub frequency: f64,
+    /// The boost or cut applied by the band, in dB.
+    pub gain_db: f64,
+    /// The bandwidth of the band.
+    pub q: f64,
+}
+
+impl EqBand {
+    /// Creates a new [`EqBand`].
+    pub fn new(frequency: f64, gain_db: f64, q: f64) -> Self {
+        Self {
+            frequency,
+            gain_db,
+            q,
+        }
+    }
+}
+
+/// The parameters consumed by [`ParametricEq`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EqParameters {
+    /// The low shelf band.
+    pub low: EqBand,
+    /// The mid peak band.
+    pub mid: EqBand,
+    /// The high shelf band.
+    pub high: EqBand,
+}
+
+impl EqParameters {
+    /// Creates a new [`EqParameters`].
+    pub fn new(low: EqBand, mid: EqBand, high: EqBand) -> Self {
+        Self { low, mid, high }
+    }
+
+    /// Computes the coefficients of the low, mid, and high bands.
+    pub fn coefficients(&self, sample_rate: f64) -> [BiquadCoefficients; 3] {
+        let EqParameters { low, mid, high } = self;
+        [
+            BiquadCoefficients::lowshelf(low.frequency, low.q, low.gain_db, sample_rate),
+            BiquadCoefficients::peak(mid.frequency, mid.q, mid.gain_db, sample_rate),
+            BiquadCoefficients::highshelf(high.frequency, high.q, high.gain_db, sample_rate),
+        ]
+    }
+}
+
+impl Default for EqParameters {
+    /// A flat response with the bands at 100 Hz, 1 kHz, and 8 kHz.
+    fn default() -> Self {
+        Self::new(
+            EqBand::new(100.0, 0.0, 0.707),
+            EqBand::new(1000.0, 0.0, 1.0),
+            EqBand::new(8000.0, 0.0, 0.707),
+        )
+    }
+}
+
+/// The parametric equalizer DSP and its internal state.
+#[derive(Debug)]
+pub struct ParametricEq {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<EqParameters>,
+    /// The bands of each channel.
+    filters: [[Biquad; 3]; 2],
+    /// The coefficients being ramped away from.
+    from: [BiquadCoefficients; 3],
+    /// The coefficients being ramped towards.
+    to: [BiquadCoefficients; 3],
+    /// The number of frames left in the ramp.
+    ramp: usize,
+}
+
+impl ParametricEq {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            parameters: None,
+            filters: [[Biquad::default(); 3]; 2],
+            from: [BiquadCoefficients::IDENTITY; 3],
+            to: [BiquadCoefficients::IDENTITY; 3],
+            ramp: 0,
+        }
+    }
+}
+
+impl ParametricEq {
+    /// Initializes the [`ParametricEq`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: EqParameters) {
+        self.parameters = None;
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`ParametricEq`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+
+    /// Applies the coefficients to the bands of every channel.
+    fn apply(&mut self, coefficients: [BiquadCoefficients; 3]) {
+        for bands in self.filters.iter_mut() {
+            for (band, coefficients) in bands.iter_mut().zip(coefficients) {
+                band.set_coefficients(coefficients);
+            }
+        }
+    }
+}
+
+impl Effect for ParametricEq {
+    type Parameters = EqParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        if self.parameters.is_none() {
+            return;
+        }
+        for frame in buffer.chunks_exact_mut(2) {
+            if self.ramp > 0 {
+                self.ramp -= 1;
+                let t = 1.0 - self.ramp as f64 / RAMP_FRAMES as f64;
+                let mut coefficients = self.to;
+                for (current, from) in coefficients.iter_mut().zip(self.from) {
+                    current.b0 = from.b0 + (current.b0 - from.b0) * t;
+                    current.b1 = from.b1 + (current.b1 - from.b1) * t;
+                    current.b2 = from.b2 + (current.b2 - from.b2) * t;
+                    current.a1 = from.a1 + (current.a1 - from.a1) * t;
+                    current.a2 = from.a2 + (current.a2 - from.a2) * t;
+                }
+                self.apply(coefficients);
+            }
+            for (sample, bands) in frame.iter_mut().zip(self.filters.iter_mut()) {
+                *sample = bands
+                    .iter_mut()
+                    .fold(*sample, |sample, band| band.process(sample));
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        for band in self.filters.iter_mut().flatten() {
+            band.reset();
+        }
+    }
+
+    /// Replaces the parameters of the effect, ramping the coefficients
+    /// over [`RAMP_FRAMES`] if the effect is already running.
+    fn set_parameters(&mut self, parameters: EqParameters) {
+        let coefficients = parameters.coefficients(self.sample_rate);
+        if self.parameters.is_some() {
+            self.from = self.filters[0].map(|band| band.coefficients());
+            self.ramp = RAMP_FRAMES;
+        } else {
+            self.apply(coefficients);
+            self.ramp = 0;
+        }
+        self.to = coefficients;
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{EqBand, EqParameters, ParametricEq};
+    use crate::core::effect::Effect;
+
+    fn measure_gain_db(parameters: EqParameters, frequency: f64) -> f64 {
+        let mut eq = ParametricEq::new(44100.0);
+        eq.initialize(parameters);
+        let mut buffer: Vec<f32> = (0..44100)
+            .flat_map(|index| {
+                let x = (TAU * frequency * index as f64 / 44100.0).sin() as f32;
+                [x, x]
+            })
+            .collect();
+        eq.process(0, &mut buffer);
+        let peak = buffer[44100..]
+            .iter()
+            .fold(0.0, |peak: f32, sample| peak.max(sample.abs()));
+        20.0 * (peak as f64).log10()
+    }
+
+    #[test]
+    fn flat_is_transparent() {
+        for frequency in [50.0, 440.0, 3000.0, 12000.0] {
+            let gain_db = measure_gain_db(EqParameters::default(), frequency);
+            assert!(gain_db.abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn band_centers() {
+        let flat = EqParameters::default();
+        let cases = [
+            (
+                EqParameters {
+                    low: EqBand::new(100.0, 6.0, 0.707),
+                    ..flat
+                },
+                100.0,
+                3.0,
+            ),
+            (
+                EqParameters {
+                    mid: EqBand::new(1000.0, -6.0, 1.0),
+                    ..flat
+                },
+                1000.0,
+                -6.0,
+            ),
+            (
+                EqParameters {
+                    high: EqBand::new(8000.0, 6.0, 0.707),
+                    ..flat
+                },
+                8000.0,
+                3.0,
+            ),
+        ];
+        for (parameters, frequency, expected) in cases {
+            let gain_db = measure_gain_db(parameters, frequency);
+            assert!((gain_db - expected).abs() < 0.25, "{}", gain_db);
+        }
+    }
+
+    #[test]
+    fn sweep_is_continuous() {
+        let mut eq = ParametricEq::new(44100.0);
+        eq.initialize(EqParameters::default());
+        let mut previous = 0.0;
+        for block in 0..64 {
+            let mid = EqBand::new(200.0 + block as f64 * 100.0, 12.0, 1.0);
+            eq.set_parameters(EqParameters {
+                mid,
+                ..EqParameters::default()
+            });
+            let mut buffer: Vec<f32> = (0..256)
+                .flat_map(|index| {
+                    let x = (TAU * 100.0 * (block * 256 + index) as f64 / 44100.0).sin() as f32;
+                    [x, x]
+                })
+                .collect();
+            eq.process(0, &mut buffer);
+            for frame in buffer.chunks_exact(2) {
+                assert!((frame[0] - previous).abs() < 0.1);
+                previous = frame[0];
+            }
+        }
+    }
+}
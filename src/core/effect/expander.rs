@@ -0,0 +1,206 @@
+//! Increases the dynamic range by attenuating quiet passages.
+use super::Effect;
+use crate::core::dsp::{
+    decibel::{db_to_gain, gain_to_db},
+    envelope::{EnvelopeFollower, Mode},
+};
+
+/// The parameters consumed by [`Expander`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExpanderParameters {
+    /// The level below which gain reduction is applied, in dB.
+    pub threshold_db: f32,
+    /// The decrease in output level for each 1 dB that the input level
+    /// falls below the threshold, at least `1.0`.
+    pub ratio: f32,
+    /// The time taken for the detector to react to rising levels.
+    pub attack_ms: f32,
+    /// The time taken for the detector to react to falling levels.
+    pub release_ms: f32,
+    /// The width of the region around the threshold where the
+    /// expansion is eased in, in dB.
+    pub knee_db: f32,
+}
+
+impl ExpanderParameters {
+    /// Creates a new [`ExpanderParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to gently push down the bleed between drum hits:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::expander::*;
+    /// let _ = ExpanderParameters::new(-35.0, 2.0, 1.0, 80.0, 6.0);
+    /// ```
+    pub fn new(
+        threshold_db: f32,
+        ratio: f32,
+        attack_ms: f32,
+        release_ms: f32,
+        knee_db: f32,
+    ) -> Self {
+        Self {
+            threshold_db,
+            ratio: ratio.max(1.0),
+            attack_ms: attack_ms.max(0.0),
+            release_ms: release_ms.max(0.0),
+            knee_db: knee_db.max(0.0),
+        }
+    }
+
+    /// Compute the static gain in dB applied to a detected `level_db`.
+    pub fn gain_db(&self, level_db: f32) -> f32 {
+        let slope = self.ratio.max(1.0) - 1.0;
+        let knee = self.knee_db.max(0.0);
+        let under = level_db - self.threshold_db;
+        if 2.0 * under < -knee {
+            slope * under
+        } else if 2.0 * under <= knee && knee > 0.0 {
+            // A quadratic joining both slopes without a corner.
+            let x = under - knee / 2.0;
+            -slope * x * x / (2.0 * knee)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The expander DSP and its internal state.
+#[derive(Debug)]
+pub struct Expander {
+    /// The parameters for the effect.
+    parameters: Option<ExpanderParameters>,
+    /// The level detector shared by both channels.
+    detector: EnvelopeFollower,
+}
+
+impl Expander {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            parameters: None,
+            detector: EnvelopeFollower::new(Mode::Peak, sample_rate),
+        }
+    }
+}
+
+impl Expander {
+    /// Initializes the [`Expander`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: ExpanderParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Expander`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Expander {
+    type Parameters = ExpanderParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        for frame in buffer.chunks_exact_mut(2) {
+            let envelope = self.detector.process(frame[0].abs().max(frame[1].abs()));
+            let gain = db_to_gain(parameters.gain_db(gain_to_db(envelope)));
+            frame[0] *= gain;
+            frame[1] *= gain;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.detector.reset();
+    }
+
+    fn set_parameters(&mut self, parameters: ExpanderParameters) {
+        self.detector.set_attack_ms(parameters.attack_ms);
+        self.detector.set_release_ms(parameters.release_ms);
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::{Expander, ExpanderParameters};
+    use crate::core::{dsp::decibel::gain_to_db, effect::Effect};
+
+    fn tone(input_db: f32) -> Vec<f32> {
+        let amplitude = 10.0_f32.powf(input_db / 20.0);
+        (0..44100)
+            .flat_map(|index| {
+                let x = amplitude * (TAU * 440.0 * index as f32 / 44100.0).sin();
+                [x, x]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quiet_tone_follows_static_curve() {
+        let (threshold_db, ratio, input_db) = (-20.0, 2.0, -30.0);
+        let mut expander = Expander::new(44100.0);
+        expander.initialize(ExpanderParameters::new(
+            threshold_db,
+            ratio,
+            1.0,
+            200.0,
+            0.0,
+        ));
+        let mut buffer = tone(input_db);
+        expander.process(0, &mut buffer);
+
+        let peak = buffer[44100..]
+            .iter()
+            .fold(0.0, |peak: f32, sample| peak.max(sample.abs()));
+        let expected_db = threshold_db + (input_db - threshold_db) * ratio;
+        assert!((gain_to_db(peak) - expected_db).abs() < 0.5);
+    }
+
+    #[test]
+    fn hard_knee_at_threshold() {
+        let parameters = ExpanderParameters::new(-20.0, 2.0, 1.0, 100.0, 0.0);
+        assert_eq!(parameters.gain_db(-20.0), 0.0);
+        assert_eq!(parameters.gain_db(-21.0), -1.0);
+
+        let mut expander = Expander::new(44100.0);
+        expander.initialize(ExpanderParameters::new(0.0, 2.0, 1.0, 100.0, 0.0));
+        let mut buffer = vec![1.0; 2 * 4096];
+        expander.process(0, &mut buffer);
+        assert!(buffer.iter().all(|sample| sample.is_finite()));
+    }
+
+    #[test]
+    fn loud_tone_is_untouched() {
+        let mut expander = Expander::new(44100.0);
+        expander.initialize(ExpanderParameters::new(-20.0, 4.0, 1.0, 200.0, 6.0));
+        let input = tone(-6.0);
+        let mut buffer = input.clone();
+        expander.process(0, &mut buffer);
+        assert_eq!(buffer[44100..], input[44100..]);
+    }
+
+    #[test]
+    fn knee_is_smooth() {
+        let parameters = ExpanderParameters::new(-20.0, 3.0, 1.0, 100.0, 10.0);
+        let step = 0.01;
+        let mut previous = parameters.gain_db(-40.0);
+        let mut previous_slope = 2.0;
+        for index in 1..=3000 {
+            let gain = parameters.gain_db(-40.0 + index as f32 * step);
+            let slope = (gain - previous) / step;
+            assert!(slope <= previous_slope + 1e-2);
+            assert!((slope - previous_slope).abs() < 0.05);
+            previous = gain;
+            previous_slope = slope;
+        }
+        assert_eq!(previous, 0.0);
+    }
+}
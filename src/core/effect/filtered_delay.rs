@@ -0,0 +1,253 @@
+//! Repeats the input after a fixed number of frames, filtering each
+//! repetition as it is fed back such that the echoes grow darker.
+use alloc::{vec, vec::Vec};
+use core::f64::consts::FRAC_1_SQRT_2;
+
+use super::Effect;
+use crate::core::dsp::{denormal::flush, Biquad, BiquadCoefficients};
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The largest loop gain accepted by [`FilteredDelayParameters`],
+/// including the peak gain of the filters.
+pub const MAX_FEEDBACK: f32 = 0.99;
+
+/// The lowest cutoff accepted by [`FilteredDelayParameters`].
+pub const MIN_CUTOFF_HZ: f32 = 20.0;
+
+/// The number of frequencies at which the filters are measured to find
+/// their peak gain.
+const RESPONSE_POINTS: usize = 64;
+
+/// The parameters consumed by [`FilteredDelay`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilteredDelayParameters {
+    /// The number of frames between the input and its first echo.
+    pub delay_samples: usize,
+    /// Determines how much of each echo is fed back into the delay
+    /// line, clamped such that the loop gain stays below
+    /// `MAX_FEEDBACK` at every frequency.
+    pub feedback: f32,
+    /// The cutoff of the low-pass in the loop, in Hz.
+    pub lowpass_hz: f32,
+    /// The cutoff of the high-pass in the loop, in Hz.
+    pub highpass_hz: f32,
+    /// Determines how much of the echoes are mixed with the original
+    /// audio.
+    pub mix: f32,
+}
+
+impl FilteredDelayParameters {
+    /// Creates a new [`FilteredDelayParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a quarter note echo in a 120 BPM track that fades
+    /// into a muffled, thin tail:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::filtered_delay::*;
+    /// let _ = FilteredDelayParameters::new(22050, 0.6, 3000.0, 200.0, 0.4);
+    /// ```
+    pub fn new(
+        delay_samples: usize,
+        feedback: f32,
+        lowpass_hz: f32,
+        highpass_hz: f32,
+        mix: f32,
+    ) -> Self {
+        Self {
+            delay_samples,
+            feedback: feedback.clamp(0.0, MAX_FEEDBACK),
+            lowpass_hz: lowpass_hz.max(MIN_CUTOFF_HZ),
+            highpass_hz: highpass_hz.max(MIN_CUTOFF_HZ),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// The filtered delay DSP and its internal state.
+#[derive(Debug)]
+pub struct FilteredDelay {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<FilteredDelayParameters>,
+    /// The feedback after accounting for the peak gain of the filters.
+    feedback: f32,
+    /// The interleaved stereo delay line.
+    line: Vec<f32>,
+    /// The frame in the delay line that is read and written next.
+    index: usize,
+    /// The low-pass and high-pass in the loop of each channel.
+    filters: [[Biquad; 2]; 2],
+}
+
+impl FilteredDelay {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            parameters: None,
+            feedback: 0.0,
+            line: vec![],
+            index: 0,
+            filters: [[Biquad::default(); 2]; 2],
+        }
+    }
+
+    /// Computes the coefficients of the low-pass and the high-pass,
+    /// keeping their cutoffs below Nyquist.
+    fn coefficients(&self, parameters: &FilteredDelayParameters) -> [BiquadCoefficients; 2] {
+        let nyquist = self.sample_rate * 0.49;
+        let cutoff = |hz: f32| (hz as f64).clamp(MIN_CUTOFF_HZ as f64, nyquist);
+        [
+            BiquadCoefficients::lowpass(
+                cutoff(parameters.lowpass_hz),
+                FRAC_1_SQRT_2,
+                self.sample_rate,
+            ),
+            BiquadCoefficients::highpass(
+                cutoff(parameters.highpass_hz),
+                FRAC_1_SQRT_2,
+                self.sample_rate,
+            ),
+        ]
+    }
+
+    /// Measures the peak gain of the filters in series, across a
+    /// logarithmic sweep up to Nyquist.
+    fn peak_gain(&self, coefficients: &[BiquadCoefficients; 2]) -> f32 {
+        let nyquist = self.sample_rate * 0.5;
+        let ratio = nyquist / MIN_CUTOFF_HZ as f64;
+        (0..=RESPONSE_POINTS)
+            .map(|point| {
+                let frequency =
+                    MIN_CUTOFF_HZ as f64 * ratio.powf(point as f64 / RESPONSE_POINTS as f64);
+                coefficients
+                    .iter()
+                    .map(|coefficients| coefficients.magnitude(frequency, self.sample_rate))
+                    .product::<f64>()
+            })
+            .fold(0.0, f64::max) as f32
+    }
+}
+
+impl FilteredDelay {
+    /// Initializes the [`FilteredDelay`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: FilteredDelayParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`FilteredDelay`] i.e. turning it off, freeing
+    /// the delay line.
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.line = vec![];
+        self.index = 0;
+    }
+}
+
+impl Effect for FilteredDelay {
+    type Parameters = FilteredDelayParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        if parameters.delay_samples == 0 {
+            return;
+        }
+        for frame in buffer.chunks_exact_mut(2) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let [lowpass, highpass] = &mut self.filters[channel];
+                let slot = &mut self.line[self.index * 2 + channel];
+                let delayed = *slot;
+                let fed_back = highpass.process(lowpass.process(*sample + delayed * self.feedback));
+                *slot = flush(fed_back);
+                *sample = *sample * (1.0 - parameters.mix) + delayed * parameters.mix;
+            }
+            self.index = (self.index + 1) % parameters.delay_samples;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.line.fill(0.0);
+        self.index = 0;
+        self.filters.iter_mut().flatten().for_each(Biquad::reset);
+    }
+
+    /// Replaces the parameters of the effect, reallocating and clearing
+    /// the delay line if `delay_samples` changes.
+    fn set_parameters(&mut self, parameters: FilteredDelayParameters) {
+        if self.line.len() != parameters.delay_samples * 2 {
+            self.line = vec![0.0; parameters.delay_samples * 2];
+            self.index = 0;
+        }
+        let coefficients = self.coefficients(&parameters);
+        for filters in self.filters.iter_mut() {
+            for (filter, coefficients) in filters.iter_mut().zip(coefficients) {
+                filter.set_coefficients(coefficients);
+            }
+        }
+        let peak = self.peak_gain(&coefficients).max(1.0);
+        self.feedback = parameters.feedback.clamp(0.0, MAX_FEEDBACK) / peak;
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FilteredDelay, FilteredDelayParameters, MAX_FEEDBACK};
+    use crate::core::effect::Effect;
+
+    /// The share of the energy of `samples` in their first difference,
+    /// which rises with their high-frequency content.
+    fn brightness(samples: &[f32]) -> f32 {
+        let energy: f32 = samples.iter().map(|x| x * x).sum();
+        let difference: f32 = samples
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).powi(2))
+            .sum();
+        difference / energy
+    }
+
+    #[test]
+    fn echoes_grow_darker() {
+        let delay = 2000;
+        let mut filtered = FilteredDelay::new(44100.0);
+        filtered.initialize(FilteredDelayParameters::new(delay, 0.9, 3000.0, 20.0, 1.0));
+        let mut buffer = vec![0.0; 2 * delay * 6];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        filtered.process(0, &mut buffer);
+
+        let left: Vec<f32> = buffer.iter().step_by(2).copied().collect();
+        let echoes: Vec<f32> = left.chunks_exact(delay).skip(1).map(brightness).collect();
+        for pair in echoes.windows(2) {
+            assert!(pair[1] < pair[0], "{:?}", echoes);
+        }
+    }
+
+    #[test]
+    fn resonance_is_compensated() {
+        let mut filtered = FilteredDelay::new(44100.0);
+        filtered.initialize(FilteredDelayParameters::new(
+            100,
+            MAX_FEEDBACK,
+            20.0,
+            20.0,
+            1.0,
+        ));
+        let mut buffer = vec![0.0; 2 * 100 * 500];
+        buffer[0] = 1.0;
+        filtered.process(0, &mut buffer);
+        assert!(buffer.iter().all(|sample| sample.is_finite()));
+        let tail = buffer[buffer.len() - 2 * 100..]
+            .iter()
+            .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+        assert!(tail < 1.0, "{}", tail);
+    }
+}
@@ -0,0 +1,229 @@
+//! Sweeps a comb filter across the signal with a short modulated delay.
+use super::Effect;
+use crate::core::dsp::{denormal::flush, DelayLine, Lfo, SmoothedValue, Smoothing, Waveform};
+
+/// The delay when the modulation is at its lowest.
+pub const MIN_DELAY_MS: f32 = 0.1;
+
+/// The longest delay reachable by the modulation.
+pub const MAX_DELAY_MS: f32 = 10.0;
+
+/// The largest feedback amount accepted by [`FlangerParameters`].
+pub const MAX_FEEDBACK: f32 = 0.95;
+
+/// The time taken for depth changes to settle.
+pub const DEPTH_SMOOTHING_MS: f32 = 20.0;
+
+/// The parameters consumed by [`Flanger`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlangerParameters {
+    /// The frequency of the modulation in Hz.
+    pub rate_hz: f32,
+    /// The range swept by the delay, up to `MAX_DELAY_MS - MIN_DELAY_MS`.
+    pub depth_ms: f32,
+    /// Determines how much of the delayed signal is fed back, clamped to
+    /// `-MAX_FEEDBACK..=MAX_FEEDBACK` to avoid self-oscillation.
+    pub feedback: f32,
+    /// Determines how much of the delayed signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl FlangerParameters {
+    /// Creates a new [`FlangerParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a slow jet-plane sweep:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::flanger::*;
+    /// let _ = FlangerParameters::new(0.2, 3.0, 0.7, 0.5);
+    /// ```
+    pub fn new(rate_hz: f32, depth_ms: f32, feedback: f32, mix: f32) -> Self {
+        Self {
+            rate_hz: rate_hz.max(0.0),
+            depth_ms: depth_ms.clamp(0.0, MAX_DELAY_MS - MIN_DELAY_MS),
+            feedback: feedback.clamp(-MAX_FEEDBACK, MAX_FEEDBACK),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// The flanger DSP and its internal state.
+#[derive(Debug)]
+pub struct Flanger {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<FlangerParameters>,
+    /// The delay line of each channel.
+    lines: [DelayLine; 2],
+    /// The modulation shared by both channels.
+    lfo: Lfo,
+    /// The depth in samples, gliding towards the parameters.
+    depth: SmoothedValue,
+}
+
+impl Flanger {
+    pub fn new(sample_rate: f64) -> Self {
+        let len = (MAX_DELAY_MS as f64 * 0.001 * sample_rate) as usize + 4;
+        let line = DelayLine::new(len);
+        let mut depth = SmoothedValue::new(Smoothing::Exponential, 0.0);
+        depth.set_smoothing_time(DEPTH_SMOOTHING_MS, sample_rate);
+        Self {
+            sample_rate,
+            parameters: None,
+            lines: [line.clone(), line],
+            lfo: Lfo::new(Waveform::Sine, 0.0, sample_rate),
+            depth,
+        }
+    }
+
+    /// Converts a depth in ms to samples.
+    fn depth_samples(&self, depth_ms: f32) -> f32 {
+        depth_ms.clamp(0.0, MAX_DELAY_MS - MIN_DELAY_MS) * (self.sample_rate * 0.001) as f32
+    }
+}
+
+impl Flanger {
+    /// Initializes the [`Flanger`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: FlangerParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+        self.depth
+            .set_value(self.depth_samples(parameters.depth_ms));
+    }
+
+    /// Deinitializes the [`Flanger`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Flanger {
+    type Parameters = FlangerParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let base = MIN_DELAY_MS * (self.sample_rate * 0.001) as f32;
+        let feedback = parameters.feedback.clamp(-MAX_FEEDBACK, MAX_FEEDBACK);
+        for frame in buffer.chunks_exact_mut(2) {
+            let delay = base + self.depth.next() * 0.5 * (1.0 + self.lfo.next());
+            for (sample, line) in frame.iter_mut().zip(self.lines.iter_mut()) {
+                // Interpolating between tiny samples can land below them.
+                let delayed = flush(line.read_cubic(delay));
+                line.write(flush(*sample + delayed * feedback));
+                *sample = *sample * (1.0 - parameters.mix) + delayed * parameters.mix;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.clear();
+        }
+        self.lfo.reset();
+    }
+
+    /// Replaces the parameters of the effect, gliding towards the new
+    /// depth over [`DEPTH_SMOOTHING_MS`].
+    fn set_parameters(&mut self, parameters: FlangerParameters) {
+        self.lfo.set_frequency(parameters.rate_hz as f64);
+        self.depth
+            .set_target(self.depth_samples(parameters.depth_ms));
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{Flanger, FlangerParameters};
+    use crate::core::effect::Effect;
+
+    #[test]
+    fn tail_flushes_to_zero() {
+        let mut flanger = Flanger::new(44100.0);
+        flanger.initialize(FlangerParameters::new(0.5, 2.0, 0.9, 1.0));
+        let mut buffer = vec![0.0; 2 * 44100 * 4];
+        buffer[0] = 1.0;
+        flanger.process(0, &mut buffer);
+        assert!(buffer.iter().all(|sample| !sample.is_subnormal()));
+        assert!(buffer[2 * 44100 * 3..].iter().all(|&sample| sample == 0.0));
+    }
+
+    /// Computes the complex spectrum of the left channel at `frequency`.
+    fn dft(buffer: &[f32], frequency: f64, sample_rate: f64) -> (f64, f64) {
+        buffer
+            .chunks_exact(2)
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (index, frame)| {
+                let phase = TAU * frequency * index as f64 / sample_rate;
+                let x = frame[0] as f64;
+                (re + x * phase.cos(), im - x * phase.sin())
+            })
+    }
+
+    /// Estimates the magnitude response at `frequency` within a window
+    /// starting at `frame`.
+    fn response(input: &[f32], output: &[f32], frame: usize, frequency: f64) -> f64 {
+        let range = frame * 2..(frame + 8192) * 2;
+        let (x_re, x_im) = dft(&input[range.clone()], frequency, 44100.0);
+        let (y_re, y_im) = dft(&output[range], frequency, 44100.0);
+        y_re.hypot(y_im) / x_re.hypot(x_im)
+    }
+
+    #[test]
+    fn notches_sweep() {
+        let sample_rate = 44100.0;
+        let mut state = 0x2545f491_u32;
+        let input: Vec<f32> = (0..44100 * 4)
+            .flat_map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                let x = state as f32 / u32::MAX as f32 * 2.0 - 1.0;
+                [x, x]
+            })
+            .collect();
+        let mut flanger = Flanger::new(sample_rate);
+        flanger.initialize(FlangerParameters::new(0.25, 4.0, 0.0, 0.5));
+        let mut output = input.clone();
+        flanger.process(0, &mut output);
+
+        // The delay peaks at 4.1ms a second in, then bottoms out at
+        // 0.1ms two seconds after that, each read one frame late.
+        let (slow, fast) = (44100 - 4096, 44100 * 3 - 4096);
+        let slow_notch = sample_rate / (2.0 * (0.0041 * sample_rate + 1.0));
+        let fast_notch = sample_rate / (2.0 * (0.0001 * sample_rate + 1.0));
+        let at = |frame, frequency| response(&input, &output, frame, frequency);
+        assert!(at(slow, slow_notch) < 0.3 * at(fast, slow_notch));
+        assert!(at(fast, fast_notch) < 0.4 * at(slow, fast_notch));
+    }
+
+    #[test]
+    fn depth_changes_glide() {
+        let mut flanger = Flanger::new(44100.0);
+        flanger.initialize(FlangerParameters::new(0.0, 0.0, 0.0, 0.5));
+        let input: Vec<f32> = (0..4096)
+            .flat_map(|index| {
+                let x = (index as f32 * 0.01).sin();
+                [x, x]
+            })
+            .collect();
+        let mut buffer = input.clone();
+        flanger.process(0, &mut buffer[..4096]);
+        flanger.set_parameters(FlangerParameters::new(0.0, 9.0, 0.0, 0.5));
+        flanger.process(0, &mut buffer[4096..]);
+        for pair in buffer.chunks_exact(2).collect::<Vec<_>>().windows(2) {
+            assert!((pair[1][0] - pair[0][0]).abs() < 0.05);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Scales the level of the signal.
+use super::Effect;
+use crate::core::dsp::{decibel::db_to_gain, SmoothedValue, Smoothing};
+
+/// The time taken for gain changes to settle.
+pub const GAIN_SMOOTHING_MS: f32 = 20.0;
+
+/// The parameters consumed by [`Gain`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GainParameters {
+    /// The gain applied to the signal, in dB.
+    pub gain_db: f32,
+}
+
+impl GainParameters {
+    /// Creates a new [`GainParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to match the level of a louder take:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::gain::*;
+    /// let _ = GainParameters::new(-3.0);
+    /// ```
+    pub fn new(gain_db: f32) -> Self {
+        Self { gain_db }
+    }
+}
+
+/// The gain DSP and its internal state.
+#[derive(Debug)]
+pub struct Gain {
+    /// The parameters for the effect.
+    parameters: Option<GainParameters>,
+    /// The linear gain, gliding towards the parameters.
+    gain: SmoothedValue,
+}
+
+impl Gain {
+    pub fn new(sample_rate: f64) -> Self {
+        let mut gain = SmoothedValue::new(Smoothing::Linear, 1.0);
+        gain.set_smoothing_time(GAIN_SMOOTHING_MS, sample_rate);
+        Self {
+            parameters: None,
+            gain,
+        }
+    }
+}
+
+impl Gain {
+    /// Initializes the [`Gain`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: GainParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Gain`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Gain {
+    type Parameters = GainParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        if self.parameters.is_none() {
+            return;
+        }
+        for frame in buffer.chunks_exact_mut(2) {
+            let gain = self.gain.next();
+            frame[0] *= gain;
+            frame[1] *= gain;
+        }
+    }
+
+    /// Clears the internal state of the effect, snapping the gain to
+    /// the parameters.
+    fn reset(&mut self) {
+        let target = self.gain.target();
+        self.gain.set_value(target);
+    }
+
+    /// Replaces the parameters of the effect, gliding towards the new
+    /// gain over [`GAIN_SMOOTHING_MS`].
+    fn set_parameters(&mut self, parameters: GainParameters) {
+        self.gain.set_target(db_to_gain(parameters.gain_db));
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gain, GainParameters};
+    use crate::core::effect::Effect;
+
+    #[test]
+    fn six_db_doubles() {
+        let mut gain = Gain::new(44100.0);
+        gain.initialize(GainParameters::new(6.0));
+        let mut buffer = vec![0.25; 2 * 100];
+        gain.process(0, &mut buffer);
+        assert!(buffer.iter().all(|sample| (sample - 0.5).abs() < 2e-3));
+    }
+
+    #[test]
+    fn changes_glide() {
+        let mut gain = Gain::new(44100.0);
+        gain.initialize(GainParameters::new(0.0));
+        gain.set_parameters(GainParameters::new(6.0));
+        let mut buffer = vec![1.0; 2 * 44100];
+        gain.process(0, &mut buffer);
+        let steps = buffer
+            .chunks_exact(2)
+            .map(|frame| frame[0])
+            .collect::<Vec<_>>();
+        assert!(steps
+            .windows(2)
+            .all(|pair| (pair[1] - pair[0]).abs() < 2e-3));
+        assert!(steps[0] < 1.01);
+        assert!((steps[44099] - 2.0).abs() < 5e-3);
+    }
+}
@@ -0,0 +1,307 @@
+//! Scatters short, overlapping grains of the recent input into a cloud.
+//!
+//! # Overview
+//!
+//! The input is captured continuously into a delay line. At a rate set
+//! by the `density`, a grain is started at a random distance behind the
+//! input, reading it back at a random pitch under a Hann window. At
+//! most [`MAX_GRAINS`] play at once, such that the cost per frame is
+//! bounded regardless of the parameters, and a new grain is dropped
+//! while all of them are busy.
+use core::f32::consts::PI;
+
+use super::Effect;
+use crate::core::dsp::DelayLine;
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The largest number of grains playing at once.
+pub const MAX_GRAINS: usize = 16;
+
+/// The shortest grain accepted by [`GranularParameters`].
+pub const MIN_GRAIN_MS: f32 = 5.0;
+
+/// The longest grain accepted by [`GranularParameters`].
+pub const MAX_GRAIN_MS: f32 = 500.0;
+
+/// The largest number of grains started per second accepted by
+/// [`GranularParameters`].
+pub const MAX_DENSITY: f32 = 200.0;
+
+/// The furthest behind the input that a grain starts at a
+/// `position_jitter` of `1.0`.
+pub const MAX_JITTER_MS: f32 = 1000.0;
+
+/// The widest random detune accepted by [`GranularParameters`], in
+/// semitones either way.
+pub const MAX_PITCH_SPREAD: f32 = 12.0;
+
+/// The parameters consumed by [`Granular`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GranularParameters {
+    /// The length of each grain, clamped to
+    /// `MIN_GRAIN_MS..=MAX_GRAIN_MS`.
+    pub grain_size_ms: f32,
+    /// The number of grains started per second, clamped to
+    /// `0.0..=MAX_DENSITY`.
+    pub density: f32,
+    /// How far behind the input a grain may start, clamped to
+    /// `0.0..=1.0` as a fraction of [`MAX_JITTER_MS`].
+    pub position_jitter: f32,
+    /// How far the pitch of a grain may be detuned, clamped to
+    /// `0.0..=MAX_PITCH_SPREAD` semitones either way.
+    pub pitch_spread: f32,
+    /// Determines how much of the cloud is mixed with the original
+    /// audio.
+    pub mix: f32,
+}
+
+impl GranularParameters {
+    /// Creates a new [`GranularParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a dense, slightly detuned shimmer of the last half
+    /// second:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::granular::*;
+    /// let _ = GranularParameters::new(80.0, 40.0, 0.5, 0.3, 0.5);
+    /// ```
+    pub fn new(
+        grain_size_ms: f32,
+        density: f32,
+        position_jitter: f32,
+        pitch_spread: f32,
+        mix: f32,
+    ) -> Self {
+        Self {
+            grain_size_ms: grain_size_ms.clamp(MIN_GRAIN_MS, MAX_GRAIN_MS),
+            density: density.clamp(0.0, MAX_DENSITY),
+            position_jitter: position_jitter.clamp(0.0, 1.0),
+            pitch_spread: pitch_spread.clamp(0.0, MAX_PITCH_SPREAD),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// A single grain being played back.
+#[derive(Debug, Clone, Copy, Default)]
+struct Grain {
+    /// The distance of the read head behind the input, in frames.
+    delay: f32,
+    /// The change in `delay` per frame, which is `0.0` at the original
+    /// pitch.
+    drift: f32,
+    /// The length of the grain in frames, or `0` if it is idle.
+    length: usize,
+    /// The number of frames played so far.
+    age: usize,
+}
+
+/// The granular DSP and its internal state.
+#[derive(Debug)]
+pub struct Granular {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<GranularParameters>,
+    /// The captured input of each channel.
+    lines: [DelayLine; 2],
+    /// The grain slots.
+    grains: [Grain; MAX_GRAINS],
+    /// The frames left until the next grain starts.
+    countdown: f32,
+    /// The seed that the random number generator restarts from.
+    seed: u32,
+    /// The state of the random number generator.
+    state: u32,
+}
+
+impl Granular {
+    /// Creates a new [`Granular`] whose randomness is generated from
+    /// `seed`, allocating enough capture for the furthest and longest
+    /// grains up front.
+    pub fn new(sample_rate: f64, seed: u32) -> Self {
+        // An octave up reads a grain twice as fast as it is captured.
+        let max_ms = MAX_JITTER_MS + MAX_GRAIN_MS * 2.0;
+        let line = DelayLine::new((max_ms as f64 * 0.001 * sample_rate) as usize + 4);
+        Self {
+            sample_rate,
+            parameters: None,
+            lines: [line.clone(), line],
+            grains: [Grain::default(); MAX_GRAINS],
+            countdown: 0.0,
+            // The generator is stuck at zero.
+            seed: seed.max(1),
+            state: seed.max(1),
+        }
+    }
+
+    /// Generates a uniform number in `0.0..1.0` with a xorshift.
+    fn uniform(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state as f64 / (u32::MAX as f64 + 1.0)) as f32
+    }
+
+    /// Starts a grain in an idle slot, if there is one.
+    fn spawn(&mut self, parameters: &GranularParameters) {
+        let slot = match self.grains.iter().position(|grain| grain.length == 0) {
+            Some(slot) => slot,
+            None => return,
+        };
+        let samples_per_ms = (self.sample_rate * 0.001) as f32;
+        let length =
+            (parameters.grain_size_ms.clamp(MIN_GRAIN_MS, MAX_GRAIN_MS) * samples_per_ms).max(1.0);
+        let jitter = parameters.position_jitter.clamp(0.0, 1.0) * MAX_JITTER_MS * samples_per_ms;
+        let spread = parameters.pitch_spread.clamp(0.0, MAX_PITCH_SPREAD);
+        let offset = self.uniform() * jitter;
+        let semitones = (self.uniform() * 2.0 - 1.0) * spread;
+        let drift = 1.0 - 2.0_f32.powf(semitones / 12.0);
+        // A raised grain catches up with the input, so it starts
+        // further behind.
+        let delay = offset + (-drift * length).max(0.0);
+        self.grains[slot] = Grain {
+            delay,
+            drift,
+            length: length as usize,
+            age: 0,
+        };
+    }
+}
+
+impl Granular {
+    /// Initializes the [`Granular`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: GranularParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Granular`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Granular {
+    type Parameters = GranularParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let density = parameters.density.clamp(0.0, MAX_DENSITY);
+        let interval = if density > 0.0 {
+            self.sample_rate as f32 / density
+        } else {
+            f32::INFINITY
+        };
+        // The Hann windows overlap by this much on average.
+        let overlap = density * parameters.grain_size_ms.clamp(MIN_GRAIN_MS, MAX_GRAIN_MS) * 0.0005;
+        let gain = 1.0 / overlap.clamp(1.0, MAX_GRAINS as f32 * 0.5);
+        let mix = parameters.mix.clamp(0.0, 1.0);
+        for frame in buffer.chunks_exact_mut(2) {
+            self.lines[0].write(frame[0]);
+            self.lines[1].write(frame[1]);
+            self.countdown -= 1.0;
+            if self.countdown <= 0.0 {
+                self.spawn(&parameters);
+                self.countdown += interval;
+            }
+            let mut wet = [0.0; 2];
+            for grain in self.grains.iter_mut().filter(|grain| grain.length > 0) {
+                let window = (PI * grain.age as f32 / grain.length as f32).sin().powi(2);
+                for (wet, line) in wet.iter_mut().zip(self.lines.iter()) {
+                    *wet += line.read_cubic(grain.delay) * window;
+                }
+                grain.delay += grain.drift;
+                grain.age += 1;
+                if grain.age >= grain.length {
+                    grain.length = 0;
+                }
+            }
+            for (sample, wet) in frame.iter_mut().zip(wet) {
+                *sample = *sample * (1.0 - mix) + wet * gain * mix;
+            }
+        }
+    }
+
+    /// Clears the capture and the grains, and restarts the random
+    /// number generator from its seed.
+    fn reset(&mut self) {
+        self.lines.iter_mut().for_each(DelayLine::clear);
+        self.grains = [Grain::default(); MAX_GRAINS];
+        self.countdown = 0.0;
+        self.state = self.seed;
+    }
+
+    fn set_parameters(&mut self, parameters: GranularParameters) {
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Granular, GranularParameters, MAX_GRAINS};
+    use crate::core::effect::Effect;
+
+    fn input() -> Vec<f32> {
+        (0..2 * 44100)
+            .map(|index| (index as f32 * 0.013).sin() * 0.5)
+            .collect()
+    }
+
+    fn render(seed: u32, parameters: GranularParameters) -> Vec<f32> {
+        let mut granular = Granular::new(44100.0, seed);
+        granular.initialize(parameters);
+        let mut buffer = input();
+        for chunk in buffer.chunks_mut(2 * 333) {
+            granular.process(0, chunk);
+        }
+        buffer
+    }
+
+    #[test]
+    fn fixed_seed_is_deterministic() {
+        let parameters = GranularParameters::new(60.0, 50.0, 0.5, 7.0, 1.0);
+        let first = render(42, parameters);
+        assert_eq!(first, render(42, parameters));
+        assert_ne!(first, render(43, parameters));
+        assert!(first.iter().all(|sample| sample.is_finite()));
+    }
+
+    #[test]
+    fn reset_replays_the_same_cloud() {
+        let parameters = GranularParameters::new(40.0, 80.0, 0.3, 2.0, 1.0);
+        let mut granular = Granular::new(44100.0, 7);
+        granular.initialize(parameters);
+        let mut first = input();
+        granular.process(0, &mut first);
+        granular.reset();
+        let mut second = input();
+        granular.process(0, &mut second);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn grains_are_bounded() {
+        let mut granular = Granular::new(44100.0, 1);
+        granular.initialize(GranularParameters::new(500.0, 200.0, 1.0, 12.0, 1.0));
+        let mut buffer = input();
+        granular.process(0, &mut buffer);
+        let active = granular.grains.iter().filter(|grain| grain.length > 0);
+        assert_eq!(active.count(), MAX_GRAINS);
+        assert!(buffer.iter().all(|sample| sample.abs() < 2.0));
+    }
+
+    #[test]
+    fn dry_passes_through() {
+        let parameters = GranularParameters::new(60.0, 50.0, 0.5, 7.0, 0.0);
+        assert_eq!(render(42, parameters), input());
+    }
+}
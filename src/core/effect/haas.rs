@@ -0,0 +1,211 @@
+//! Widens the stereo image by delaying one channel, relying on the
+//! precedence effect rather than mid/side scaling.
+use super::Effect;
+use crate::core::dsp::DelayLine;
+
+/// The longest delay accepted by [`HaasParameters`], past which the
+/// delayed channel is heard as a distinct echo.
+pub const MAX_DELAY_MS: f32 = 40.0;
+
+/// The channel delayed by the [`Haas`] effect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Side {
+    /// Delays the left channel, pulling the image to the right.
+    Left,
+    /// Delays the right channel, pulling the image to the left.
+    #[default]
+    Right,
+}
+
+impl Side {
+    /// The index of the channel within an interleaved frame.
+    fn channel(&self) -> usize {
+        match self {
+            Side::Left => 0,
+            Side::Right => 1,
+        }
+    }
+}
+
+/// The parameters consumed by [`Haas`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HaasParameters {
+    /// The delay of the `side` channel, clamped to `0.0..=MAX_DELAY_MS`,
+    /// where `0.0` is transparent.
+    ///
+    /// Delays of 5 to 30 ms are heard as width rather than as an echo.
+    pub delay_ms: f32,
+    /// The channel that is delayed.
+    pub side: Side,
+}
+
+impl HaasParameters {
+    /// Creates a new [`HaasParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to spread a mono source, leaning to the left:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::haas::*;
+    /// let _ = HaasParameters::new(15.0, Side::Right);
+    /// ```
+    pub fn new(delay_ms: f32, side: Side) -> Self {
+        Self {
+            delay_ms: delay_ms.clamp(0.0, MAX_DELAY_MS),
+            side,
+        }
+    }
+}
+
+/// The Haas DSP and its internal state.
+#[derive(Debug)]
+pub struct Haas {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<HaasParameters>,
+    /// The delay line of the delayed channel.
+    line: DelayLine,
+}
+
+impl Haas {
+    /// Creates a new [`Haas`], allocating enough delay for the longest
+    /// delay up front.
+    pub fn new(sample_rate: f64) -> Self {
+        let len = (MAX_DELAY_MS as f64 * 0.001 * sample_rate) as usize + 2;
+        Self {
+            sample_rate,
+            parameters: None,
+            line: DelayLine::new(len),
+        }
+    }
+}
+
+impl Haas {
+    /// Initializes the [`Haas`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: HaasParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Haas`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Haas {
+    type Parameters = HaasParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let delay =
+            parameters.delay_ms.clamp(0.0, MAX_DELAY_MS) * (self.sample_rate * 0.001) as f32;
+        let channel = parameters.side.channel();
+        for frame in buffer.chunks_exact_mut(2) {
+            self.line.write(frame[channel]);
+            frame[channel] = self.line.read(delay);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.line.clear();
+    }
+
+    /// Replaces the parameters of the effect, clearing the delay line if
+    /// the delayed channel changes.
+    fn set_parameters(&mut self, parameters: HaasParameters) {
+        if self.parameters.map(|previous| previous.side) != Some(parameters.side) {
+            self.line.clear();
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Haas, HaasParameters, Side};
+    use crate::core::effect::Effect;
+
+    fn mono() -> Vec<f32> {
+        (0..2000)
+            .flat_map(|index| {
+                let x = (index as f32 * 0.05).sin();
+                [x, x]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn delayed_channel_is_shifted() {
+        let mut haas = Haas::new(48000.0);
+        haas.initialize(HaasParameters::new(10.0, Side::Right));
+        let input = mono();
+        let mut buffer = input.clone();
+        haas.process(0, &mut buffer);
+
+        let shift = 480;
+        for (index, frame) in buffer.chunks_exact(2).enumerate() {
+            assert_eq!(frame[0], input[index * 2]);
+            let expected = if index < shift {
+                0.0
+            } else {
+                input[(index - shift) * 2]
+            };
+            assert_eq!(frame[1], expected);
+        }
+    }
+
+    #[test]
+    fn left_side_delays_left() {
+        let mut haas = Haas::new(44100.0);
+        haas.initialize(HaasParameters::new(5.0, Side::Left));
+        let input = mono();
+        let mut buffer = input.clone();
+        haas.process(0, &mut buffer);
+        assert!(buffer.chunks_exact(2).any(|frame| frame[0] != frame[1]));
+        for (x, y) in buffer
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .zip(input.iter().skip(1).step_by(2))
+        {
+            assert_eq!(x, y);
+        }
+    }
+
+    #[test]
+    fn zero_delay_is_transparent() {
+        let mut haas = Haas::new(44100.0);
+        haas.initialize(HaasParameters::new(0.0, Side::Right));
+        let input = mono();
+        let mut buffer = input.clone();
+        haas.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+    }
+
+    #[test]
+    fn delay_is_clamped() {
+        let mut haas = Haas::new(96000.0);
+        haas.initialize(HaasParameters {
+            delay_ms: 1000.0,
+            side: Side::Right,
+        });
+        let mut buffer = vec![1.0; 2 * 8000];
+        haas.process(0, &mut buffer);
+        let silent = buffer
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .filter(|&&x| x == 0.0)
+            .count();
+        assert_eq!(silent, (0.04 * 96000.0) as usize);
+    }
+}
@@ -0,0 +1,221 @@
+//! Keeps peaks below a ceiling by reacting ahead of time.
+//!
+//! # Overview
+//!
+//! The incoming audio is delayed by the lookahead while the gain
+//! required by each frame is computed immediately. The gain is held at
+//! its minimum across the lookahead window, then averaged across the
+//! same window, which produces a smooth ramp that is guaranteed to
+//! reach the required gain by the time the peak leaves the delay.
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::dsp::{decibel::db_to_gain, envelope::time_coefficient};
+
+/// The parameters consumed by [`Limiter`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LimiterParameters {
+    /// How far ahead the limiter reacts to peaks.
+    pub lookahead_ms: f32,
+    /// The highest level that the output may reach, in dB.
+    pub ceiling_db: f32,
+    /// The time taken for the gain to recover after a peak.
+    pub release_ms: f32,
+}
+
+impl LimiterParameters {
+    /// Creates a new [`LimiterParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to catch peaks on a master bus:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::limiter::*;
+    /// let _ = LimiterParameters::new(5.0, -1.0, 50.0);
+    /// ```
+    pub fn new(lookahead_ms: f32, ceiling_db: f32, release_ms: f32) -> Self {
+        Self {
+            lookahead_ms: lookahead_ms.max(0.0),
+            ceiling_db,
+            release_ms: release_ms.max(0.0),
+        }
+    }
+}
+
+/// The limiter DSP and its internal state.
+#[derive(Debug)]
+pub struct Limiter {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<LimiterParameters>,
+    /// The lookahead in frames.
+    lookahead: usize,
+    /// The linear ceiling.
+    ceiling: f32,
+    /// The smoothing coefficient for recovering gain.
+    release: f32,
+    /// The interleaved stereo audio awaiting output.
+    delay: Vec<f32>,
+    /// The gain required by each frame in the window.
+    required: Vec<f32>,
+    /// The held gain of each frame in the window.
+    held: Vec<f32>,
+    /// The sum of `held`, used for averaging.
+    held_sum: f64,
+    /// The previous held gain, used for the release.
+    last_held: f32,
+    /// The frame in the window that is read and written next.
+    index: usize,
+    /// The frame in the delay that is read and written next.
+    delay_index: usize,
+}
+
+impl Limiter {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            parameters: None,
+            lookahead: 0,
+            ceiling: 1.0,
+            release: 0.0,
+            delay: vec![],
+            required: vec![1.0],
+            held: vec![1.0],
+            held_sum: 1.0,
+            last_held: 1.0,
+            index: 0,
+            delay_index: 0,
+        }
+    }
+}
+
+impl Limiter {
+    /// Initializes the [`Limiter`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: LimiterParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Limiter`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Limiter {
+    type Parameters = LimiterParameters;
+
+    /// The delay introduced by the lookahead, in frames.
+    fn latency_samples(&self) -> usize {
+        self.lookahead
+    }
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        if self.parameters.is_none() {
+            return;
+        }
+        let window = self.lookahead + 1;
+        for frame in buffer.chunks_exact_mut(2) {
+            let peak = frame[0].abs().max(frame[1].abs());
+            self.required[self.index] = if peak > self.ceiling {
+                self.ceiling / peak
+            } else {
+                1.0
+            };
+
+            let hold = self.required.iter().fold(1.0, |a: f32, &b| a.min(b));
+            let recovered = self.release * self.last_held + (1.0 - self.release);
+            let hold = hold.min(recovered);
+            self.last_held = hold;
+
+            self.held_sum += (hold - self.held[self.index]) as f64;
+            self.held[self.index] = hold;
+            let gain = (self.held_sum / window as f64) as f32;
+
+            let (output_0, output_1) = if self.lookahead == 0 {
+                (frame[0], frame[1])
+            } else {
+                let slot = self.delay_index * 2;
+                let delayed = (self.delay[slot], self.delay[slot + 1]);
+                self.delay[slot] = frame[0];
+                self.delay[slot + 1] = frame[1];
+                self.delay_index = (self.delay_index + 1) % self.lookahead;
+                delayed
+            };
+            // The average is guaranteed to be under the required gain,
+            // but rounding can still leave it a hair above the ceiling.
+            frame[0] = (output_0 * gain).clamp(-self.ceiling, self.ceiling);
+            frame[1] = (output_1 * gain).clamp(-self.ceiling, self.ceiling);
+
+            self.index = (self.index + 1) % window;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.delay.fill(0.0);
+        self.required.fill(1.0);
+        self.held.fill(1.0);
+        self.held_sum = self.held.len() as f64;
+        self.last_held = 1.0;
+        self.index = 0;
+        self.delay_index = 0;
+    }
+
+    /// Replaces the parameters of the effect, reallocating and clearing
+    /// the lookahead if its length changes.
+    fn set_parameters(&mut self, parameters: LimiterParameters) {
+        let lookahead = (parameters.lookahead_ms as f64 * 0.001 * self.sample_rate) as usize;
+        if lookahead != self.lookahead || self.parameters.is_none() {
+            self.lookahead = lookahead;
+            self.delay = vec![0.0; lookahead * 2];
+            self.required = vec![1.0; lookahead + 1];
+            self.held = vec![1.0; lookahead + 1];
+            self.reset();
+        }
+        self.release = time_coefficient(parameters.release_ms, self.sample_rate);
+        self.ceiling = db_to_gain(parameters.ceiling_db);
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Limiter, LimiterParameters};
+    use crate::core::{dsp::decibel::db_to_gain, effect::Effect};
+
+    #[test]
+    fn impulse_stays_below_ceiling() {
+        let ceiling = db_to_gain(-1.0);
+        for lookahead_ms in [0.0, 5.0] {
+            let mut limiter = Limiter::new(44100.0);
+            limiter.initialize(LimiterParameters::new(lookahead_ms, -1.0, 50.0));
+            let mut buffer = vec![0.1; 2 * 1024];
+            buffer[600] = db_to_gain(6.0);
+            buffer[601] = -db_to_gain(6.0);
+            limiter.process(0, &mut buffer);
+            assert!(buffer.iter().all(|sample| sample.abs() <= ceiling));
+
+            let latency = limiter.latency_samples();
+            assert!(buffer[(300 + latency) * 2 + 1].abs() > 0.5);
+        }
+    }
+
+    #[test]
+    fn quiet_signal_is_delayed() {
+        let mut limiter = Limiter::new(44100.0);
+        limiter.initialize(LimiterParameters::new(1.0, 0.0, 50.0));
+        let latency = limiter.latency_samples();
+        assert_eq!(latency, 44);
+
+        let input: Vec<f32> = (0..512)
+            .map(|index| (index as f32 * 0.01).sin() * 0.5)
+            .collect();
+        let mut buffer = input.clone();
+        limiter.process(0, &mut buffer);
+        assert_eq!(buffer[latency * 2..], input[..input.len() - latency * 2]);
+    }
+}
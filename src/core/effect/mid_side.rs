@@ -0,0 +1,91 @@
+//! Runs an effect over the mid and side of a stereo signal.
+use super::Effect;
+use crate::core::dsp::ms;
+use crate::core::transport::Transport;
+
+/// Encodes the input to mid/side, runs an inner [`Effect`] over the mid
+/// as the left channel and the side as the right, then decodes.
+///
+/// Effects processing their channels independently can then target the
+/// side alone, e.g. gating only the stereo image.
+#[derive(Debug)]
+pub struct MidSide<E: Effect> {
+    /// The effect processing the mid and side.
+    effect: E,
+}
+
+impl<E: Effect> MidSide<E> {
+    /// Creates a new [`MidSide`] wrapping an `effect`.
+    pub fn new(effect: E) -> Self {
+        Self { effect }
+    }
+
+    /// A reference to the inner effect.
+    pub fn inner(&self) -> &E {
+        &self.effect
+    }
+
+    /// A mutable reference to the inner effect.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.effect
+    }
+}
+
+impl<E: Effect> Effect for MidSide<E> {
+    type Parameters = E::Parameters;
+
+    fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        ms::encode(buffer);
+        self.effect.process(position, buffer);
+        ms::decode(buffer);
+    }
+
+    fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [f32]) {
+        ms::encode(buffer);
+        self.effect.process_with_transport(transport, buffer);
+        ms::decode(buffer);
+    }
+
+    fn reset(&mut self) {
+        self.effect.reset();
+    }
+
+    fn set_parameters(&mut self, parameters: E::Parameters) {
+        self.effect.set_parameters(parameters);
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.effect.latency_samples()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MidSide;
+    use crate::core::effect::Effect;
+
+    /// Silences the right channel, i.e. the side.
+    struct MuteRight;
+
+    impl Effect for MuteRight {
+        type Parameters = ();
+
+        fn process(&mut self, _: usize, buffer: &mut [f32]) {
+            for frame in buffer.chunks_exact_mut(2) {
+                frame[1] = 0.0;
+            }
+        }
+
+        fn reset(&mut self) {}
+
+        fn set_parameters(&mut self, _: ()) {}
+    }
+
+    #[test]
+    fn removing_side_collapses_to_mono() {
+        let mut mid_side = MidSide::new(MuteRight);
+        let mut buffer = vec![1.0, 0.0, 0.25, 0.75];
+        mid_side.process(0, &mut buffer);
+        assert_eq!(buffer, [0.5, 0.5, 0.5, 0.5]);
+    }
+}
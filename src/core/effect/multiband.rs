@@ -0,0 +1,324 @@
+//! Splits the signal into frequency bands, each with its own effect.
+//!
+//! # Overview
+//!
+//! Each crossover is a 4th-order Linkwitz-Riley pair, i.e. two cascaded
+//! Butterworth filters, whose low-pass and high-pass outputs sum to an
+//! allpass. The bands below a crossover never pass through it, so they
+//! are run through the matching allpass instead, such that every band
+//! shares the same phase response and the sum stays flat:
+//!
+//! ```text
+//!        +- LP(f0) -- AP(f1) --------- band 0
+//! in ----+
+//!        +- HP(f0) -+- LP(f1) -------- band 1
+//!                   +- HP(f1) -------- band 2
+//! ```
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::f64::consts::FRAC_1_SQRT_2;
+
+use super::Effect;
+use crate::core::dsp::{Biquad, LinkwitzRiley};
+
+/// The parameters consumed by [`Multiband`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultibandParameters {
+    /// The frequencies between adjacent bands in Hz, sorted in
+    /// ascending order, such that there is one more band than there are
+    /// crossovers.
+    pub crossovers: Vec<f64>,
+}
+
+impl MultibandParameters {
+    /// Creates a new [`MultibandParameters`], sorting the crossovers.
+    ///
+    /// # Example
+    ///
+    /// If you want separate low, mid, and high bands:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::multiband::*;
+    /// let _ = MultibandParameters::new(vec![200.0, 2000.0]);
+    /// ```
+    pub fn new(mut crossovers: Vec<f64>) -> Self {
+        crossovers.retain(|frequency| frequency.is_finite());
+        crossovers.sort_by(f64::total_cmp);
+        Self { crossovers }
+    }
+}
+
+/// The filters of a single channel.
+#[derive(Debug, Clone, Default)]
+struct Channel {
+    /// The low-pass and high-pass of each crossover.
+    splits: Vec<(LinkwitzRiley, LinkwitzRiley)>,
+    /// The allpasses of each band, one for every crossover above it.
+    compensation: Vec<Vec<Biquad>>,
+}
+
+impl Channel {
+    fn new(crossovers: &[f64], sample_rate: f64) -> Self {
+        let splits = crossovers
+            .iter()
+            .map(|&frequency| {
+                (
+                    LinkwitzRiley::lowpass(frequency, sample_rate),
+                    LinkwitzRiley::highpass(frequency, sample_rate),
+                )
+            })
+            .collect();
+        let compensation = (0..=crossovers.len())
+            .map(|band| {
+                crossovers
+                    .iter()
+                    .skip(band + 1)
+                    .map(|&frequency| Biquad::allpass(frequency, FRAC_1_SQRT_2, sample_rate))
+                    .collect()
+            })
+            .collect();
+        Self {
+            splits,
+            compensation,
+        }
+    }
+
+    /// Copies the coefficients of a channel with the same number of
+    /// crossovers, keeping the state of the filters.
+    fn retune(&mut self, tuned: &Channel) {
+        for ((low, high), tuned) in self.splits.iter_mut().zip(tuned.splits.iter()) {
+            low.set_coefficients(&tuned.0);
+            high.set_coefficients(&tuned.1);
+        }
+        for (band, tuned) in self.compensation.iter_mut().zip(tuned.compensation.iter()) {
+            for (filter, tuned) in band.iter_mut().zip(tuned.iter()) {
+                filter.set_coefficients(tuned.coefficients());
+            }
+        }
+    }
+}
+
+/// Processes each frequency band with a separate effect and sums them.
+pub struct Multiband {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<MultibandParameters>,
+    /// The filters of each channel.
+    channels: [Channel; 2],
+    /// The effect of each band, where `None` passes it through.
+    effects: Vec<Option<Box<dyn Effect>>>,
+    /// The interleaved samples of each band.
+    bands: Vec<Vec<f32>>,
+}
+
+impl Multiband {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            parameters: None,
+            channels: Default::default(),
+            effects: vec![],
+            bands: vec![],
+        }
+    }
+
+    /// The number of bands, one more than the number of crossovers.
+    pub fn bands(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Sets the effect applied to the band at `index`, where `None`
+    /// passes the band through, returning the previous effect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no band at `index`.
+    pub fn set_band(
+        &mut self,
+        index: usize,
+        effect: Option<Box<dyn Effect>>,
+    ) -> Option<Box<dyn Effect>> {
+        core::mem::replace(&mut self.effects[index], effect)
+    }
+
+    /// Preallocates the band buffers for blocks of up to `len` samples,
+    /// such that `process` does not allocate.
+    pub fn reserve(&mut self, len: usize) {
+        for band in self.bands.iter_mut() {
+            if band.len() < len {
+                band.resize(len, 0.0);
+            }
+        }
+    }
+}
+
+impl Multiband {
+    /// Initializes the [`Multiband`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: MultibandParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Multiband`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Multiband {
+    type Parameters = MultibandParameters;
+
+    fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        if self.parameters.is_none() {
+            return;
+        }
+        self.reserve(buffer.len());
+        let len = buffer.len() - buffer.len() % 2;
+        for (index, frame) in buffer.chunks_exact(2).enumerate() {
+            for (channel, (&x, filters)) in frame.iter().zip(self.channels.iter_mut()).enumerate() {
+                let mut rest = x;
+                for (band, (low, high)) in filters.splits.iter_mut().enumerate() {
+                    let mut y = low.process(rest);
+                    for allpass in filters.compensation[band].iter_mut() {
+                        y = allpass.process(y);
+                    }
+                    self.bands[band][index * 2 + channel] = y;
+                    rest = high.process(rest);
+                }
+                self.bands[filters.splits.len()][index * 2 + channel] = rest;
+            }
+        }
+
+        buffer[..len].fill(0.0);
+        for (band, effect) in self.bands.iter_mut().zip(self.effects.iter_mut()) {
+            let band = &mut band[..len];
+            if let Some(effect) = effect {
+                effect.process(position, band);
+            }
+            for (sample, y) in buffer.iter_mut().zip(band.iter()) {
+                *sample += y;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        if let Some(parameters) = &self.parameters {
+            self.channels = [
+                Channel::new(&parameters.crossovers, self.sample_rate),
+                Channel::new(&parameters.crossovers, self.sample_rate),
+            ];
+        }
+        for effect in self.effects.iter_mut().flatten() {
+            effect.reset();
+        }
+    }
+
+    /// Replaces the parameters of the effect.
+    ///
+    /// Changing the number of crossovers reallocates the filters and
+    /// the band buffers, and should not be done on the audio thread.
+    fn set_parameters(&mut self, parameters: MultibandParameters) {
+        let bands = parameters.crossovers.len() + 1;
+        if bands != self.effects.len() {
+            self.effects.resize_with(bands, || None);
+            self.bands.resize_with(bands, Vec::new);
+            self.channels = [
+                Channel::new(&parameters.crossovers, self.sample_rate),
+                Channel::new(&parameters.crossovers, self.sample_rate),
+            ];
+        } else {
+            // Retune the filters in place, keeping their state.
+            let tuned = Channel::new(&parameters.crossovers, self.sample_rate);
+            for channel in self.channels.iter_mut() {
+                channel.retune(&tuned);
+            }
+        }
+        self.parameters = Some(parameters);
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.effects
+            .iter()
+            .flatten()
+            .map(|effect| effect.latency_samples())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl core::fmt::Debug for Multiband {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Multiband")
+            .field("parameters", &self.parameters)
+            .field("bands", &self.effects.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{Multiband, MultibandParameters};
+    use crate::core::effect::Effect;
+
+    #[test]
+    fn pass_through_is_flat() {
+        for frequency in [30.0, 150.0, 200.0, 450.0, 1000.0, 2000.0, 5000.0, 15000.0] {
+            let mut multiband = Multiband::new(44100.0);
+            multiband.initialize(MultibandParameters::new(vec![2000.0, 200.0]));
+            assert_eq!(multiband.bands(), 3);
+
+            let mut buffer: Vec<f32> = (0..44100)
+                .flat_map(|index| {
+                    let x = (TAU * frequency * index as f64 / 44100.0).sin() as f32;
+                    [x, x]
+                })
+                .collect();
+            multiband.process(0, &mut buffer);
+            let peak = buffer[buffer.len() / 2..]
+                .iter()
+                .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+            let db = 20.0 * peak.log10();
+            assert!(db.abs() < 1.0, "{} Hz: {} dB", frequency, db);
+        }
+    }
+
+    /// Silences its input.
+    struct Mute;
+
+    impl Effect for Mute {
+        type Parameters = ();
+
+        fn process(&mut self, _: usize, buffer: &mut [f32]) {
+            buffer.fill(0.0);
+        }
+
+        fn reset(&mut self) {}
+
+        fn set_parameters(&mut self, _: ()) {}
+    }
+
+    #[test]
+    fn bands_are_processed_separately() {
+        let mut multiband = Multiband::new(44100.0);
+        multiband.initialize(MultibandParameters::new(vec![1000.0]));
+        multiband.set_band(1, Some(Box::new(Mute)));
+
+        for (frequency, expected) in [(100.0, 1.0_f32), (10000.0, 0.0)] {
+            let mut buffer: Vec<f32> = (0..8192)
+                .flat_map(|index| {
+                    let x = (TAU * frequency * index as f64 / 44100.0).sin() as f32;
+                    [x, x]
+                })
+                .collect();
+            multiband.process(0, &mut buffer);
+            let peak = buffer[8192..]
+                .iter()
+                .fold(0.0_f32, |peak, x| peak.max(x.abs()));
+            assert!((peak - expected).abs() < 0.1, "{} Hz: {}", frequency, peak);
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Silences the signal while its level stays below a threshold.
+use super::Effect;
+use crate::core::dsp::{
+    decibel::gain_to_db,
+    envelope::{time_coefficient, EnvelopeFollower, Mode},
+};
+
+/// The release of the level detector, long enough to ride over the
+/// zero crossings of low frequencies without adding to the attack.
+const DETECTOR_RELEASE_MS: f32 = 10.0;
+
+/// The parameters consumed by [`NoiseGate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoiseGateParameters {
+    /// The level above which the gate opens, in dB.
+    pub threshold_db: f32,
+    /// The time taken for the gate to open.
+    pub attack_ms: f32,
+    /// The time the gate stays open after the level falls below the
+    /// closing threshold.
+    pub hold_ms: f32,
+    /// The time taken for the gate to close.
+    pub release_ms: f32,
+    /// How far below the threshold the level must fall before the gate
+    /// closes, in dB.
+    pub hysteresis_db: f32,
+}
+
+impl NoiseGateParameters {
+    /// Creates a new [`NoiseGateParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to clean up the noise floor between drum hits:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::noise_gate::*;
+    /// let _ = NoiseGateParameters::new(-40.0, 0.5, 20.0, 50.0, 6.0);
+    /// ```
+    pub fn new(
+        threshold_db: f32,
+        attack_ms: f32,
+        hold_ms: f32,
+        release_ms: f32,
+        hysteresis_db: f32,
+    ) -> Self {
+        Self {
+            threshold_db,
+            attack_ms: attack_ms.max(0.0),
+            hold_ms: hold_ms.max(0.0),
+            release_ms: release_ms.max(0.0),
+            hysteresis_db: hysteresis_db.max(0.0),
+        }
+    }
+
+    /// The level below which the gate starts to close, in dB.
+    pub fn close_threshold_db(&self) -> f32 {
+        self.threshold_db - self.hysteresis_db.max(0.0)
+    }
+}
+
+/// The noise gate DSP and its internal state.
+#[derive(Debug)]
+pub struct NoiseGate {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<NoiseGateParameters>,
+    /// The level detector shared by both channels.
+    detector: EnvelopeFollower,
+    /// Whether the level last crossed above the threshold.
+    open: bool,
+    /// The frames left before a falling level closes the gate.
+    hold: usize,
+    /// The gain currently applied, gliding towards `0.0` or `1.0`.
+    gain: f32,
+    /// The smoothing coefficient for an opening gate.
+    attack: f32,
+    /// The smoothing coefficient for a closing gate.
+    release: f32,
+}
+
+impl NoiseGate {
+    pub fn new(sample_rate: f64) -> Self {
+        let mut detector = EnvelopeFollower::new(Mode::Peak, sample_rate);
+        detector.set_release_ms(DETECTOR_RELEASE_MS);
+        Self {
+            sample_rate,
+            parameters: None,
+            detector,
+            open: false,
+            hold: 0,
+            gain: 0.0,
+            attack: 0.0,
+            release: 0.0,
+        }
+    }
+
+    /// Whether the gate is open, including while it is being held.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+impl NoiseGate {
+    /// Initializes the [`NoiseGate`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: NoiseGateParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`NoiseGate`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for NoiseGate {
+    type Parameters = NoiseGateParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let hold = (parameters.hold_ms.max(0.0) as f64 * 0.001 * self.sample_rate) as usize;
+        for frame in buffer.chunks_exact_mut(2) {
+            let level_db = gain_to_db(self.detector.process(frame[0].abs().max(frame[1].abs())));
+            if level_db > parameters.threshold_db {
+                self.open = true;
+                self.hold = hold;
+            } else if self.open && level_db < parameters.close_threshold_db() {
+                if self.hold == 0 {
+                    self.open = false;
+                } else {
+                    self.hold -= 1;
+                }
+            } else if self.open {
+                // Levels within the hysteresis keep the gate held open.
+                self.hold = hold;
+            }
+
+            let (target, coefficient) = if self.open {
+                (1.0, self.attack)
+            } else {
+                (0.0, self.release)
+            };
+            self.gain = target + coefficient * (self.gain - target);
+            frame[0] *= self.gain;
+            frame[1] *= self.gain;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.detector.reset();
+        self.open = false;
+        self.hold = 0;
+        self.gain = 0.0;
+    }
+
+    fn set_parameters(&mut self, parameters: NoiseGateParameters) {
+        self.attack = time_coefficient(parameters.attack_ms, self.sample_rate);
+        self.release = time_coefficient(parameters.release_ms, self.sample_rate);
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::{NoiseGate, NoiseGateParameters};
+    use crate::core::effect::Effect;
+
+    #[test]
+    fn closes_between_bursts() {
+        let sample_rate = 44100.0;
+        // Bursts of 50 ms separated by 200 ms of low-level noise.
+        let mut buffer: Vec<f32> = (0..44100)
+            .flat_map(|index| {
+                let x = if index % 11025 < 2205 {
+                    (TAU * 440.0 * index as f32 / sample_rate as f32).sin()
+                } else if index % 2 == 0 {
+                    0.001
+                } else {
+                    -0.001
+                };
+                [x, x]
+            })
+            .collect();
+        let mut gate = NoiseGate::new(sample_rate);
+        gate.initialize(NoiseGateParameters::new(-40.0, 0.1, 20.0, 5.0, 6.0));
+        gate.process(0, &mut buffer);
+
+        for burst in 0..4 {
+            let start = burst * 11025;
+            // The burst passes once the gate has opened.
+            let peak = buffer[2 * (start + 100)..2 * (start + 2205)]
+                .iter()
+                .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+            assert!(peak > 0.99, "{}", peak);
+            // The gate has closed well before the next burst.
+            let gap = &buffer[2 * (start + 9000)..2 * (start + 11025)];
+            assert!(gap.iter().all(|sample| sample.abs() < 1e-6));
+        }
+    }
+
+    #[test]
+    fn hysteresis_prevents_chatter() {
+        let sample_rate = 44100.0;
+        let mut gate = NoiseGate::new(sample_rate);
+        gate.initialize(NoiseGateParameters::new(-40.0, 0.1, 5.0, 10.0, 6.0));
+        let mut toggles = 0;
+        let mut was_open = false;
+        // The level hovers between -38 dB and -42 dB every 10 ms.
+        for index in 0..44100 {
+            let amplitude = if index / 441 % 2 == 0 { 0.0126 } else { 0.0079 };
+            let x = amplitude * if index % 2 == 0 { 1.0 } else { -1.0 };
+            gate.process(0, &mut [x, x]);
+            if gate.is_open() != was_open {
+                toggles += 1;
+                was_open = gate.is_open();
+            }
+        }
+        assert_eq!(toggles, 1);
+    }
+}
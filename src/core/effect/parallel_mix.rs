@@ -0,0 +1,183 @@
+//! Blends the output of an effect with its input, lined up for latency.
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::dsp::delay_comp::DelayCompensator;
+use crate::core::transport::Transport;
+
+/// Mixes the dry input with the wet output of an inner [`Effect`] like
+/// [`DryWet`], but first delays the dry input by the latency of the
+/// effect, such that both paths stay in phase.
+///
+/// [`DryWet`]: super::DryWet
+#[derive(Debug)]
+pub struct ParallelMix<E: Effect> {
+    /// The effect producing the wet signal.
+    effect: E,
+    /// Determines how much of the wet signal is mixed in, from `0.0`
+    /// for fully dry to `1.0` for fully wet.
+    mix: f32,
+    /// The delay of the dry input of each channel.
+    dry: [DelayCompensator; 2],
+    /// A copy of the dry input, reused across calls to `process`.
+    scratch: Vec<f32>,
+}
+
+impl<E: Effect> ParallelMix<E> {
+    /// Creates a new [`ParallelMix`] wrapping an `effect`.
+    pub fn new(effect: E, mix: f32) -> Self {
+        let dry = DelayCompensator::new(effect.latency_samples());
+        Self {
+            effect,
+            mix: mix.clamp(0.0, 1.0),
+            dry: [dry.clone(), dry],
+            scratch: vec![],
+        }
+    }
+
+    /// Preallocates the scratch buffer for blocks of up to `len`
+    /// samples, such that `process` does not allocate.
+    pub fn reserve(&mut self, len: usize) {
+        if self.scratch.len() < len {
+            self.scratch.resize(len, 0.0);
+        }
+    }
+
+    /// Sets the amount of wet signal that is mixed in.
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    /// The amount of wet signal that is mixed in.
+    pub fn mix(&self) -> f32 {
+        self.mix
+    }
+
+    /// Matches the delay of the dry input to the latency of the effect,
+    /// which may change along with its parameters.
+    fn sync_latency(&mut self) {
+        let latency = self.effect.latency_samples();
+        for dry in self.dry.iter_mut() {
+            if dry.delay() != latency {
+                dry.set_delay(latency);
+            }
+        }
+    }
+
+    /// Runs the inner effect over the `buffer` with `process`, then
+    /// mixes the delayed dry input back in.
+    fn mix_with(&mut self, buffer: &mut [f32], process: impl FnOnce(&mut E, &mut [f32])) {
+        self.sync_latency();
+        self.reserve(buffer.len());
+        let dry = &mut self.scratch[..buffer.len()];
+        dry.copy_from_slice(buffer);
+        process(&mut self.effect, buffer);
+        for (wet, dry) in buffer.chunks_exact_mut(2).zip(dry.chunks_exact(2)) {
+            for ((wet, &dry), delay) in wet.iter_mut().zip(dry.iter()).zip(self.dry.iter_mut()) {
+                *wet = delay.process(dry) * (1.0 - self.mix) + *wet * self.mix;
+            }
+        }
+    }
+
+    /// A reference to the inner effect.
+    pub fn inner(&self) -> &E {
+        &self.effect
+    }
+
+    /// A mutable reference to the inner effect.
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.effect
+    }
+}
+
+impl<E: Effect> Effect for ParallelMix<E> {
+    type Parameters = E::Parameters;
+
+    fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        self.mix_with(buffer, |effect, buffer| effect.process(position, buffer));
+    }
+
+    fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [f32]) {
+        self.mix_with(buffer, |effect, buffer| {
+            effect.process_with_transport(transport, buffer)
+        });
+    }
+
+    fn reset(&mut self) {
+        self.effect.reset();
+        self.dry.iter_mut().for_each(DelayCompensator::reset);
+    }
+
+    fn set_parameters(&mut self, parameters: E::Parameters) {
+        self.effect.set_parameters(parameters);
+        self.sync_latency();
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.effect.latency_samples()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParallelMix;
+    use crate::core::effect::Effect;
+
+    /// Delays the signal by a fixed number of frames, reporting it as
+    /// its latency.
+    struct Latent {
+        buffer: Vec<f32>,
+    }
+
+    impl Effect for Latent {
+        type Parameters = usize;
+
+        fn process(&mut self, _: usize, buffer: &mut [f32]) {
+            for sample in buffer.iter_mut() {
+                self.buffer.push(*sample);
+                *sample = self.buffer.remove(0);
+            }
+        }
+
+        fn reset(&mut self) {
+            self.buffer.fill(0.0);
+        }
+
+        fn set_parameters(&mut self, latency: usize) {
+            self.buffer = vec![0.0; 2 * latency];
+        }
+
+        fn latency_samples(&self) -> usize {
+            self.buffer.len() / 2
+        }
+    }
+
+    #[test]
+    fn dry_lines_up_with_wet() {
+        let latency = 37;
+        let mut mix = ParallelMix::new(
+            Latent {
+                buffer: vec![0.0; 2 * latency],
+            },
+            0.5,
+        );
+        let mut buffer = vec![0.0; 2 * 256];
+        buffer[0] = 1.0;
+        buffer[1] = -1.0;
+        for block in buffer.chunks_mut(2 * 50) {
+            mix.process(0, block);
+        }
+        let mut expected = vec![0.0; 2 * 256];
+        expected[2 * latency] = 1.0;
+        expected[2 * latency + 1] = -1.0;
+        assert_eq!(buffer, expected);
+
+        // The dry path follows a change in latency.
+        mix.set_parameters(5);
+        let mut buffer = vec![0.0; 2 * 64];
+        buffer[0] = 1.0;
+        mix.process(0, &mut buffer);
+        assert_eq!(buffer[10], 1.0);
+        assert_eq!(buffer.iter().sum::<f32>(), 1.0);
+    }
+}
@@ -0,0 +1,289 @@
+//! Sweeps notches across the signal with a cascade of allpass filters.
+use core::f64::consts::PI;
+
+use super::Effect;
+use crate::core::dsp::{denormal::flush, Lfo, Waveform};
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The number of allpass sections preallocated by each channel.
+pub const MAX_STAGES: usize = 12;
+
+/// The largest feedback amount accepted by [`PhaserParameters`].
+pub const MAX_FEEDBACK: f32 = 0.95;
+
+/// The break frequency of the allpass sections at the bottom of the sweep.
+pub const MIN_FREQUENCY: f64 = 200.0;
+
+/// The break frequency of the allpass sections at the top of a full
+/// depth sweep.
+pub const MAX_FREQUENCY: f64 = 4000.0;
+
+/// The parameters consumed by [`Phaser`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhaserParameters {
+    /// The frequency of the modulation in Hz.
+    pub rate_hz: f32,
+    /// The portion of the frequency range swept by the modulation,
+    /// clamped to `0.0..=1.0`.
+    pub depth: f32,
+    /// The number of allpass sections, clamped to `1..=MAX_STAGES`.
+    pub stages: usize,
+    /// Determines how much of the filtered signal is fed back, clamped
+    /// to `-MAX_FEEDBACK..=MAX_FEEDBACK`.
+    pub feedback: f32,
+    /// Determines how much of the filtered signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl PhaserParameters {
+    /// Creates a new [`PhaserParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a classic four stage phaser:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::phaser::*;
+    /// let _ = PhaserParameters::new(0.5, 0.8, 4, 0.3, 0.5);
+    /// ```
+    pub fn new(rate_hz: f32, depth: f32, stages: usize, feedback: f32, mix: f32) -> Self {
+        Self {
+            rate_hz: rate_hz.max(0.0),
+            depth: depth.clamp(0.0, 1.0),
+            stages: stages.clamp(1, MAX_STAGES),
+            feedback: feedback.clamp(-MAX_FEEDBACK, MAX_FEEDBACK),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// A first-order allpass section.
+#[derive(Debug, Clone, Copy, Default)]
+struct Allpass {
+    x1: f32,
+    y1: f32,
+}
+
+impl Allpass {
+    fn process(&mut self, coefficient: f32, x: f32) -> f32 {
+        let y = flush(coefficient * x + self.x1 - coefficient * self.y1);
+        self.x1 = x;
+        self.y1 = y;
+        y
+    }
+}
+
+/// The phaser DSP and its internal state.
+#[derive(Debug)]
+pub struct Phaser {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<PhaserParameters>,
+    /// The allpass sections of each channel, of which only the first
+    /// `stages` are used.
+    sections: [[Allpass; MAX_STAGES]; 2],
+    /// The output of the last section of each channel.
+    last: [f32; 2],
+    /// The modulation shared by both channels.
+    lfo: Lfo,
+}
+
+impl Phaser {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            parameters: None,
+            sections: [[Allpass::default(); MAX_STAGES]; 2],
+            last: [0.0; 2],
+            lfo: Lfo::new(Waveform::Sine, 0.0, sample_rate),
+        }
+    }
+
+    /// Compute the allpass coefficient for a break frequency.
+    fn coefficient(&self, frequency: f64) -> f32 {
+        let t = (PI * frequency / self.sample_rate).tan();
+        ((t - 1.0) / (t + 1.0)) as f32
+    }
+}
+
+impl Phaser {
+    /// Initializes the [`Phaser`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: PhaserParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Phaser`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Phaser {
+    type Parameters = PhaserParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let stages = parameters.stages.clamp(1, MAX_STAGES);
+        let ratio = MAX_FREQUENCY / MIN_FREQUENCY;
+        for frame in buffer.chunks_exact_mut(2) {
+            let sweep = parameters.depth as f64 * 0.5 * (1.0 + self.lfo.next() as f64);
+            let coefficient = self.coefficient(MIN_FREQUENCY * ratio.powf(sweep));
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let mut wet = flush(*sample + self.last[channel] * parameters.feedback);
+                for section in self.sections[channel][..stages].iter_mut() {
+                    wet = section.process(coefficient, wet);
+                }
+                self.last[channel] = wet;
+                *sample = *sample * (1.0 - parameters.mix) + wet * parameters.mix;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.sections = [[Allpass::default(); MAX_STAGES]; 2];
+        self.last = [0.0; 2];
+        self.lfo.reset();
+    }
+
+    /// Replaces the parameters of the effect.
+    ///
+    /// The sections are preallocated, such that changing the number of
+    /// `stages` is safe on the audio thread. Sections brought into use
+    /// start from silence.
+    fn set_parameters(&mut self, parameters: PhaserParameters) {
+        let previous = self.parameters.map_or(0, |previous| previous.stages);
+        let stages = parameters.stages.clamp(1, MAX_STAGES);
+        if stages > previous {
+            for sections in self.sections.iter_mut() {
+                for section in sections[previous..stages].iter_mut() {
+                    *section = Allpass::default();
+                }
+            }
+        }
+        self.lfo.set_frequency(parameters.rate_hz as f64);
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{Phaser, PhaserParameters};
+    use crate::core::{effect::Effect, testing::assert_no_alloc};
+
+    /// Computes the amplitude and phase of the left channel at
+    /// `frequency` over a window starting at `frame`.
+    fn measure(buffer: &[f32], frame: usize, frequency: f64) -> (f64, f64) {
+        let len = 4410;
+        let (re, im) = buffer[frame * 2..(frame + len) * 2]
+            .chunks_exact(2)
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (index, frame)| {
+                let phase = TAU * frequency * index as f64 / 44100.0;
+                let x = frame[0] as f64;
+                (re + x * phase.cos(), im - x * phase.sin())
+            });
+        (2.0 * re.hypot(im) / len as f64, im.atan2(re))
+    }
+
+    #[test]
+    fn phase_is_modulated() {
+        let input: Vec<f32> = (0..44100 * 2)
+            .flat_map(|index| {
+                let x = (TAU * 1000.0 * index as f64 / 44100.0).sin() as f32;
+                [x, x]
+            })
+            .collect();
+        let mut phaser = Phaser::new(44100.0);
+        phaser.initialize(PhaserParameters::new(0.5, 1.0, 4, 0.0, 1.0));
+        let mut output = input.clone();
+        phaser.process(0, &mut output);
+
+        // The sweep peaks half a second in, then bottoms out a second
+        // after that, with both windows spanning whole cycles.
+        let (top, bottom) = (22050 - 2205, 66150 - 2205);
+        let (top_amplitude, top_phase) = measure(&output, top, 1000.0);
+        let (bottom_amplitude, bottom_phase) = measure(&output, bottom, 1000.0);
+        assert!((top_amplitude - 1.0).abs() < 0.01);
+        assert!((bottom_amplitude - 1.0).abs() < 0.01);
+        let delta = (top_phase - bottom_phase).rem_euclid(TAU);
+        assert!(delta > 0.5 && delta < TAU - 0.5, "{}", delta);
+    }
+
+    #[test]
+    fn notch_moves() {
+        // Four sections cancel the dry signal where each shifts by 45
+        // degrees, i.e. at `tan(PI / 8)` of the top break frequency.
+        let frequency = 1650.0;
+        let input: Vec<f32> = (0..44100 * 2)
+            .flat_map(|index| {
+                let x = (TAU * frequency * index as f64 / 44100.0).sin() as f32;
+                [x, x]
+            })
+            .collect();
+        let mut phaser = Phaser::new(44100.0);
+        phaser.initialize(PhaserParameters::new(0.5, 1.0, 4, 0.0, 0.5));
+        let mut output = input.clone();
+        phaser.process(0, &mut output);
+
+        let (top, _) = measure(&output, 22050 - 2205, frequency);
+        let (bottom, _) = measure(&output, 66150 - 2205, frequency);
+        assert!(top < 0.2, "{}", top);
+        assert!(bottom > 0.7, "{}", bottom);
+    }
+
+    #[test]
+    fn tail_flushes_to_zero() {
+        let mut phaser = Phaser::new(44100.0);
+        phaser.initialize(PhaserParameters::new(0.5, 1.0, 12, 0.9, 1.0));
+        let mut buffer = vec![0.0; 2 * 44100 * 4];
+        buffer[0] = 1.0;
+        phaser.process(0, &mut buffer);
+        assert!(buffer.iter().all(|sample| !sample.is_subnormal()));
+        assert!(phaser.last.iter().all(|sample| !sample.is_subnormal()));
+        for section in phaser.sections.iter().flatten() {
+            assert!(!section.x1.is_subnormal() && !section.y1.is_subnormal());
+        }
+    }
+
+    #[test]
+    fn stages_change_in_place() {
+        let input: Vec<f32> = (0..1024)
+            .flat_map(|index| {
+                let x = (TAU * 1650.0 * index as f64 / 44100.0).sin() as f32;
+                [x, x]
+            })
+            .collect();
+        let (first, second) = input.split_at(1024);
+        let mut phasers = [Phaser::new(44100.0), Phaser::new(44100.0)];
+        let mut outputs = [second.to_vec(), second.to_vec()];
+        for phaser in phasers.iter_mut() {
+            phaser.initialize(PhaserParameters::new(0.5, 1.0, 4, 0.3, 0.5));
+            phaser.process(0, &mut first.to_vec());
+        }
+        let [fixed, changed] = &mut phasers;
+        let [fixed_output, changed_output] = &mut outputs;
+        fixed.process(512, fixed_output);
+        assert_no_alloc(|| {
+            changed.set_parameters(PhaserParameters::new(0.5, 1.0, 12, 0.3, 0.5));
+            changed.process(512, changed_output);
+        });
+        assert!(changed_output.iter().all(|sample| sample.is_finite()));
+        let difference = fixed_output
+            .iter()
+            .zip(changed_output.iter())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max);
+        assert!(difference > 0.1, "{}", difference);
+    }
+}
@@ -0,0 +1,169 @@
+//! Repeats the input after a fixed number of frames, bouncing the
+//! repetitions between the channels.
+
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::dsp::denormal::flush;
+
+/// The largest feedback amount accepted by [`PingPongParameters`].
+pub const MAX_FEEDBACK: f32 = 0.99;
+
+/// The parameters consumed by [`PingPongDelay`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PingPongParameters {
+    /// The number of frames between each echo and the next, which then
+    /// lands in the opposite channel.
+    pub delay_samples: usize,
+    /// Determines how much of each echo is fed across into the other
+    /// channel, clamped to `0.0..=MAX_FEEDBACK` to avoid runaway.
+    pub feedback: f32,
+    /// Determines how much of the echoes are mixed with the original
+    /// audio.
+    pub mix: f32,
+}
+
+impl PingPongParameters {
+    /// Creates a new [`PingPongParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want quarter note echoes alternating in a 120 BPM track:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::pingpong::*;
+    /// let delay_samples = (60.0 / 120.0 * 44100.0) as usize;
+    /// let _ = PingPongParameters::new(delay_samples, 0.6, 0.4);
+    /// ```
+    pub fn new(delay_samples: usize, feedback: f32, mix: f32) -> Self {
+        Self {
+            delay_samples,
+            feedback: feedback.clamp(0.0, MAX_FEEDBACK),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// The ping-pong delay DSP and its internal state.
+///
+/// The input is summed to mono and fed into the left delay line, whose
+/// output is fed into the right delay line and back again, such that
+/// echoes start on the left and alternate from there.
+#[derive(Debug)]
+pub struct PingPongDelay {
+    /// The parameters for the effect.
+    parameters: Option<PingPongParameters>,
+    /// The interleaved stereo delay line.
+    line: Vec<f32>,
+    /// The frame in the delay line that is read and written next.
+    index: usize,
+}
+
+impl PingPongDelay {
+    pub fn new() -> Self {
+        Self {
+            parameters: None,
+            line: vec![],
+            index: 0,
+        }
+    }
+}
+
+impl Default for PingPongDelay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PingPongDelay {
+    /// Initializes the [`PingPongDelay`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: PingPongParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`PingPongDelay`] i.e. turning it off, freeing
+    /// both delay lines.
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.line = vec![];
+        self.index = 0;
+    }
+}
+
+impl Effect for PingPongDelay {
+    type Parameters = PingPongParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        if parameters.delay_samples == 0 {
+            return;
+        }
+        let feedback = parameters.feedback.clamp(0.0, MAX_FEEDBACK);
+        for frame in buffer.chunks_exact_mut(2) {
+            let slot = self.index * 2;
+            let left = self.line[slot];
+            let right = self.line[slot + 1];
+            self.line[slot] = flush((frame[0] + frame[1]) * 0.5 + right * feedback);
+            self.line[slot + 1] = flush(left * feedback);
+            frame[0] = frame[0] * (1.0 - parameters.mix) + left * parameters.mix;
+            frame[1] = frame[1] * (1.0 - parameters.mix) + right * parameters.mix;
+            self.index = (self.index + 1) % parameters.delay_samples;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.line.fill(0.0);
+        self.index = 0;
+    }
+
+    /// Replaces the parameters of the effect, reallocating and clearing
+    /// the delay lines if `delay_samples` changes.
+    fn set_parameters(&mut self, parameters: PingPongParameters) {
+        if self.line.len() != parameters.delay_samples * 2 {
+            self.line = vec![0.0; parameters.delay_samples * 2];
+            self.index = 0;
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PingPongDelay, PingPongParameters};
+    use crate::core::effect::Effect;
+
+    #[test]
+    fn echoes_alternate() {
+        let mut delay = PingPongDelay::new();
+        delay.initialize(PingPongParameters::new(100, 0.5, 1.0));
+        let mut buffer = vec![0.0; 2 * 350];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        delay.process(0, &mut buffer);
+
+        for (frame, left, right) in [(100, 1.0, 0.0), (200, 0.0, 0.5), (300, 0.25, 0.0)] {
+            assert_eq!(buffer[frame * 2], left);
+            assert_eq!(buffer[frame * 2 + 1], right);
+        }
+        let echoes = buffer.iter().filter(|&&sample| sample != 0.0).count();
+        assert_eq!(echoes, 3);
+    }
+
+    #[test]
+    fn deinitialize_clears_lines() {
+        let mut delay = PingPongDelay::new();
+        let parameters = PingPongParameters::new(100, 0.9, 1.0);
+        delay.initialize(parameters);
+        delay.process(0, &mut vec![1.0; 2 * 150]);
+        delay.deinitialize();
+        delay.initialize(parameters);
+        let mut buffer = vec![0.0; 2 * 300];
+        delay.process(0, &mut buffer);
+        assert!(buffer.iter().all(|&sample| sample == 0.0));
+    }
+}
@@ -0,0 +1,219 @@
+//! Shifts the pitch of the signal without changing its duration.
+//!
+//! # Overview
+//!
+//! The input is read back from a delay line through two taps whose
+//! delays sweep across a window at a rate set by the pitch ratio, such
+//! that the signal is resampled while the taps move. Each tap wraps
+//! around to the other end of the window once it reaches an end, so
+//! the taps are half a window apart and crossfaded along a `sin²`
+//! curve, keeping the one that is wrapping silent. At a ratio of `1.0`
+//! the taps stand still, with the audible one half a window behind the
+//! input.
+use core::f32::consts::PI;
+
+use super::Effect;
+use crate::core::dsp::DelayLine;
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The largest shift accepted by [`PitchParameters`], in either
+/// direction.
+pub const MAX_SEMITONES: f32 = 24.0;
+
+/// The length of the window swept by the taps of the [`PitchShifter`],
+/// trading smearing of transients for a lower flutter.
+pub const WINDOW_MS: f32 = 50.0;
+
+/// The parameters consumed by [`PitchShifter`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PitchParameters {
+    /// The shift in semitones, clamped to
+    /// `-MAX_SEMITONES..=MAX_SEMITONES`.
+    pub semitones: f32,
+    /// Determines how much of the shifted signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl PitchParameters {
+    /// Creates a new [`PitchParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to layer the signal with a copy an octave up:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::pitch_shift::*;
+    /// let _ = PitchParameters::new(12.0, 0.5);
+    /// ```
+    pub fn new(semitones: f32, mix: f32) -> Self {
+        Self {
+            semitones: semitones.clamp(-MAX_SEMITONES, MAX_SEMITONES),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+
+    /// The ratio between the shifted and the original frequencies.
+    pub fn ratio(&self) -> f32 {
+        2.0_f32.powf(self.semitones.clamp(-MAX_SEMITONES, MAX_SEMITONES) / 12.0)
+    }
+}
+
+/// The pitch shifter DSP and its internal state.
+#[derive(Debug)]
+pub struct PitchShifter {
+    /// The parameters for the effect.
+    parameters: Option<PitchParameters>,
+    /// The length of the window in frames.
+    window: usize,
+    /// The delay line of each channel.
+    lines: [DelayLine; 2],
+    /// The position of the first tap within the window, in `0.0..1.0`,
+    /// where the second tap is half a window ahead.
+    phase: f32,
+}
+
+impl PitchShifter {
+    /// Creates a new [`PitchShifter`], allocating the delay lines for
+    /// the window up front.
+    pub fn new(sample_rate: f64) -> Self {
+        // An even window keeps the unshifted tap on a whole frame.
+        let window = ((WINDOW_MS as f64 * 0.0005 * sample_rate) as usize).max(2) * 2;
+        let line = DelayLine::new(window + 4);
+        Self {
+            parameters: None,
+            window,
+            lines: [line.clone(), line],
+            phase: 0.0,
+        }
+    }
+}
+
+impl PitchShifter {
+    /// Initializes the [`PitchShifter`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: PitchParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`PitchShifter`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for PitchShifter {
+    type Parameters = PitchParameters;
+
+    /// The delay of the audible tap while unshifted, in frames.
+    fn latency_samples(&self) -> usize {
+        self.window / 2
+    }
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let window = self.window as f32;
+        let latency = self.latency_samples() as isize;
+        let mix = parameters.mix.clamp(0.0, 1.0);
+        // The taps move towards the input to raise the pitch.
+        let step = (1.0 - parameters.ratio()) / window;
+        for frame in buffer.chunks_exact_mut(2) {
+            let phases = [self.phase, (self.phase + 0.5).fract()];
+            for (sample, line) in frame.iter_mut().zip(self.lines.iter_mut()) {
+                line.write(*sample);
+                let wet = phases
+                    .iter()
+                    .map(|&phase| (PI * phase).sin().powi(2) * line.read_cubic(phase * window))
+                    .sum::<f32>();
+                *sample = line.tap(latency) * (1.0 - mix) + wet * mix;
+            }
+            self.phase = (self.phase + step).rem_euclid(1.0);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.lines.iter_mut().for_each(DelayLine::clear);
+        self.phase = 0.0;
+    }
+
+    fn set_parameters(&mut self, parameters: PitchParameters) {
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PitchParameters, PitchShifter};
+    use crate::core::dsp::fft::{hann, Complex, Fft};
+    use crate::core::effect::Effect;
+
+    /// Finds the frequency of the loudest bin in `samples`.
+    fn dominant_frequency(samples: &[f32], sample_rate: f32) -> f32 {
+        let fft = Fft::new(samples.len());
+        let mut window = vec![0.0; samples.len()];
+        hann(&mut window);
+        let mut spectrum: Vec<Complex<f32>> = samples
+            .iter()
+            .zip(window.iter())
+            .map(|(&x, &w)| Complex::new(x * w, 0.0))
+            .collect();
+        fft.forward(&mut spectrum);
+        let peak = (0..samples.len() / 2)
+            .max_by(|&a, &b| spectrum[a].norm().total_cmp(&spectrum[b].norm()))
+            .unwrap();
+        peak as f32 * sample_rate / samples.len() as f32
+    }
+
+    fn tone(frequency: f32, sample_rate: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|index| {
+                let x = (core::f32::consts::TAU * frequency * index as f32 / sample_rate).sin();
+                [x, x]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn octave_up_doubles_frequency() {
+        let mut shifter = PitchShifter::new(44100.0);
+        shifter.initialize(PitchParameters::new(12.0, 1.0));
+        let mut buffer = tone(440.0, 44100.0, 32768);
+        for chunk in buffer.chunks_mut(2 * 256) {
+            shifter.process(0, chunk);
+        }
+        let left: Vec<f32> = buffer.iter().step_by(2).skip(16384).copied().collect();
+        let frequency = dominant_frequency(&left, 44100.0);
+        assert!((frequency - 880.0).abs() < 5.0, "{}", frequency);
+    }
+
+    #[test]
+    fn octave_down_halves_frequency() {
+        let mut shifter = PitchShifter::new(44100.0);
+        shifter.initialize(PitchParameters::new(-12.0, 1.0));
+        let mut buffer = tone(880.0, 44100.0, 32768);
+        shifter.process(0, &mut buffer);
+        let left: Vec<f32> = buffer.iter().step_by(2).skip(16384).copied().collect();
+        let frequency = dominant_frequency(&left, 44100.0);
+        assert!((frequency - 440.0).abs() < 5.0, "{}", frequency);
+    }
+
+    #[test]
+    fn unshifted_is_transparent() {
+        let mut shifter = PitchShifter::new(44100.0);
+        shifter.initialize(PitchParameters::new(0.0, 1.0));
+        let latency = shifter.latency_samples();
+        let input: Vec<f32> = (0..2 * 4096)
+            .map(|index| (index as f32 * 0.07).sin())
+            .collect();
+        let mut buffer = input.clone();
+        shifter.process(0, &mut buffer);
+        assert!(buffer[..latency * 2].iter().all(|&x| x == 0.0));
+        assert_eq!(buffer[latency * 2..], input[..input.len() - latency * 2]);
+    }
+}
@@ -15,10 +15,11 @@
 //!    +---+---+---+---+
 //!        retrigger
 //! ```
-use std::sync::Arc;
+use alloc::{sync::Arc, vec::Vec};
 
 /// The parameters consumed by [`Retrigger`].
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RetriggerParameters {
     /// The starting index of the repetition.
     pub repeat_start: usize,
@@ -26,9 +27,9 @@ pub struct RetriggerParameters {
     pub repeat_end: usize,
     /// The threshold for fading between repetitions.
     ///
-    /// By default, this is set to 441 or about 10ms in 441000 Hz. If
-    /// the total duration of the samples being repeated is smaller,
-    /// then the 1/4th and 3/4th points are used.
+    /// By default, this is set to about 10ms, or 441 samples in 44100
+    /// Hz. If the total duration of the samples being repeated is
+    /// smaller, then the 1/4th and 3/4th points are used.
     pub fade_threshold: usize,
     /// Determines how much of the repeated samples is mixed with the
     /// original audio.
@@ -49,12 +50,17 @@ impl RetriggerParameters {
     /// ```rust
     /// # use photon::core::effect::retrigger::*;
     /// let repeat_duration = 60.0 / 256.0 * 4.0 / 16.0;
-    /// let _ = RetriggerParameters::new(0, repeat_duration, 0.8);
+    /// let _ = RetriggerParameters::new(0, repeat_duration, 0.8, 44100.0);
     /// ```
-    pub fn new(repeat_start: usize, repeat_duration: f64, mix_factor: f32) -> Self {
-        let repeat_samples = (repeat_duration * 44100.0) as usize;
-        let repeat_end = repeat_start + repeat_samples as usize;
-        let fade_threshold = (repeat_samples as usize / 4).min(441);
+    pub fn new(
+        repeat_start: usize,
+        repeat_duration: f64,
+        mix_factor: f32,
+        sample_rate: f64,
+    ) -> Self {
+        let repeat_samples = (repeat_duration * sample_rate) as usize;
+        let repeat_end = repeat_start + repeat_samples;
+        let fade_threshold = (repeat_samples / 4).min((sample_rate * 0.01) as usize);
         let mix_factor = mix_factor.clamp(0.0, 1.0);
         Self {
             repeat_start,
@@ -0,0 +1,311 @@
+//! Simulates a room with the Freeverb network of combs and allpasses.
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::dsp::denormal::flush;
+
+/// The comb lengths at 44.1 kHz, as tuned by Freeverb.
+const COMB_LENGTHS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+
+/// The allpass lengths at 44.1 kHz, as tuned by Freeverb.
+const ALLPASS_LENGTHS: [usize; 4] = [556, 441, 341, 225];
+
+/// The extra length of the right channel buffers at 44.1 kHz, which
+/// decorrelates it from the left channel.
+const STEREO_SPREAD: usize = 23;
+
+/// The sample rate that the lengths above are tuned for.
+const TUNING_SAMPLE_RATE: f64 = 44100.0;
+
+/// The attenuation of the input into the combs, keeping their sum in
+/// range.
+const INPUT_GAIN: f32 = 0.015;
+
+/// The gain of the wet signal, compensating for [`INPUT_GAIN`].
+const WET_GAIN: f32 = 3.0;
+
+/// The comb feedback at a `room_size` of `0.0`.
+const ROOM_OFFSET: f32 = 0.7;
+
+/// The comb feedback added by a `room_size` of `1.0`, keeping the
+/// largest feedback at `0.98`.
+const ROOM_SCALE: f32 = 0.28;
+
+/// The damping coefficient at a `damping` of `1.0`.
+const DAMPING_SCALE: f32 = 0.4;
+
+/// The feedback of the allpass filters.
+const ALLPASS_FEEDBACK: f32 = 0.5;
+
+/// The parameters consumed by [`Reverb`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReverbParameters {
+    /// The size of the simulated room, clamped to `0.0..=1.0`, where
+    /// larger rooms have longer tails.
+    pub room_size: f32,
+    /// How quickly the high frequencies decay, clamped to `0.0..=1.0`.
+    pub damping: f32,
+    /// The stereo width of the tail, clamped to `0.0..=1.0`.
+    pub width: f32,
+    /// Determines how much of the reverberated signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl ReverbParameters {
+    /// Creates a new [`ReverbParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a medium-sized, fairly dark room:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::reverb::*;
+    /// let _ = ReverbParameters::new(0.5, 0.5, 1.0, 0.3);
+    /// ```
+    pub fn new(room_size: f32, damping: f32, width: f32, mix: f32) -> Self {
+        Self {
+            room_size: room_size.clamp(0.0, 1.0),
+            damping: damping.clamp(0.0, 1.0),
+            width: width.clamp(0.0, 1.0),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// A feedback comb with a low-pass in its feedback path.
+#[derive(Debug)]
+struct Comb {
+    buffer: Vec<f32>,
+    index: usize,
+    filter_state: f32,
+}
+
+impl Comb {
+    fn new(len: usize) -> Self {
+        Self {
+            buffer: vec![0.0; len],
+            index: 0,
+            filter_state: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f32, feedback: f32, damping: f32) -> f32 {
+        let y = self.buffer[self.index];
+        self.filter_state = flush(y * (1.0 - damping) + self.filter_state * damping);
+        self.buffer[self.index] = flush(x + self.filter_state * feedback);
+        self.index = (self.index + 1) % self.buffer.len();
+        y
+    }
+
+    fn clear(&mut self) {
+        self.buffer.fill(0.0);
+        self.filter_state = 0.0;
+    }
+}
+
+/// A Schroeder allpass as approximated by Freeverb.
+#[derive(Debug)]
+struct Allpass {
+    buffer: Vec<f32>,
+    index: usize,
+}
+
+impl Allpass {
+    fn new(len: usize) -> Self {
+        Self {
+            buffer: vec![0.0; len],
+            index: 0,
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let delayed = self.buffer[self.index];
+        self.buffer[self.index] = flush(x + delayed * ALLPASS_FEEDBACK);
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - x
+    }
+
+    fn clear(&mut self) {
+        self.buffer.fill(0.0);
+    }
+}
+
+/// The combs and allpasses of a single channel.
+#[derive(Debug)]
+struct Tank {
+    combs: Vec<Comb>,
+    allpasses: Vec<Allpass>,
+}
+
+impl Tank {
+    fn new(sample_rate: f64, spread: usize) -> Self {
+        let scale = |len: usize| {
+            let scaled = ((len + spread) as f64 * sample_rate / TUNING_SAMPLE_RATE) as usize;
+            scaled.max(1)
+        };
+        Self {
+            combs: COMB_LENGTHS
+                .iter()
+                .map(|&len| Comb::new(scale(len)))
+                .collect(),
+            allpasses: ALLPASS_LENGTHS
+                .iter()
+                .map(|&len| Allpass::new(scale(len)))
+                .collect(),
+        }
+    }
+
+    fn process(&mut self, x: f32, feedback: f32, damping: f32) -> f32 {
+        let mut y = self
+            .combs
+            .iter_mut()
+            .map(|comb| comb.process(x, feedback, damping))
+            .sum();
+        for allpass in self.allpasses.iter_mut() {
+            y = allpass.process(y);
+        }
+        y
+    }
+
+    fn clear(&mut self) {
+        self.combs.iter_mut().for_each(Comb::clear);
+        self.allpasses.iter_mut().for_each(Allpass::clear);
+    }
+}
+
+/// The reverb DSP and its internal state.
+#[derive(Debug)]
+pub struct Reverb {
+    /// The parameters for the effect.
+    parameters: Option<ReverbParameters>,
+    /// The network of each channel.
+    tanks: [Tank; 2],
+}
+
+impl Reverb {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            parameters: None,
+            tanks: [
+                Tank::new(sample_rate, 0),
+                Tank::new(sample_rate, STEREO_SPREAD),
+            ],
+        }
+    }
+}
+
+impl Reverb {
+    /// Initializes the [`Reverb`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: ReverbParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Reverb`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Reverb {
+    type Parameters = ReverbParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let feedback = parameters.room_size.clamp(0.0, 1.0) * ROOM_SCALE + ROOM_OFFSET;
+        let damping = parameters.damping.clamp(0.0, 1.0) * DAMPING_SCALE;
+        let width = parameters.width.clamp(0.0, 1.0);
+        let wet = WET_GAIN * parameters.mix;
+        let (wet_direct, wet_cross) = (wet * (0.5 + width * 0.5), wet * (0.5 - width * 0.5));
+        for frame in buffer.chunks_exact_mut(2) {
+            let input = (frame[0] + frame[1]) * INPUT_GAIN;
+            let left = self.tanks[0].process(input, feedback, damping);
+            let right = self.tanks[1].process(input, feedback, damping);
+            let dry = 1.0 - parameters.mix;
+            frame[0] = frame[0] * dry + left * wet_direct + right * wet_cross;
+            frame[1] = frame[1] * dry + right * wet_direct + left * wet_cross;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.tanks.iter_mut().for_each(Tank::clear);
+    }
+
+    fn set_parameters(&mut self, parameters: ReverbParameters) {
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Reverb, ReverbParameters, COMB_LENGTHS, ROOM_OFFSET, ROOM_SCALE};
+    use crate::core::effect::Effect;
+
+    /// Computes the energy of each consecutive window of `len` frames.
+    fn energies(buffer: &[f32], len: usize) -> Vec<f64> {
+        buffer
+            .chunks(len * 2)
+            .map(|window| window.iter().map(|&x| (x as f64).powi(2)).sum())
+            .collect()
+    }
+
+    #[test]
+    fn tail_decays() {
+        let room_size = 0.5;
+        let mut reverb = Reverb::new(44100.0);
+        reverb.initialize(ReverbParameters::new(room_size, 0.0, 1.0, 1.0));
+        let mut buffer = vec![0.0; 44100 * 2 * 4];
+        buffer[0] = 1.0;
+        buffer[1] = 1.0;
+        reverb.process(0, &mut buffer);
+
+        // The longest comb decays the slowest, taking 60 dB to decay
+        // by its feedback for each pass around its buffer.
+        let feedback = (room_size * ROOM_SCALE + ROOM_OFFSET) as f64;
+        let passes = 3.0 / -feedback.log10();
+        let decay_seconds = passes * COMB_LENGTHS[7] as f64 / 44100.0;
+
+        // Measure in 100ms windows, skipping the build-up of echoes.
+        let windows = energies(&buffer, 4410);
+        let peak = windows.iter().cloned().fold(0.0, f64::max);
+        for pair in windows[2..].windows(2) {
+            assert!(pair[1] < pair[0]);
+        }
+        let decayed = (decay_seconds * 10.0) as usize;
+        let db = |energy: f64| 10.0 * (energy / peak).log10();
+        assert!(
+            db(windows[decayed / 3]) > -40.0,
+            "{}",
+            db(windows[decayed / 3])
+        );
+        assert!(db(windows[decayed]) < -50.0, "{}", db(windows[decayed]));
+    }
+
+    #[test]
+    fn dry_is_transparent() {
+        let mut reverb = Reverb::new(44100.0);
+        reverb.initialize(ReverbParameters::new(0.8, 0.5, 1.0, 0.0));
+        let input: Vec<f32> = (0..4096).map(|index| (index as f32 * 0.01).sin()).collect();
+        let mut buffer = input.clone();
+        reverb.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+    }
+
+    #[test]
+    fn largest_room_is_stable() {
+        let mut reverb = Reverb::new(44100.0);
+        reverb.initialize(ReverbParameters::new(1.0, 0.0, 1.0, 1.0));
+        let mut buffer = vec![0.0; 44100 * 2 * 10];
+        buffer[0] = 1.0;
+        reverb.process(0, &mut buffer);
+        let peak = |buffer: &[f32]| buffer.iter().fold(0.0_f32, |a, x| a.max(x.abs()));
+        let tail = &buffer[buffer.len() - 4410 * 2..];
+        assert!(peak(tail) < peak(&buffer) * 0.1);
+    }
+}
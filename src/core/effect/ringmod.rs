@@ -0,0 +1,155 @@
+//! Multiplies the signal with a sine carrier for metallic sidebands.
+use super::Effect;
+use crate::core::dsp::{Lfo, Waveform};
+
+/// The highest carrier accepted by [`RingMod`] as a fraction of the
+/// sample rate, keeping the carrier itself clear of Nyquist.
+///
+/// Sidebands at the sum of the input and carrier frequencies may still
+/// fold back once they exceed Nyquist.
+pub const MAX_FREQUENCY_RATIO: f64 = 0.45;
+
+/// The parameters consumed by [`RingMod`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RingModParameters {
+    /// The frequency of the carrier in Hz, limited by the effect to
+    /// [`MAX_FREQUENCY_RATIO`] of the sample rate.
+    pub frequency_hz: f32,
+    /// Determines how much of the modulated signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl RingModParameters {
+    /// Creates a new [`RingModParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a bell-like, fully modulated signal:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::ringmod::*;
+    /// let _ = RingModParameters::new(440.0, 1.0);
+    /// ```
+    pub fn new(frequency_hz: f32, mix: f32) -> Self {
+        Self {
+            frequency_hz: frequency_hz.max(0.0),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// The ring modulator DSP and its internal state.
+#[derive(Debug)]
+pub struct RingMod {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<RingModParameters>,
+    /// The carrier, running at audio rate.
+    carrier: Lfo,
+}
+
+impl RingMod {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            parameters: None,
+            carrier: Lfo::new(Waveform::Sine, 0.0, sample_rate),
+        }
+    }
+}
+
+impl RingMod {
+    /// Initializes the [`RingMod`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: RingModParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`RingMod`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for RingMod {
+    type Parameters = RingModParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let mix = parameters.mix.clamp(0.0, 1.0);
+        for frame in buffer.chunks_exact_mut(2) {
+            let carrier = self.carrier.next();
+            for sample in frame.iter_mut() {
+                *sample = *sample * (1.0 - mix) + *sample * carrier * mix;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.carrier.reset();
+    }
+
+    /// Replaces the parameters of the effect, limiting the carrier to
+    /// [`MAX_FREQUENCY_RATIO`] of the sample rate.
+    fn set_parameters(&mut self, parameters: RingModParameters) {
+        let frequency =
+            (parameters.frequency_hz.max(0.0) as f64).min(self.sample_rate * MAX_FREQUENCY_RATIO);
+        self.carrier.set_frequency(frequency);
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{RingMod, RingModParameters};
+    use crate::core::effect::Effect;
+
+    /// Measures the magnitude of a `frequency` in the left channel.
+    fn goertzel(buffer: &[f32], frequency: f64, sample_rate: f64) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (index, frame) in buffer.chunks_exact(2).enumerate() {
+            let phase = TAU * frequency * index as f64 / sample_rate;
+            re += frame[0] as f64 * phase.cos();
+            im -= frame[0] as f64 * phase.sin();
+        }
+        re.hypot(im) / (buffer.len() / 2) as f64
+    }
+
+    #[test]
+    fn sidebands_replace_input() {
+        let sample_rate = 48000.0;
+        let mut buffer: Vec<f32> = (0..48000)
+            .flat_map(|index| {
+                let x = (TAU * 1000.0 * index as f64 / sample_rate).sin() as f32;
+                [x, x]
+            })
+            .collect();
+        let mut ringmod = RingMod::new(sample_rate);
+        ringmod.initialize(RingModParameters::new(300.0, 1.0));
+        ringmod.process(0, &mut buffer);
+        assert!(goertzel(&buffer, 700.0, sample_rate) > 0.2);
+        assert!(goertzel(&buffer, 1300.0, sample_rate) > 0.2);
+        assert!(goertzel(&buffer, 1000.0, sample_rate) < 0.01);
+    }
+
+    #[test]
+    fn carrier_stays_below_nyquist() {
+        let mut ringmod = RingMod::new(1000.0);
+        ringmod.initialize(RingModParameters::new(5000.0, 1.0));
+        let mut buffer = vec![1.0; 200];
+        ringmod.process(0, &mut buffer);
+        // A carrier at Nyquist or above would collapse to a constant.
+        let distinct = buffer.windows(2).filter(|pair| pair[0] != pair[1]).count();
+        assert!(distinct > 0);
+        assert!(buffer.iter().all(|sample| sample.abs() <= 1.0));
+    }
+}
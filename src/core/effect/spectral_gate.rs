@@ -0,0 +1,356 @@
+//! Removes low-level noise by silencing the quiet parts of the spectrum.
+//!
+//! # Overview
+//!
+//! The input is split into overlapping frames of `fft_size` frames,
+//! each windowed and transformed. Bins whose magnitude falls below the
+//! threshold are zeroed, then each frame is transformed back, windowed
+//! again, and overlap-added into the output. The analysis and synthesis
+//! windows are both the square root of a Hann window, such that they
+//! multiply into a Hann window that overlaps to a constant and leaves
+//! an untouched spectrum transparent. The output lags the input by
+//! exactly one frame.
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::dsp::{
+    decibel::db_to_gain,
+    fft::{hann, Complex, Fft},
+};
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The smallest frame accepted by [`SpectralGateParameters`].
+pub const MIN_FFT_SIZE: usize = 64;
+
+/// The largest frame accepted by [`SpectralGateParameters`].
+pub const MAX_FFT_SIZE: usize = 16384;
+
+/// The largest number of overlapping frames accepted by
+/// [`SpectralGateParameters`].
+pub const MAX_OVERLAP: usize = 8;
+
+/// The parameters consumed by [`SpectralGate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpectralGateParameters {
+    /// The magnitude below which a bin is silenced, in dB relative to a
+    /// full scale sine.
+    pub threshold_db: f32,
+    /// The number of frames transformed at a time, rounded up to a power
+    /// of two within `MIN_FFT_SIZE..=MAX_FFT_SIZE`.
+    ///
+    /// Larger sizes resolve the spectrum more finely at the cost of
+    /// latency and smearing in time.
+    pub fft_size: usize,
+    /// The number of frames overlapping at any time, rounded up to a
+    /// power of two within `2..=MAX_OVERLAP`.
+    pub overlap: usize,
+}
+
+impl SpectralGateParameters {
+    /// Creates a new [`SpectralGateParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to remove hiss below -60 dB, at about 46ms of latency
+    /// at 44.1 kHz:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::spectral_gate::*;
+    /// let _ = SpectralGateParameters::new(-60.0, 2048, 4);
+    /// ```
+    pub fn new(threshold_db: f32, fft_size: usize, overlap: usize) -> Self {
+        Self {
+            threshold_db,
+            fft_size,
+            overlap,
+        }
+        .sanitized()
+    }
+
+    /// Rounds the sizes up to the nearest supported ones, e.g. after
+    /// loading them from a preset.
+    pub fn sanitized(self) -> Self {
+        Self {
+            fft_size: self
+                .fft_size
+                .clamp(MIN_FFT_SIZE, MAX_FFT_SIZE)
+                .next_power_of_two(),
+            overlap: self.overlap.clamp(2, MAX_OVERLAP).next_power_of_two(),
+            ..self
+        }
+    }
+
+    /// The number of frames between the starts of consecutive frames.
+    pub fn hop_size(&self) -> usize {
+        self.fft_size / self.overlap
+    }
+}
+
+/// The per-channel state of the [`SpectralGate`].
+#[derive(Debug, Clone)]
+struct Channel {
+    /// The most recent frame of input, oldest first.
+    input: Vec<f32>,
+    /// The overlap-added output, starting at the next frame to play.
+    output: Vec<f32>,
+}
+
+impl Channel {
+    fn new(fft_size: usize) -> Self {
+        Self {
+            input: vec![0.0; fft_size],
+            output: vec![0.0; fft_size],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.input.fill(0.0);
+        self.output.fill(0.0);
+    }
+}
+
+/// The spectral gate DSP and its internal state.
+#[derive(Debug)]
+pub struct SpectralGate {
+    /// The parameters for the effect, sanitized.
+    parameters: Option<SpectralGateParameters>,
+    /// The transform of a frame.
+    fft: Fft,
+    /// The analysis and synthesis window.
+    window: Vec<f32>,
+    /// The sum of the `window`, computed once whenever it is rebuilt.
+    window_sum: f32,
+    /// The state of each channel.
+    channels: [Channel; 2],
+    /// The position within the current hop.
+    index: usize,
+    /// The work buffer for the spectrum of a frame.
+    scratch: Vec<Complex<f32>>,
+}
+
+impl SpectralGate {
+    pub fn new() -> Self {
+        Self {
+            parameters: None,
+            fft: Fft::new(1),
+            window: vec![],
+            window_sum: 0.0,
+            channels: [Channel::new(0), Channel::new(0)],
+            index: 0,
+            scratch: vec![],
+        }
+    }
+}
+
+impl Default for SpectralGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpectralGate {
+    /// Initializes the [`SpectralGate`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: SpectralGateParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`SpectralGate`] i.e. turning it off, freeing
+    /// the frames.
+    pub fn deinitialize(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Gates the most recent frame of a `channel` and overlap-adds it
+    /// into its output.
+    fn process_frame(&mut self, channel: usize, threshold: f32, scale: f32, hop: usize) {
+        let channel = &mut self.channels[channel];
+        for ((bin, &x), &w) in self
+            .scratch
+            .iter_mut()
+            .zip(channel.input.iter())
+            .zip(self.window.iter())
+        {
+            *bin = Complex::new(x * w, 0.0);
+        }
+        self.fft.forward(&mut self.scratch);
+        for bin in self.scratch.iter_mut() {
+            if bin.norm() < threshold {
+                *bin = Complex::ZERO;
+            }
+        }
+        self.fft.inverse(&mut self.scratch);
+
+        channel.output.copy_within(hop.., 0);
+        let len = channel.output.len();
+        channel.output[len - hop..].fill(0.0);
+        for ((y, bin), &w) in channel
+            .output
+            .iter_mut()
+            .zip(self.scratch.iter())
+            .zip(self.window.iter())
+        {
+            *y += bin.re * w * scale;
+        }
+        channel.input.copy_within(hop.., 0);
+    }
+}
+
+impl Effect for SpectralGate {
+    type Parameters = SpectralGateParameters;
+
+    /// The delay introduced by the frame processing, in frames.
+    fn latency_samples(&self) -> usize {
+        self.parameters
+            .map(|parameters| parameters.fft_size)
+            .unwrap_or(0)
+    }
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let size = parameters.fft_size;
+        let hop = parameters.hop_size();
+        // The product of the windows is a Hann window, whose overlapping
+        // copies sum to half the overlap.
+        let scale = 2.0 / parameters.overlap as f32;
+        // A full scale sine peaks at half the sum of the window.
+        let threshold = db_to_gain(parameters.threshold_db) * self.window_sum / 2.0;
+        for frame in buffer.chunks_exact_mut(2) {
+            for (sample, channel) in frame.iter_mut().zip(self.channels.iter_mut()) {
+                channel.input[size - hop + self.index] = *sample;
+                *sample = channel.output[self.index];
+            }
+            self.index += 1;
+            if self.index == hop {
+                for channel in 0..2 {
+                    self.process_frame(channel, threshold, scale, hop);
+                }
+                self.index = 0;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.channels.iter_mut().for_each(Channel::clear);
+        self.index = 0;
+    }
+
+    /// Replaces the parameters of the effect, reallocating and clearing
+    /// the frames if the `fft_size` or `overlap` change.
+    fn set_parameters(&mut self, parameters: SpectralGateParameters) {
+        let parameters = parameters.sanitized();
+        let resized = self
+            .parameters
+            .map(|previous| (previous.fft_size, previous.overlap))
+            != Some((parameters.fft_size, parameters.overlap));
+        if resized {
+            let size = parameters.fft_size;
+            self.fft = Fft::new(size);
+            self.window = vec![0.0; size];
+            hann(&mut self.window);
+            self.window.iter_mut().for_each(|w| *w = w.sqrt());
+            self.window_sum = self.window.iter().sum();
+            self.channels = [Channel::new(size), Channel::new(size)];
+            self.index = 0;
+            self.scratch = vec![Complex::ZERO; size];
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SpectralGate, SpectralGateParameters};
+    use crate::core::effect::Effect;
+
+    /// A deterministic white noise in `-1.0..1.0`.
+    fn noise(len: usize) -> Vec<f32> {
+        let mut state = 0x1234_5678_u32;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 8) as f32 / (1 << 23) as f32 - 1.0
+            })
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn passes_through_above_threshold() {
+        let mut gate = SpectralGate::new();
+        gate.initialize(SpectralGateParameters::new(-200.0, 512, 4));
+        let latency = gate.latency_samples();
+        assert_eq!(latency, 512);
+
+        let input: Vec<f32> = (0..4096)
+            .flat_map(|index| {
+                let x = (index as f32 * 0.05).sin() * 0.5;
+                [x, -x]
+            })
+            .collect();
+        let mut buffer = input.clone();
+        for chunk in buffer.chunks_mut(2 * 100) {
+            gate.process(0, chunk);
+        }
+        assert!(buffer[..latency * 2].iter().all(|x| x.abs() < 1e-6));
+        // The first frames fade in along with the window.
+        let settled = 2 * 512;
+        for (y, x) in buffer[latency * 2 + settled..]
+            .iter()
+            .zip(input[settled..].iter())
+        {
+            assert!((y - x).abs() < 1e-4, "{} {}", y, x);
+        }
+    }
+
+    #[test]
+    fn noise_floor_drops_between_tones() {
+        let mut gate = SpectralGate::new();
+        gate.initialize(SpectralGateParameters::new(-40.0, 1024, 4));
+        let latency = gate.latency_samples();
+
+        // Bursts of a tone every other 8192 frames, over quiet noise.
+        let noise = noise(8192 * 4);
+        let input: Vec<f32> = noise
+            .iter()
+            .enumerate()
+            .map(|(index, &noise)| {
+                let tone = if index / 8192 % 2 == 0 {
+                    (index as f32 * 0.1).sin() * 0.5
+                } else {
+                    0.0
+                };
+                tone + noise * 0.001
+            })
+            .collect();
+        let mut buffer: Vec<f32> = input.iter().flat_map(|&x| [x, x]).collect();
+        gate.process(0, &mut buffer);
+        let output: Vec<f32> = buffer.iter().step_by(2).copied().collect();
+
+        let gap = 8192 + 2048..8192 * 2 - 2048;
+        let before = rms(&input[gap.clone()]);
+        let after = rms(&output[gap.start + latency..gap.end + latency]);
+        assert!(after < before * 0.01, "{} {}", after, before);
+
+        let tone = 8192 * 2 + 2048..8192 * 3 - 2048;
+        let before = rms(&input[tone.clone()]);
+        let after = rms(&output[tone.start + latency..tone.end + latency]);
+        assert!((after / before - 1.0).abs() < 0.01, "{} {}", after, before);
+    }
+
+    #[test]
+    fn sizes_are_sanitized() {
+        let parameters = SpectralGateParameters::new(-60.0, 1000, 3);
+        assert_eq!(parameters.fft_size, 1024);
+        assert_eq!(parameters.overlap, 4);
+        assert_eq!(parameters.hop_size(), 256);
+    }
+}
@@ -0,0 +1,301 @@
+//! Captures a slice of the incoming audio and repeats it on demand.
+//!
+//! # Overview
+//!
+//! Once triggered, this audio effect transforms the track from:
+//! ```text
+//! A B C D E F G H I J K L
+//! ```
+//! into:
+//! ```text
+//! trigger
+//!    v
+//! A B C D C D C D C D K L
+//!    |   |   |   |
+//!    +---+---+---+
+//!  capture  repeats
+//! ```
+use alloc::{vec, vec::Vec};
+
+use super::Effect;
+use crate::core::tempo::{note_duration_secs, NoteValue};
+
+/// The largest number of frames faded at each edge of a repeated slice.
+pub const FADE_SAMPLES: usize = 32;
+
+/// The parameters consumed by [`Stutter`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StutterParameters {
+    /// The number of frames captured and repeated.
+    pub slice_samples: usize,
+    /// The number of times the slice is repeated after being captured,
+    /// at least `1`.
+    pub repeats: usize,
+    /// Determines how much of the repeated slice is mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl StutterParameters {
+    /// Creates a new [`StutterParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to repeat a 50ms slice three times:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::stutter::*;
+    /// let _ = StutterParameters::new(2205, 3, 1.0);
+    /// ```
+    pub fn new(slice_samples: usize, repeats: usize, mix: f32) -> Self {
+        Self {
+            slice_samples,
+            repeats: repeats.max(1),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Creates a new [`StutterParameters`] whose slice lasts `division`
+    /// at the given `bpm`.
+    ///
+    /// # Example
+    ///
+    /// If you want to repeat a 16th note four times at 128 BPM:
+    ///
+    /// ```rust
+    /// # use photon::core::{effect::stutter::*, tempo::NoteValue};
+    /// let _ = StutterParameters::synced(128.0, NoteValue::Sixteenth, 4, 1.0, 44100.0);
+    /// ```
+    pub fn synced(
+        bpm: f64,
+        division: NoteValue,
+        repeats: usize,
+        mix: f32,
+        sample_rate: f64,
+    ) -> Self {
+        let slice_samples = (note_duration_secs(bpm, division) * sample_rate) as usize;
+        Self::new(slice_samples, repeats, mix)
+    }
+
+    /// Compute the window applied to the slice at an `index`, fading the
+    /// edges such that each repetition blends in and out of the input.
+    pub fn window(&self, index: usize) -> f32 {
+        let fade = FADE_SAMPLES.min(self.slice_samples / 4);
+        if fade == 0 {
+            return 1.0;
+        }
+        let rising = (index + 1) as f32 / fade as f32;
+        let falling = self.slice_samples.saturating_sub(index) as f32 / fade as f32;
+        rising.min(falling).min(1.0)
+    }
+}
+
+/// The progress of the [`Stutter`] through a triggered repeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Passing the input through.
+    Idle,
+    /// Recording the next `slice_samples` frames, having written the
+    /// given number of frames.
+    Capturing(usize),
+    /// Playing back the slice from an `index`, with `remaining`
+    /// repetitions including the current one.
+    Repeating { index: usize, remaining: usize },
+}
+
+/// The stutter DSP and its internal state.
+#[derive(Debug)]
+pub struct Stutter {
+    /// The parameters for the effect.
+    parameters: Option<StutterParameters>,
+    /// The interleaved stereo slice being captured or repeated.
+    slice: Vec<f32>,
+    /// Whether a capture starts at the next opportunity.
+    armed: bool,
+    /// The progress through a triggered repeat.
+    state: State,
+}
+
+impl Stutter {
+    pub fn new() -> Self {
+        Self {
+            parameters: None,
+            slice: vec![],
+            armed: false,
+            state: State::Idle,
+        }
+    }
+
+    /// Arms the [`Stutter`] such that it captures the next slice and
+    /// repeats it.
+    ///
+    /// While a slice is being repeated, the capture waits for the end
+    /// of the current repetition such that the slice fades out first.
+    pub fn trigger(&mut self) {
+        self.armed = true;
+    }
+
+    /// Whether a slice is being captured or repeated.
+    pub fn is_active(&self) -> bool {
+        self.state != State::Idle
+    }
+}
+
+impl Default for Stutter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stutter {
+    /// Initializes the [`Stutter`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: StutterParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Stutter`] i.e. turning it off, freeing the
+    /// slice and returning to pass-through.
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.slice = vec![];
+        self.armed = false;
+        self.state = State::Idle;
+    }
+}
+
+impl Effect for Stutter {
+    type Parameters = StutterParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        if parameters.slice_samples == 0 {
+            return;
+        }
+        for frame in buffer.chunks_exact_mut(2) {
+            if self.state == State::Idle && self.armed {
+                self.armed = false;
+                self.state = State::Capturing(0);
+            }
+            self.state = match self.state {
+                State::Idle => State::Idle,
+                State::Capturing(written) => {
+                    self.slice[written * 2..written * 2 + 2].copy_from_slice(frame);
+                    if written + 1 < parameters.slice_samples {
+                        State::Capturing(written + 1)
+                    } else {
+                        State::Repeating {
+                            index: 0,
+                            remaining: parameters.repeats.max(1),
+                        }
+                    }
+                }
+                State::Repeating { index, remaining } => {
+                    let wet = parameters.window(index) * parameters.mix;
+                    for (channel, sample) in frame.iter_mut().enumerate() {
+                        let repeated = self.slice[index * 2 + channel];
+                        *sample = *sample * (1.0 - wet) + repeated * wet;
+                    }
+                    if index + 1 < parameters.slice_samples {
+                        State::Repeating {
+                            index: index + 1,
+                            remaining,
+                        }
+                    } else if remaining > 1 && !self.armed {
+                        State::Repeating {
+                            index: 0,
+                            remaining: remaining - 1,
+                        }
+                    } else {
+                        State::Idle
+                    }
+                }
+            };
+        }
+    }
+
+    fn reset(&mut self) {
+        self.slice.fill(0.0);
+        self.armed = false;
+        self.state = State::Idle;
+    }
+
+    /// Replaces the parameters of the effect, reallocating the slice and
+    /// cancelling any repeat if `slice_samples` changes.
+    fn set_parameters(&mut self, parameters: StutterParameters) {
+        if self.slice.len() != parameters.slice_samples * 2 {
+            self.slice = vec![0.0; parameters.slice_samples * 2];
+            self.state = State::Idle;
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stutter, StutterParameters, FADE_SAMPLES};
+    use crate::core::effect::Effect;
+
+    fn ramp(frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|index| [index as f32, -(index as f32)])
+            .collect()
+    }
+
+    #[test]
+    fn slice_repeats_verbatim() {
+        let mut stutter = Stutter::new();
+        stutter.initialize(StutterParameters::new(200, 3, 1.0));
+        let input = ramp(1000);
+        let mut buffer = input.clone();
+        stutter.trigger();
+        stutter.process(0, &mut buffer);
+
+        // The slice plays through while being captured.
+        assert_eq!(buffer[..400], input[..400]);
+        for repeat in 1..=3 {
+            for index in FADE_SAMPLES..200 - FADE_SAMPLES {
+                let frame = repeat * 200 + index;
+                assert_eq!(buffer[frame * 2], index as f32);
+                assert_eq!(buffer[frame * 2 + 1], -(index as f32));
+            }
+        }
+        assert_eq!(buffer[1600..], input[1600..]);
+        assert!(!stutter.is_active());
+    }
+
+    #[test]
+    fn retrigger_waits_for_repeat() {
+        let mut stutter = Stutter::new();
+        stutter.initialize(StutterParameters::new(200, 3, 1.0));
+        let mut buffer = vec![1.0; 2 * 300];
+        stutter.trigger();
+        stutter.process(0, &mut buffer);
+        stutter.trigger();
+        let mut buffer = ramp(400);
+        stutter.process(0, &mut buffer);
+        // The interrupted repeat finishes before capturing the ramp.
+        assert_eq!(buffer[2 * 50], 1.0);
+        assert_eq!(buffer[2 * 100..2 * 300], ramp(400)[2 * 100..2 * 300]);
+        assert_eq!(buffer[2 * 350], 150.0);
+        assert!(stutter.is_active());
+    }
+
+    #[test]
+    fn deinitialize_passes_through() {
+        let mut stutter = Stutter::new();
+        stutter.initialize(StutterParameters::new(100, 4, 1.0));
+        stutter.trigger();
+        stutter.process(0, &mut ramp(150));
+        stutter.deinitialize();
+        let input = ramp(500);
+        let mut buffer = input.clone();
+        stutter.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+        assert!(!stutter.is_active());
+    }
+}
@@ -0,0 +1,265 @@
+//! Imitates a tape machine, saturating the signal and wobbling its
+//! pitch.
+//!
+//! # Overview
+//!
+//! The input is first driven into an asymmetric soft clipper, as tape
+//! is biased off-center, then passed through a [`DcBlocker`] to remove
+//! the offset that the asymmetry adds. The result is read back from a
+//! delay line whose delay is swept by two sines: a slow, deep one for
+//! the wow of an uneven reel and a fast, shallow one for the flutter of
+//! the capstan.
+use super::{DcBlocker, DcBlockerParameters, Effect};
+use crate::core::dsp::{DelayLine, Lfo, SmoothedValue, Smoothing, Waveform};
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The gain of the clipper at a `drive` of `1.0`.
+pub const MAX_DRIVE_GAIN: f32 = 8.0;
+
+/// The offset of the clipper before its gain, which adds even
+/// harmonics.
+pub const BIAS: f32 = 0.2;
+
+/// The largest wow depth accepted by [`TapeParameters`].
+pub const MAX_WOW_DEPTH_MS: f32 = 5.0;
+
+/// The largest flutter depth accepted by [`TapeParameters`].
+pub const MAX_FLUTTER_DEPTH_MS: f32 = 0.5;
+
+/// The frequency of the flutter.
+pub const FLUTTER_RATE_HZ: f64 = 9.0;
+
+/// The time taken for depth changes to settle.
+pub const DEPTH_SMOOTHING_MS: f32 = 20.0;
+
+/// The parameters consumed by [`Tape`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TapeParameters {
+    /// Determines how hard the signal is saturated, clamped to
+    /// `0.0..=1.0`, where `0.0` leaves it clean.
+    pub drive: f32,
+    /// The frequency of the wow in Hz.
+    pub wow_rate: f32,
+    /// The range swept by the delay for the wow, clamped to
+    /// `0.0..=MAX_WOW_DEPTH_MS`.
+    pub wow_depth: f32,
+    /// The range swept by the delay for the flutter, clamped to
+    /// `0.0..=MAX_FLUTTER_DEPTH_MS`.
+    pub flutter_depth: f32,
+}
+
+impl TapeParameters {
+    /// Creates a new [`TapeParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a warm, slightly worn cassette:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::tape::*;
+    /// let _ = TapeParameters::new(0.3, 0.8, 1.5, 0.1);
+    /// ```
+    pub fn new(drive: f32, wow_rate: f32, wow_depth: f32, flutter_depth: f32) -> Self {
+        Self {
+            drive: drive.clamp(0.0, 1.0),
+            wow_rate: wow_rate.max(0.0),
+            wow_depth: wow_depth.clamp(0.0, MAX_WOW_DEPTH_MS),
+            flutter_depth: flutter_depth.clamp(0.0, MAX_FLUTTER_DEPTH_MS),
+        }
+    }
+}
+
+/// Saturates a sample, crossfading from clean at a `drive` of `0.0` to
+/// a biased `tanh` at `1.0`.
+fn saturate(x: f32, drive: f32) -> f32 {
+    let gain = 1.0 + drive * (MAX_DRIVE_GAIN - 1.0);
+    // Subtracting the bias keeps silence silent.
+    let shaped = ((gain * x + BIAS).tanh() - BIAS.tanh()) / gain.tanh();
+    x * (1.0 - drive) + shaped * drive
+}
+
+/// The tape DSP and its internal state.
+#[derive(Debug)]
+pub struct Tape {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<TapeParameters>,
+    /// Removes the offset added by the bias of the clipper.
+    dc_blocker: DcBlocker,
+    /// The delay line of each channel.
+    lines: [DelayLine; 2],
+    /// The slow modulation of the delay.
+    wow: Lfo,
+    /// The fast modulation of the delay.
+    flutter: Lfo,
+    /// The wow depth in samples, gliding towards the parameters.
+    wow_depth: SmoothedValue,
+    /// The flutter depth in samples, gliding towards the parameters.
+    flutter_depth: SmoothedValue,
+}
+
+impl Tape {
+    /// Creates a new [`Tape`], allocating enough delay for the deepest
+    /// modulation up front.
+    pub fn new(sample_rate: f64) -> Self {
+        let len =
+            ((MAX_WOW_DEPTH_MS + MAX_FLUTTER_DEPTH_MS) as f64 * 0.001 * sample_rate) as usize + 4;
+        let line = DelayLine::new(len);
+        let mut depth = SmoothedValue::new(Smoothing::Exponential, 0.0);
+        depth.set_smoothing_time(DEPTH_SMOOTHING_MS, sample_rate);
+        let mut dc_blocker = DcBlocker::new();
+        dc_blocker.initialize(DcBlockerParameters::default());
+        Self {
+            sample_rate,
+            parameters: None,
+            dc_blocker,
+            lines: [line.clone(), line],
+            wow: Lfo::new(Waveform::Sine, 0.0, sample_rate),
+            flutter: Lfo::new(Waveform::Sine, FLUTTER_RATE_HZ, sample_rate),
+            wow_depth: depth,
+            flutter_depth: depth,
+        }
+    }
+
+    /// Converts a depth in ms to samples.
+    fn depth_samples(&self, depth_ms: f32) -> f32 {
+        depth_ms * (self.sample_rate * 0.001) as f32
+    }
+}
+
+impl Tape {
+    /// Initializes the [`Tape`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: TapeParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+        self.wow_depth
+            .set_value(self.depth_samples(parameters.wow_depth.clamp(0.0, MAX_WOW_DEPTH_MS)));
+        self.flutter_depth.set_value(
+            self.depth_samples(parameters.flutter_depth.clamp(0.0, MAX_FLUTTER_DEPTH_MS)),
+        );
+    }
+
+    /// Deinitializes the [`Tape`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Tape {
+    type Parameters = TapeParameters;
+
+    fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let drive = parameters.drive.clamp(0.0, 1.0);
+        if drive > 0.0 {
+            for sample in buffer.iter_mut() {
+                *sample = saturate(*sample, drive);
+            }
+            self.dc_blocker.process(position, buffer);
+        }
+        for frame in buffer.chunks_exact_mut(2) {
+            // A delay of zero reads the sample just written.
+            let delay = self.wow_depth.next() * 0.5 * (1.0 + self.wow.next())
+                + self.flutter_depth.next() * 0.5 * (1.0 + self.flutter.next());
+            for (sample, line) in frame.iter_mut().zip(self.lines.iter_mut()) {
+                line.write(*sample);
+                *sample = line.read_cubic(delay);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.dc_blocker.reset();
+        for line in self.lines.iter_mut() {
+            line.clear();
+        }
+        self.wow.reset();
+        self.flutter.reset();
+    }
+
+    /// Replaces the parameters of the effect, gliding towards the new
+    /// depths over [`DEPTH_SMOOTHING_MS`].
+    fn set_parameters(&mut self, parameters: TapeParameters) {
+        self.wow.set_frequency(parameters.wow_rate.max(0.0) as f64);
+        self.wow_depth
+            .set_target(self.depth_samples(parameters.wow_depth.clamp(0.0, MAX_WOW_DEPTH_MS)));
+        self.flutter_depth.set_target(
+            self.depth_samples(parameters.flutter_depth.clamp(0.0, MAX_FLUTTER_DEPTH_MS)),
+        );
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{Tape, TapeParameters};
+    use crate::core::effect::Effect;
+
+    /// Measures the magnitude of a `frequency` in the left channel.
+    fn goertzel(buffer: &[f32], frequency: f64, sample_rate: f64) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (index, frame) in buffer.chunks_exact(2).enumerate() {
+            let phase = TAU * frequency * index as f64 / sample_rate;
+            re += frame[0] as f64 * phase.cos();
+            im -= frame[0] as f64 * phase.sin();
+        }
+        re.hypot(im) / (buffer.len() / 2) as f64
+    }
+
+    fn tone(frequency: f64, amplitude: f32) -> Vec<f32> {
+        (0..44100 * 4)
+            .flat_map(|index| {
+                let x = (TAU * frequency * index as f64 / 44100.0).sin() as f32 * amplitude;
+                [x, x]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn wow_modulates_pitch() {
+        let input = tone(1000.0, 0.5);
+        let mut tape = Tape::new(44100.0);
+        tape.initialize(TapeParameters::new(0.0, 2.0, 2.0, 0.0));
+        let mut buffer = input.clone();
+        tape.process(0, &mut buffer);
+
+        let window = 44100 * 2;
+        for sideband in [998.0, 1002.0] {
+            assert!(goertzel(&input[window..], sideband, 44100.0) < 0.005);
+            assert!(goertzel(&buffer[window..], sideband, 44100.0) > 0.05);
+        }
+    }
+
+    #[test]
+    fn neutral_is_transparent() {
+        let input = tone(440.0, 0.5);
+        let mut tape = Tape::new(44100.0);
+        tape.initialize(TapeParameters::new(0.0, 1.0, 0.0, 0.0));
+        let mut buffer = input.clone();
+        tape.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+    }
+
+    #[test]
+    fn saturation_adds_no_offset() {
+        let mut tape = Tape::new(44100.0);
+        tape.initialize(TapeParameters::new(1.0, 0.0, 0.0, 0.0));
+        let mut buffer = tone(441.0, 0.8);
+        tape.process(0, &mut buffer);
+        // A window of whole cycles, after the blocker settles.
+        let window = &buffer[2 * 4000..2 * 44000];
+        let mean = window.iter().step_by(2).sum::<f32>() / 40000.0;
+        assert!(mean.abs() < 1e-3, "{}", mean);
+        let peak = window.iter().fold(0.0_f32, |peak, x| peak.max(x.abs()));
+        assert!(peak < 1.2, "{}", peak);
+    }
+}
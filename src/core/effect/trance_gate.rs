@@ -1,7 +1,110 @@
 //! Ramps the volume down and up given a duration.
+use core::{f32::consts::PI, fmt, marker::PhantomData};
+
+use super::{Effect, DEFAULT_SAMPLE_RATE, UNKNOWN_POSITION};
+use crate::core::dsp::{
+    envelope::{EnvelopeFollower, Mode},
+    Crossover, SmoothedValue, Smoothing,
+};
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+use crate::core::midi::MidiEvent;
+use crate::core::sample::Sample;
+use crate::core::tempo::{note_duration_secs, NoteValue};
+use crate::core::transport::Transport;
+
+/// The amplitude curve traced by the [`TranceGate`] over a cycle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GateShape {
+    /// Holds, then ramps down and up along the [`FadeCurve`], which is
+    /// linear by default.
+    #[default]
+    Trapezoid,
+    /// Holds, then ramps down and up along a half-cosine, avoiding
+    /// clicks at the edges of the ramps.
+    Sine,
+    /// Switches between fully open and fully closed at the midpoint.
+    Square,
+}
+
+/// The steepness of [`FadeCurve::Exponential`] and
+/// [`FadeCurve::Logarithmic`], where higher values bend them further
+/// away from a straight line.
+pub const FADE_CURVE_STEEPNESS: f32 = 4.0;
+
+/// The curve followed by each fade of the [`TranceGate`], applied to
+/// the ramps of [`GateShape::Trapezoid`] and [`GateShape::Sine`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FadeCurve {
+    /// Changes the amplitude at a constant rate.
+    #[default]
+    Linear,
+    /// Changes the amplitude slowly near silence and quickly near full
+    /// volume, which is closer to a constant rate in decibels.
+    Exponential,
+    /// Changes the amplitude quickly near silence and slowly near full
+    /// volume, mirroring [`FadeCurve::Exponential`].
+    Logarithmic,
+}
+
+impl FadeCurve {
+    /// Bends the `progress` of a fade in along the curve, keeping both
+    /// endpoints at exactly `0.0` and `1.0`.
+    pub fn apply(self, progress: f32) -> f32 {
+        let exponential =
+            |x: f32| ((FADE_CURVE_STEEPNESS * x).exp() - 1.0) / (FADE_CURVE_STEEPNESS.exp() - 1.0);
+        match self {
+            FadeCurve::Linear => progress,
+            FadeCurve::Exponential => exponential(progress),
+            FadeCurve::Logarithmic => 1.0 - exponential(1.0 - progress),
+        }
+    }
+}
+
+/// The errors produced by [`TranceGateParameters::try_new`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParameterError {
+    /// The gate duration is negative or not finite.
+    InvalidDuration(f64),
+    /// The sample rate is not positive or not finite.
+    InvalidSampleRate(f64),
+    /// The mix factor lies outside of `0.0..=1.0`.
+    MixOutOfRange(f32),
+    /// The gate is too short for its fades to span at least one frame.
+    ZeroLength {
+        /// The number of frames in the rejected gate.
+        gate_length: usize,
+    },
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::InvalidDuration(duration) => {
+                write!(f, "invalid gate duration {}s", duration)
+            }
+            ParameterError::InvalidSampleRate(sample_rate) => {
+                write!(f, "invalid sample rate {} Hz", sample_rate)
+            }
+            ParameterError::MixOutOfRange(mix_factor) => {
+                write!(f, "mix factor {} is outside of 0.0..=1.0", mix_factor)
+            }
+            ParameterError::ZeroLength { gate_length } => {
+                write!(f, "gate of {} frames is too short to fade", gate_length)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParameterError {}
 
 /// The parameters consumed by [`TranceGate`].
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "UncheckedParameters"))]
 pub struct TranceGateParameters {
     /// The length of the gate effect.
     pub gate_length: usize,
@@ -17,6 +120,38 @@ pub struct TranceGateParameters {
     pub fade_out: usize,
     /// The number of samples before fading in.
     pub fade_in: usize,
+    /// The sample rate that the lengths above are measured in.
+    pub sample_rate: f64,
+    /// The amplitude curve of the gate.
+    pub shape: GateShape,
+    /// The lowest amplitude reached while the gate is closed, clamped
+    /// to `0.0..=1.0`.
+    ///
+    /// A value of `0.0` fully mutes the closed portion, while the
+    /// default value of `0.1` keeps some of it audible.
+    pub floor: f32,
+    /// The number of samples by which the gate of the right channel
+    /// leads the left, wrapping around the cycle.
+    ///
+    /// A value of half the `gate_length` opens one channel while the
+    /// other is closed, while the default value of `0` keeps them in
+    /// sync.
+    pub stereo_offset: usize,
+    /// The frequency in Hz below which the signal plays through
+    /// ungated, or `None` to gate the full band.
+    ///
+    /// The signal is split with a Linkwitz-Riley crossover and only the
+    /// upper band is gated, which keeps the low end steady.
+    pub gate_crossover_hz: Option<f32>,
+    /// Determines if the gate is mirrored, such that it opens where it
+    /// would close and vice versa, e.g. for rhythmic swells.
+    ///
+    /// The `floor` and `mix_factor` apply as usual, such that the
+    /// inverted gate still never exceeds unity gain.
+    pub invert: bool,
+    /// The curve followed by each fade, where a fade out retraces the
+    /// fade in backwards.
+    pub fade_curve: FadeCurve,
 }
 
 impl TranceGateParameters {
@@ -31,10 +166,20 @@ impl TranceGateParameters {
     /// ```rust
     /// # use photon::core::effect::trance_gate::*;
     /// let gate_duration = 60.0 / 256.0 * 4.0 / 8.0;
-    /// let _ = TranceGateParameters::new(gate_duration, 0.8);
+    /// let _ = TranceGateParameters::new(gate_duration, 0.8, 44100.0);
     /// ```
-    pub fn new(gate_duration: f64, mix_factor: f32) -> Self {
-        let gate_length = gate_duration * 44100.0;
+    ///
+    /// The lengths are measured in frames of the given sample rate,
+    /// such that the same duration spans more frames at 48 kHz:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::trance_gate::*;
+    /// let parameters = TranceGateParameters::new(0.5, 0.8, 48000.0);
+    /// assert_eq!(parameters.gate_length, 24000);
+    /// assert_eq!(parameters.gate_midpoint, 12000);
+    /// ```
+    pub fn new(gate_duration: f64, mix_factor: f32, sample_rate: f64) -> Self {
+        let gate_length = gate_duration * sample_rate;
         let gate_midpoint = gate_length / 2.0;
         let fade_out = gate_midpoint * 0.05;
         let fade_in = gate_midpoint * 0.95;
@@ -45,78 +190,1793 @@ impl TranceGateParameters {
             mix_factor,
             fade_out: fade_out as usize,
             fade_in: fade_in as usize,
+            sample_rate,
+            shape: GateShape::default(),
+            floor: 0.1,
+            stereo_offset: 0,
+            gate_crossover_hz: None,
+            invert: false,
+            fade_curve: FadeCurve::default(),
+        }
+    }
+
+    /// Creates a new [`TranceGateParameters`] like [`new`], rejecting
+    /// values that it would otherwise clamp or round down to nothing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use photon::core::effect::trance_gate::*;
+    /// assert!(TranceGateParameters::try_new(0.5, 0.8, 44100.0).is_ok());
+    /// assert_eq!(
+    ///     TranceGateParameters::try_new(0.5, 1.5, 44100.0),
+    ///     Err(ParameterError::MixOutOfRange(1.5)),
+    /// );
+    /// ```
+    ///
+    /// [`new`]: Self::new
+    pub fn try_new(
+        gate_duration: f64,
+        mix_factor: f32,
+        sample_rate: f64,
+    ) -> Result<Self, ParameterError> {
+        if !gate_duration.is_finite() || gate_duration < 0.0 {
+            return Err(ParameterError::InvalidDuration(gate_duration));
+        }
+        if !sample_rate.is_finite() || sample_rate <= 0.0 {
+            return Err(ParameterError::InvalidSampleRate(sample_rate));
+        }
+        if !(0.0..=1.0).contains(&mix_factor) {
+            return Err(ParameterError::MixOutOfRange(mix_factor));
+        }
+        let parameters = Self::new(gate_duration, mix_factor, sample_rate);
+        if parameters.fade_out == 0 || parameters.fade_in == 0 {
+            return Err(ParameterError::ZeroLength {
+                gate_length: parameters.gate_length,
+            });
+        }
+        Ok(parameters)
+    }
+
+    /// Creates a new [`TranceGateParameters`] whose cycle lasts for one
+    /// `division` at the given tempo.
+    ///
+    /// # Example
+    ///
+    /// The same gate as the example for [`new`], cycling in 8th notes
+    /// at 256 BPM:
+    ///
+    /// ```rust
+    /// # use photon::core::{effect::trance_gate::*, tempo::NoteValue};
+    /// let _ = TranceGateParameters::synced(256.0, NoteValue::Eighth, 0.8, 44100.0);
+    /// ```
+    ///
+    /// [`new`]: Self::new
+    pub fn synced(bpm: f64, division: NoteValue, mix_factor: f32, sample_rate: f64) -> Self {
+        Self::new(note_duration_secs(bpm, division), mix_factor, sample_rate)
+    }
+
+    /// Creates a [`TranceGateParametersBuilder`] for setting the fades
+    /// independently of the gate duration.
+    ///
+    /// # Example
+    ///
+    /// If you want a gate that closes quickly but opens slowly:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::trance_gate::*;
+    /// let parameters = TranceGateParameters::builder()
+    ///     .gate_duration(0.5)
+    ///     .fade_out_samples(100)
+    ///     .fade_in_samples(10000)
+    ///     .build();
+    /// assert_eq!(parameters.fade_in, 10000);
+    /// ```
+    pub fn builder() -> TranceGateParametersBuilder {
+        TranceGateParametersBuilder::default()
+    }
+
+    /// Clamps the lengths such that the fades fit within each half of
+    /// the cycle, e.g. after loading them from a preset.
+    pub fn sanitized(self) -> Self {
+        let gate_midpoint = self.gate_midpoint.min(self.gate_length);
+        let fade_out = self.fade_out.min(gate_midpoint);
+        Self {
+            gate_midpoint,
+            fade_out,
+            fade_in: self.fade_in.min(gate_midpoint - fade_out),
+            stereo_offset: self.stereo_offset % self.gate_length.max(1),
+            mix_factor: self.mix_factor.clamp(0.0, 1.0),
+            floor: self.floor.clamp(0.0, 1.0),
+            gate_crossover_hz: self
+                .gate_crossover_hz
+                .filter(|hz| hz.is_finite() && *hz > 0.0),
+            ..self
+        }
+    }
+
+    /// Retimes the parameters for a different sample rate, keeping
+    /// their durations intact.
+    ///
+    /// # Example
+    ///
+    /// Parameters computed for the [default sample rate] can be reused
+    /// by a host running at 96 kHz:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::{trance_gate::*, DEFAULT_SAMPLE_RATE};
+    /// let parameters = TranceGateParameters::new(0.5, 0.8, DEFAULT_SAMPLE_RATE);
+    /// assert_eq!(parameters.with_sample_rate(96000.0).gate_length, 48000);
+    /// ```
+    ///
+    /// [default sample rate]: super::DEFAULT_SAMPLE_RATE
+    pub fn with_sample_rate(self, sample_rate: f64) -> Self {
+        let ratio = sample_rate / self.sample_rate;
+        let retime = |length: usize| (length as f64 * ratio) as usize;
+        Self {
+            gate_length: retime(self.gate_length),
+            gate_midpoint: retime(self.gate_midpoint),
+            fade_out: retime(self.fade_out),
+            fade_in: retime(self.fade_in),
+            stereo_offset: retime(self.stereo_offset),
+            sample_rate,
+            ..self
+        }
+    }
+
+    /// Compute the gate factor given the position within the cycle,
+    /// where `1.0` is fully open and `0.0` is fully closed, mirrored if
+    /// the gate is inverted.
+    pub fn gate_factor(&self, counter: usize) -> f32 {
+        self.gate_factor_at(counter as f64 / self.gate_length.max(1) as f64)
+    }
+
+    /// Compute the gate factor given the normalized `phase` within the
+    /// cycle, wrapping into `0.0..1.0`.
+    ///
+    /// Except for [`GateShape::Square`], the curve is continuous across
+    /// the whole cycle including the wrap, even when the fades do not
+    /// fit within each half.
+    pub fn gate_factor_at(&self, phase: f64) -> f32 {
+        let phase = phase.rem_euclid(1.0);
+        let midpoint = (self.gate_midpoint as f64 / self.gate_length.max(1) as f64).min(1.0);
+        let factor = match self.shape {
+            GateShape::Trapezoid => self.ramp(phase, midpoint),
+            GateShape::Sine => 0.5 - 0.5 * (PI * self.ramp(phase, midpoint)).cos(),
+            GateShape::Square => {
+                if phase < midpoint {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        };
+        if self.invert {
+            1.0 - factor
+        } else {
+            factor
+        }
+    }
+
+    /// Compute the linear ramp down before the midpoint and back up
+    /// after it, shortening the fades such that each ramp settles
+    /// before its half of the cycle ends.
+    fn ramp(&self, phase: f64, midpoint: f64) -> f32 {
+        let length = self.gate_length.max(1) as f64;
+        let closing = phase < midpoint;
+        let (local, half) = if closing {
+            (phase * length, midpoint * length)
+        } else {
+            ((phase - midpoint) * length, (1.0 - midpoint) * length)
+        };
+        let fade_out = (self.fade_out as f64).min(half);
+        let fade_in = (self.fade_in as f64).min(half - fade_out);
+        let progress = if fade_in > 0.0 {
+            ((local - fade_out) / fade_in).clamp(0.0, 1.0) as f32
+        } else if local >= fade_out {
+            // A zero-length fade is an instant step.
+            1.0
+        } else {
+            0.0
+        };
+        if closing {
+            self.fade_curve.apply(1.0 - progress)
+        } else {
+            self.fade_curve.apply(progress)
+        }
+    }
+
+    /// Fill `factors` with the gate factors of consecutive frames,
+    /// starting at the position `start` within the cycle and wrapping
+    /// at its end.
+    ///
+    /// This matches calling [`gate_factor`] once per frame, but settles
+    /// the shape and the fades of each half up front, leaving a loop
+    /// that selects between precomputed values rather than branching,
+    /// which the compiler is free to vectorize.
+    ///
+    /// [`gate_factor`]: Self::gate_factor
+    pub fn gate_factors(&self, start: usize, factors: &mut [f32]) {
+        let length = self.gate_length.max(1);
+        let midpoint = (self.gate_midpoint as f64 / length as f64).min(1.0);
+        let phases = (0..factors.len()).map(|index| {
+            let counter = (start + index) % length;
+            counter as f64 / length as f64
+        });
+        match self.shape {
+            GateShape::Trapezoid => {
+                let ramp = BranchlessRamp::new(self, midpoint);
+                for (factor, phase) in factors.iter_mut().zip(phases) {
+                    *factor = ramp.at(phase);
+                }
+            }
+            GateShape::Sine => {
+                let ramp = BranchlessRamp::new(self, midpoint);
+                for (factor, phase) in factors.iter_mut().zip(phases) {
+                    *factor = 0.5 - 0.5 * (PI * ramp.at(phase)).cos();
+                }
+            }
+            GateShape::Square => {
+                for (factor, phase) in factors.iter_mut().zip(phases) {
+                    *factor = (phase < midpoint) as u8 as f32;
+                }
+            }
+        }
+        if self.invert {
+            for factor in factors.iter_mut() {
+                *factor = 1.0 - *factor;
+            }
         }
     }
 }
 
-/// The trance gate DSP and its internal state.
+/// The ramps of [`TranceGateParameters::ramp`] with the fades of each
+/// half of the cycle settled up front, for
+/// [`TranceGateParameters::gate_factors`].
+struct BranchlessRamp {
+    /// The length of the cycle in frames.
+    length: f64,
+    /// The normalized position of the midpoint.
+    midpoint: f64,
+    /// The shortened `(fade_out, fade_in)` of the closing half.
+    closing: (f64, f64),
+    /// The shortened `(fade_out, fade_in)` of the opening half.
+    opening: (f64, f64),
+    /// The curve followed by each fade.
+    curve: FadeCurve,
+}
+
+impl BranchlessRamp {
+    fn new(parameters: &TranceGateParameters, midpoint: f64) -> Self {
+        let length = parameters.gate_length.max(1) as f64;
+        let fades = |half: f64| {
+            let fade_out = (parameters.fade_out as f64).min(half);
+            let fade_in = (parameters.fade_in as f64).min(half - fade_out);
+            (fade_out, fade_in)
+        };
+        Self {
+            length,
+            midpoint,
+            closing: fades(midpoint * length),
+            opening: fades((1.0 - midpoint) * length),
+            curve: parameters.fade_curve,
+        }
+    }
+
+    /// Compute the ramp at a `phase` within `0.0..1.0`.
+    fn at(&self, phase: f64) -> f32 {
+        let closing = phase < self.midpoint;
+        let start = if closing { 0.0 } else { self.midpoint };
+        let (fade_out, fade_in) = if closing { self.closing } else { self.opening };
+        let local = (phase - start) * self.length;
+        // A zero-length fade is an instant step, where the division is
+        // infinite or NaN and is discarded.
+        let ramp = ((local - fade_out) / fade_in).clamp(0.0, 1.0) as f32;
+        let step = (local >= fade_out) as u8 as f32;
+        let progress = if fade_in > 0.0 { ramp } else { step };
+        let progress = if closing { 1.0 - progress } else { progress };
+        self.curve.apply(progress)
+    }
+}
+
+/// Builds [`TranceGateParameters`] one field at a time.
+///
+/// Unset fields fall back to the defaults of [`TranceGateParameters::new`],
+/// with a gate duration of half a second at the [default sample rate].
+///
+/// [default sample rate]: super::DEFAULT_SAMPLE_RATE
+#[derive(Debug, Clone, Copy)]
+pub struct TranceGateParametersBuilder {
+    gate_duration: f64,
+    mix_factor: f32,
+    floor: f32,
+    shape: GateShape,
+    fade_in: Option<usize>,
+    fade_out: Option<usize>,
+    stereo_offset: usize,
+    gate_crossover_hz: Option<f32>,
+    invert: bool,
+    fade_curve: FadeCurve,
+    sample_rate: f64,
+}
+
+impl Default for TranceGateParametersBuilder {
+    fn default() -> Self {
+        Self {
+            gate_duration: 0.5,
+            mix_factor: 0.8,
+            floor: 0.1,
+            shape: GateShape::default(),
+            fade_in: None,
+            fade_out: None,
+            stereo_offset: 0,
+            gate_crossover_hz: None,
+            invert: false,
+            fade_curve: FadeCurve::default(),
+            sample_rate: DEFAULT_SAMPLE_RATE,
+        }
+    }
+}
+
+impl TranceGateParametersBuilder {
+    /// Sets the duration of a full cycle in seconds.
+    pub fn gate_duration(mut self, gate_duration: f64) -> Self {
+        self.gate_duration = gate_duration;
+        self
+    }
+
+    /// Sets how much of the gated signal is mixed with the original.
+    pub fn mix_factor(mut self, mix_factor: f32) -> Self {
+        self.mix_factor = mix_factor;
+        self
+    }
+
+    /// Sets the lowest amplitude reached while the gate is closed.
+    pub fn floor(mut self, floor: f32) -> Self {
+        self.floor = floor;
+        self
+    }
+
+    /// Sets the amplitude curve of the gate.
+    pub fn shape(mut self, shape: GateShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets the length of each ramp, which defaults to 95% of a half
+    /// cycle.
+    pub fn fade_in_samples(mut self, fade_in: usize) -> Self {
+        self.fade_in = Some(fade_in);
+        self
+    }
+
+    /// Sets the hold before each ramp, which defaults to 5% of a half
+    /// cycle.
+    pub fn fade_out_samples(mut self, fade_out: usize) -> Self {
+        self.fade_out = Some(fade_out);
+        self
+    }
+
+    /// Sets the number of samples by which the right channel leads.
+    pub fn stereo_offset_samples(mut self, stereo_offset: usize) -> Self {
+        self.stereo_offset = stereo_offset;
+        self
+    }
+
+    /// Sets the frequency below which the signal plays through ungated.
+    pub fn gate_crossover_hz(mut self, gate_crossover_hz: f32) -> Self {
+        self.gate_crossover_hz = Some(gate_crossover_hz);
+        self
+    }
+
+    /// Sets whether the gate opens where it would close and vice versa.
+    pub fn invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    /// Sets the curve followed by each fade.
+    pub fn fade_curve(mut self, fade_curve: FadeCurve) -> Self {
+        self.fade_curve = fade_curve;
+        self
+    }
+
+    /// Sets the sample rate that the lengths are measured in.
+    pub fn sample_rate(mut self, sample_rate: f64) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Creates the [`TranceGateParameters`], shortening the fades if
+    /// they do not fit within each half of the cycle.
+    pub fn build(self) -> TranceGateParameters {
+        let parameters =
+            TranceGateParameters::new(self.gate_duration, self.mix_factor, self.sample_rate);
+        TranceGateParameters {
+            fade_in: self.fade_in.unwrap_or(parameters.fade_in),
+            fade_out: self.fade_out.unwrap_or(parameters.fade_out),
+            shape: self.shape,
+            floor: self.floor,
+            stereo_offset: self.stereo_offset,
+            gate_crossover_hz: self.gate_crossover_hz,
+            invert: self.invert,
+            fade_curve: self.fade_curve,
+            ..parameters
+        }
+        .sanitized()
+    }
+}
+
+/// The fields of [`TranceGateParameters`] as written in a preset, which
+/// are sanitized before use.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct UncheckedParameters {
+    gate_length: usize,
+    gate_midpoint: usize,
+    mix_factor: f32,
+    fade_out: usize,
+    fade_in: usize,
+    sample_rate: f64,
+    shape: GateShape,
+    floor: f32,
+    #[serde(default)]
+    stereo_offset: usize,
+    #[serde(default)]
+    gate_crossover_hz: Option<f32>,
+    #[serde(default)]
+    invert: bool,
+    #[serde(default)]
+    fade_curve: FadeCurve,
+}
+
+#[cfg(feature = "serde")]
+impl From<UncheckedParameters> for TranceGateParameters {
+    fn from(unchecked: UncheckedParameters) -> Self {
+        TranceGateParameters {
+            gate_length: unchecked.gate_length,
+            gate_midpoint: unchecked.gate_midpoint,
+            mix_factor: unchecked.mix_factor,
+            fade_out: unchecked.fade_out,
+            fade_in: unchecked.fade_in,
+            sample_rate: unchecked.sample_rate,
+            shape: unchecked.shape,
+            floor: unchecked.floor,
+            stereo_offset: unchecked.stereo_offset,
+            gate_crossover_hz: unchecked.gate_crossover_hz,
+            invert: unchecked.invert,
+            fade_curve: unchecked.fade_curve,
+        }
+        .sanitized()
+    }
+}
+
+/// The number of frames taken by the [`TranceGate`] to glide towards a
+/// new `mix_factor` or `floor`, about 5ms at 44.1 kHz.
+pub const DEFAULT_SMOOTHING_SAMPLES: usize = 256;
+
+/// The time taken by the sidechain detector of the [`TranceGate`] to
+/// react to a rising level.
+pub const SIDECHAIN_ATTACK_MS: f32 = 1.0;
+
+/// The time taken by the sidechain detector of the [`TranceGate`] to
+/// react to a falling level.
+pub const SIDECHAIN_RELEASE_MS: f32 = 50.0;
+
+/// The number of channels of the [`TranceGate`] that are split at the
+/// `gate_crossover_hz`, past which channels are gated across the full
+/// band.
+pub const MAX_CROSSOVER_CHANNELS: usize = 8;
+
+/// The number of frames whose gate factors are computed at once before
+/// being multiplied in with SIMD.
+#[cfg(feature = "simd")]
+const BLOCK_FRAMES: usize = 64;
+
+/// The trance gate DSP and its internal state, processing samples of
+/// type `S`.
 #[derive(Debug)]
-pub struct TranceGate {
+pub struct TranceGateImpl<S: Sample = f32> {
     /// The parameters for the effect.
     parameters: Option<TranceGateParameters>,
     /// The number of samples processsed, used for bookkeeping.
     counter: usize,
+    /// The `mix_factor` gliding towards the parameters.
+    mix_factor: SmoothedValue,
+    /// The `floor` gliding towards the parameters.
+    floor: SmoothedValue,
+    /// The level detector for [`TranceGateImpl::process_sidechain`].
+    sidechain: EnvelopeFollower,
+    /// The crossover of each channel, used while the parameters have a
+    /// `gate_crossover_hz`.
+    crossovers: [Crossover; MAX_CROSSOVER_CHANNELS],
+    /// Determines if the `counter` snaps to the bar position reported
+    /// to [`TranceGateImpl::process_with_transport`].
+    transport_sync: bool,
+    /// The type of the samples processed.
+    sample: PhantomData<S>,
 }
 
-impl TranceGate {
+/// The [`TranceGateImpl`] processing `f32` samples, which implements
+/// [`Effect`].
+pub type TranceGate = TranceGateImpl<f32>;
+
+impl<S: Sample> TranceGateImpl<S> {
     pub fn new() -> Self {
+        let mut smoothed = SmoothedValue::new(Smoothing::Linear, 0.0);
+        smoothed.set_smoothing_samples(DEFAULT_SMOOTHING_SAMPLES);
         Self {
             parameters: None,
             counter: 0,
+            mix_factor: smoothed,
+            floor: smoothed,
+            sidechain: sidechain_detector(DEFAULT_SAMPLE_RATE),
+            crossovers: [Crossover::default(); MAX_CROSSOVER_CHANNELS],
+            transport_sync: false,
+            sample: PhantomData,
         }
     }
+
+    /// Sets the number of frames taken to glide towards a new
+    /// `mix_factor` or `floor`, where `0` applies them immediately.
+    pub fn set_smoothing_samples(&mut self, smoothing_samples: usize) {
+        self.mix_factor.set_smoothing_samples(smoothing_samples);
+        self.floor.set_smoothing_samples(smoothing_samples);
+    }
+
+    /// Ramps the `mix_factor` linearly from `start` to `end` over the
+    /// next `duration_samples` frames, then holds it at `end`, e.g. for
+    /// a riser where the gate bites harder towards a drop.
+    ///
+    /// The ramp advances once per processed frame, such that it is the
+    /// same however the signal is split into blocks. It is cut short by
+    /// parameters with a different `mix_factor`, which glide from
+    /// wherever the ramp was.
+    pub fn set_mix_envelope(&mut self, start: f32, end: f32, duration_samples: usize) {
+        self.mix_factor
+            .set_ramp(start.clamp(0.0, 1.0), end.clamp(0.0, 1.0), duration_samples);
+    }
+
+    /// Sets whether the gate cycle snaps to the bar position of the
+    /// transport passed to [`process_with_transport`], restarting at
+    /// the start of every bar.
+    ///
+    /// [`process_with_transport`]: Self::process_with_transport
+    pub fn set_transport_sync(&mut self, transport_sync: bool) {
+        self.transport_sync = transport_sync;
+    }
+}
+
+/// Creates the level detector for the sidechain of a [`TranceGate`].
+fn sidechain_detector(sample_rate: f64) -> EnvelopeFollower {
+    let mut detector = EnvelopeFollower::new(Mode::Peak, sample_rate);
+    detector.set_attack_ms(SIDECHAIN_ATTACK_MS);
+    detector.set_release_ms(SIDECHAIN_RELEASE_MS);
+    detector
+}
+
+impl<S: Sample> Default for TranceGateImpl<S> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl TranceGate {
+impl<S: Sample> TranceGateImpl<S> {
     /// Initializes the [`TranceGate`] i.e. turning it on
     pub fn initialize(&mut self, parameters: TranceGateParameters) {
-        self.parameters = Some(parameters);
-        self.counter = 0;
+        self.set_parameters(parameters);
+        self.reset();
     }
 
     /// Deinitializes the [`TranceGate`] i.e. turning it off
     pub fn deinitialize(&mut self) {
         self.parameters = None;
+        self.reset();
+    }
+
+    /// Restarts the gate cycle while keeping the current parameters,
+    /// e.g. when the transport loops back to the start of a bar.
+    pub fn reset(&mut self) {
         self.counter = 0;
+        self.sidechain.reset();
+        self.crossovers.iter_mut().for_each(Crossover::reset);
+    }
+
+    /// Replaces the parameters of the effect, gliding towards the new
+    /// `mix_factor` and `floor` unless the gate was deinitialized. An
+    /// unchanged `mix_factor` leaves a [`set_mix_envelope`] running.
+    ///
+    /// Unlike [`initialize`], the gate cycle carries on from its current
+    /// position, such that parameters can be swept without retriggering
+    /// the rhythm. If the new cycle is shorter than that position, the
+    /// current cycle ends immediately.
+    ///
+    /// [`initialize`]: Self::initialize
+    /// [`set_mix_envelope`]: Self::set_mix_envelope
+    pub fn set_parameters(&mut self, parameters: TranceGateParameters) {
+        let floor = parameters.floor.clamp(0.0, 1.0);
+        let mix_factor = parameters.mix_factor.clamp(0.0, 1.0);
+        if self.parameters.map(|previous| previous.sample_rate) != Some(parameters.sample_rate) {
+            self.sidechain = sidechain_detector(parameters.sample_rate);
+        }
+        if let Some(previous) = self.parameters {
+            self.floor.set_target(floor);
+            if previous.mix_factor != parameters.mix_factor {
+                self.mix_factor.set_target(mix_factor);
+            }
+        } else {
+            self.floor.set_value(floor);
+            self.mix_factor.set_value(mix_factor);
+        }
+        if let Some(hz) = parameters.gate_crossover_hz {
+            let hz = (hz as f64).clamp(1.0, parameters.sample_rate * 0.45);
+            let previous = self
+                .parameters
+                .and_then(|previous| previous.gate_crossover_hz);
+            for crossover in self.crossovers.iter_mut() {
+                // Keep the state of a crossover that was already running.
+                if previous.is_some() {
+                    crossover.set_frequency(hz, parameters.sample_rate);
+                } else {
+                    *crossover = Crossover::new(hz, parameters.sample_rate);
+                }
+            }
+        }
+        self.counter = self.counter.min(parameters.gate_length);
+        self.parameters = Some(parameters);
     }
 
-    /// Applies the effect to the `buffer`.
+    /// Applies the effect to the `buffer`, where `position` is the
+    /// absolute frame index of its start.
+    ///
+    /// The gate cycle is derived from the `position` rather than from
+    /// the frames processed so far, such that the output at a position
+    /// is the same after seeking or dropping blocks. Passing an
+    /// [`UNKNOWN_POSITION`] continues from the previous call instead.
+    ///
+    /// Either way, the output does not depend on how the signal is split
+    /// into blocks, as the cycle wraps before each frame is computed
+    /// rather than after the last frame of a block.
+    ///
+    /// This is a no-op if the [`TranceGate`] is deinitialized.
+    pub fn process(&mut self, position: usize, buffer: &mut [S]) {
+        if let Some(parameters) = self.parameters {
+            if position != UNKNOWN_POSITION {
+                self.counter = position % parameters.gate_length.max(1);
+            }
+        }
+        #[cfg(feature = "simd")]
+        if self
+            .parameters
+            .is_some_and(|parameters| parameters.gate_crossover_hz.is_none())
+        {
+            self.process_blocks(buffer);
+            return;
+        }
+        self.process_channels(2, buffer);
+    }
+
+    /// Applies the effect to the `buffer`, which starts at the playhead
+    /// of the `transport`.
+    ///
+    /// If transport sync is enabled and the `transport` is playing, the
+    /// gate cycle is first snapped to the position within the bar, such
+    /// that it stays phase-locked to the bar across seeks and loops.
+    /// Otherwise, it continues from the previous call.
     ///
     /// This is a no-op if the [`TranceGate`] is deinitialized.
-    pub fn process(&mut self, _: usize, buffer: &mut [f32]) {
+    pub fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [S]) {
+        if let Some(parameters) = self.parameters {
+            if self.transport_sync && transport.playing {
+                self.counter =
+                    transport.samples_into_bar() as usize % parameters.gate_length.max(1);
+            }
+        }
+        self.process(UNKNOWN_POSITION, buffer);
+    }
+
+    /// Applies the effect to a `buffer` with an arbitrary number of
+    /// interleaved `channels`, advancing the gate once per frame.
+    ///
+    /// The second channel is offset by the `stereo_offset`.
+    ///
+    /// This is a no-op if the [`TranceGate`] is deinitialized.
+    pub fn process_channels(&mut self, channels: usize, buffer: &mut [S]) {
+        self.process_frames(channels, buffer, None);
+    }
+
+    /// Applies the effect to the first `frames` of each of the separate
+    /// `channels` directly, advancing the gate once per frame.
+    ///
+    /// This is a no-op if the [`TranceGate`] is deinitialized.
+    pub fn process_planar(&mut self, frames: usize, channels: &mut [&mut [S]]) {
         let parameters = match self.parameters {
             Some(parameters) => parameters,
             None => return,
         };
-        for index in 0..buffer.len() / 2 {
-            if self.counter >= parameters.gate_length {
-                self.counter = 0;
+        let frames = channels
+            .iter()
+            .map(|channel| channel.len())
+            .fold(frames, usize::min);
+        for index in 0..frames {
+            let gate = self.advance(&parameters, None);
+            for (channel, samples) in channels.iter_mut().enumerate() {
+                let factor = gate.factor(&parameters, channel);
+                samples[index] = self.gate_sample(&parameters, channel, samples[index], factor);
             }
+        }
+    }
 
-            let mut gate_factor = if self.counter < parameters.gate_midpoint {
-                if self.counter > parameters.fade_out {
-                    1.0 - (self.counter - parameters.fade_out) as f32 / parameters.fade_in as f32
-                } else {
-                    1.0
+    /// Applies the effect to a stereo `buffer`, scaling the `mix_factor`
+    /// by the level of an interleaved stereo `sidechain` such that the
+    /// gate bites harder while the sidechain is loud.
+    ///
+    /// Processes at most `frames` frames, stopping early at the end of
+    /// the shorter of the `buffer` and the `sidechain`. Returns the
+    /// number of frames processed, leaving the rest of the `buffer`
+    /// untouched.
+    ///
+    /// This is a no-op if the [`TranceGate`] is deinitialized.
+    pub fn process_sidechain(&mut self, frames: usize, buffer: &mut [S], sidechain: &[S]) -> usize {
+        if self.parameters.is_none() {
+            return 0;
+        }
+        let frames = frames.min(buffer.len() / 2).min(sidechain.len() / 2);
+        self.process_frames(2, &mut buffer[..2 * frames], Some(&sidechain[..2 * frames]));
+        frames
+    }
+
+    /// Applies the effect to a stereo `buffer`, restarting the gate
+    /// cycle at the `sample_offset` of every note pressed by the
+    /// `events`, such that the rhythm follows the notes rather than a
+    /// free-running timer.
+    ///
+    /// The `events` are expected in order of their offsets. Events past
+    /// the end of the buffer are ignored, and releasing a note leaves
+    /// the cycle running. Processes at most `frames` frames, returning
+    /// the number of frames processed.
+    ///
+    /// This is a no-op if the [`TranceGate`] is deinitialized.
+    pub fn process_midi(&mut self, frames: usize, buffer: &mut [S], events: &[MidiEvent]) -> usize {
+        if self.parameters.is_none() {
+            return 0;
+        }
+        let frames = frames.min(buffer.len() / 2);
+        let mut start = 0;
+        for event in events.iter().filter(|event| event.is_note_on()) {
+            if event.sample_offset >= frames {
+                break;
+            }
+            let offset = event.sample_offset.max(start);
+            self.process(UNKNOWN_POSITION, &mut buffer[2 * start..2 * offset]);
+            self.counter = 0;
+            start = offset;
+        }
+        self.process(UNKNOWN_POSITION, &mut buffer[2 * start..2 * frames]);
+        frames
+    }
+
+    /// Applies the effect to a `buffer` with an arbitrary number of
+    /// interleaved `channels`, scaling the `mix_factor` by the level of
+    /// a `sidechain` with the same layout if there is one.
+    fn process_frames(&mut self, channels: usize, buffer: &mut [S], sidechain: Option<&[S]>) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        if channels == 0 {
+            return;
+        }
+        for (index, frame) in buffer.chunks_exact_mut(channels).enumerate() {
+            let level = sidechain.map(|sidechain| {
+                sidechain[index * channels..(index + 1) * channels]
+                    .iter()
+                    .fold(0.0_f32, |level, sample| level.max(sample.to_f32().abs()))
+            });
+            self.apply_frame(&parameters, frame, level);
+        }
+    }
+
+    /// Applies the effect to a stereo `buffer`, computing the gate
+    /// factors a block at a time then multiplying them in with
+    /// [`Sample::multiply`].
+    #[cfg(feature = "simd")]
+    fn process_blocks(&mut self, buffer: &mut [S]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let len = buffer.len() / 2 * 2;
+        let mut factors = [S::ZERO; 2 * BLOCK_FRAMES];
+        for block in buffer[..len].chunks_mut(2 * BLOCK_FRAMES) {
+            let factors = &mut factors[..block.len()];
+            factors.fill(S::ONE);
+            for frame in factors.chunks_exact_mut(2) {
+                self.apply_frame(&parameters, frame, None);
+            }
+            S::multiply(block, factors);
+        }
+    }
+
+    /// Applies the gate to each channel of a `frame` then advances it,
+    /// scaling the `mix_factor` by a sidechain `level` if there is one.
+    fn apply_frame(
+        &mut self,
+        parameters: &TranceGateParameters,
+        frame: &mut [S],
+        level: Option<f32>,
+    ) {
+        let gate = self.advance(parameters, level);
+        for (channel, sample) in frame.iter_mut().enumerate() {
+            let factor = gate.factor(parameters, channel);
+            *sample = self.gate_sample(parameters, channel, *sample, factor);
+        }
+    }
+
+    /// Scales a `sample` of a `channel` by the gate `factor`, leaving
+    /// the band below the `gate_crossover_hz` untouched if there is one.
+    fn gate_sample(
+        &mut self,
+        parameters: &TranceGateParameters,
+        channel: usize,
+        sample: S,
+        factor: S,
+    ) -> S {
+        match (
+            parameters.gate_crossover_hz,
+            self.crossovers.get_mut(channel),
+        ) {
+            (Some(_), Some(crossover)) => {
+                let (low, high) = crossover.split(sample.to_f32());
+                S::from_f32(low) + S::from_f32(high) * factor
+            }
+            _ => sample * factor,
+        }
+    }
+
+    /// Advances the gate by a frame, returning its state for that frame
+    /// and scaling the `mix_factor` by a sidechain `level` if there is
+    /// one.
+    fn advance(&mut self, parameters: &TranceGateParameters, level: Option<f32>) -> FrameGate<S> {
+        if self.counter >= parameters.gate_length {
+            self.counter = 0;
+        }
+
+        let length = parameters.gate_length.max(1) as f64;
+        let floor = self.floor.next();
+        let mut mix_factor = self.mix_factor.next();
+        if let Some(level) = level {
+            mix_factor *= self.sidechain.process(level).min(1.0);
+        }
+        let gate = FrameGate {
+            phase: self.counter as f64 / length,
+            offset: parameters.stereo_offset as f64 / length,
+            floor: S::from_f32(floor),
+            mix_factor: S::from_f32(mix_factor),
+        };
+
+        self.counter += 1;
+        gate
+    }
+}
+
+/// The state of the [`TranceGate`] at a single frame, shared by all of
+/// its channels.
+struct FrameGate<S> {
+    /// The position within the cycle, in `[0, 1)`.
+    phase: f64,
+    /// The lead of the second channel, as a fraction of the cycle.
+    offset: f64,
+    /// The `floor` at this frame.
+    floor: S,
+    /// The `mix_factor` at this frame.
+    mix_factor: S,
+}
+
+impl<S: Sample> FrameGate<S> {
+    /// Compute the gain applied to a `channel`.
+    fn factor(&self, parameters: &TranceGateParameters, channel: usize) -> S {
+        let mut gate_factor = S::from_f32(match channel {
+            1 => parameters.gate_factor_at(self.phase + self.offset),
+            _ => parameters.gate_factor_at(self.phase),
+        });
+
+        // Transform gate_factor such that its baseline is the floor
+        gate_factor = gate_factor * (S::ONE - self.floor) + self.floor;
+        // Transform gate_factor relative to the mix_factor
+        gate_factor * self.mix_factor + (S::ONE - self.mix_factor)
+    }
+}
+
+impl Effect for TranceGate {
+    type Parameters = TranceGateParameters;
+
+    fn process(&mut self, position: usize, buffer: &mut [f32]) {
+        TranceGateImpl::process(self, position, buffer);
+    }
+
+    fn process_with_transport(&mut self, transport: &Transport, buffer: &mut [f32]) {
+        TranceGateImpl::process_with_transport(self, transport, buffer);
+    }
+
+    /// Applies the effect to each of the separate `channels` directly,
+    /// advancing the gate once per frame.
+    fn process_planar(&mut self, frames: usize, channels: &mut [&mut [f32]]) {
+        TranceGateImpl::process_planar(self, frames, channels);
+    }
+
+    fn reset(&mut self) {
+        TranceGateImpl::reset(self);
+    }
+
+    fn set_parameters(&mut self, parameters: TranceGateParameters) {
+        TranceGateImpl::set_parameters(self, parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FadeCurve, GateShape, ParameterError, TranceGate, TranceGateImpl, TranceGateParameters,
+        DEFAULT_SMOOTHING_SAMPLES,
+    };
+    use crate::core::midi::MidiEvent;
+    use std::f32::consts::TAU;
+
+    use proptest::prelude::*;
+
+    use crate::core::effect::{Effect, UNKNOWN_POSITION};
+    use crate::core::tempo::NoteValue;
+    use crate::core::testing::property::{
+        check_finite, check_split_invariant, check_transparent, stereo_buffer,
+        trance_gate_parameters,
+    };
+    use crate::core::transport::Transport;
+
+    #[test]
+    fn try_new_rejects_degenerate_values() {
+        assert_eq!(
+            TranceGateParameters::try_new(-1.0, 0.5, 44100.0),
+            Err(ParameterError::InvalidDuration(-1.0))
+        );
+        assert_eq!(
+            TranceGateParameters::try_new(0.5, -0.1, 44100.0),
+            Err(ParameterError::MixOutOfRange(-0.1))
+        );
+        assert!(matches!(
+            TranceGateParameters::try_new(0.5, 0.5, 0.0),
+            Err(ParameterError::InvalidSampleRate(_))
+        ));
+        assert_eq!(
+            TranceGateParameters::try_new(0.0, 0.5, 44100.0),
+            Err(ParameterError::ZeroLength { gate_length: 0 })
+        );
+        assert!(matches!(
+            TranceGateParameters::try_new(0.0002, 0.5, 44100.0),
+            Err(ParameterError::ZeroLength { .. })
+        ));
+        assert_eq!(
+            TranceGateParameters::try_new(0.5, 0.8, 44100.0),
+            Ok(TranceGateParameters::new(0.5, 0.8, 44100.0))
+        );
+    }
+
+    #[test]
+    fn reset_restarts_cycle() {
+        let parameters = TranceGateParameters::new(0.01, 0.9, 44100.0);
+
+        let mut fresh = TranceGate::new();
+        fresh.initialize(parameters);
+        let mut expected = vec![1.0; 256];
+        fresh.process(0, &mut expected);
+
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        gate.process(UNKNOWN_POSITION, &mut vec![1.0; 300]);
+        gate.reset();
+        let mut buffer = vec![1.0; 256];
+        gate.process(UNKNOWN_POSITION, &mut buffer);
+
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn set_parameters_keeps_phase() {
+        let parameters = TranceGateParameters::new(0.01, 0.9, 44100.0);
+        let swept = TranceGateParameters {
+            mix_factor: 0.4,
+            floor: 0.3,
+            ..parameters
+        };
+
+        let mut fresh = TranceGate::new();
+        fresh.initialize(swept);
+        fresh.process(0, &mut vec![1.0; 2 * 300]);
+        let mut expected = vec![1.0; 2 * 256];
+        fresh.process(UNKNOWN_POSITION, &mut expected);
+
+        let mut gate = TranceGate::new();
+        gate.set_smoothing_samples(0);
+        gate.initialize(parameters);
+        gate.process(0, &mut vec![1.0; 2 * 300]);
+        gate.set_parameters(swept);
+        let mut buffer = vec![1.0; 2 * 256];
+        gate.process(UNKNOWN_POSITION, &mut buffer);
+
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn set_parameters_clamps_phase() {
+        let parameters = TranceGateParameters::new(0.01, 1.0, 44100.0);
+        let shorter = TranceGateParameters::new(0.005, 1.0, 44100.0);
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        gate.process(0, &mut vec![1.0; 2 * 400]);
+        gate.set_parameters(shorter);
+        let mut buffer = vec![1.0; 2];
+        gate.process(UNKNOWN_POSITION, &mut buffer);
+        // The shorter cycle restarts rather than running past its end.
+        assert_eq!(buffer[0], shorter.gate_factor(0));
+    }
+
+    #[test]
+    fn crossover_gates_only_highs() {
+        let sample_rate = 44100.0;
+        let parameters = TranceGateParameters::builder()
+            .gate_duration(0.2)
+            .mix_factor(1.0)
+            .floor(0.0)
+            .shape(GateShape::Square)
+            .gate_crossover_hz(1000.0)
+            .sample_rate(sample_rate)
+            .build();
+        let half = parameters.gate_midpoint;
+        // The peak of a sine over the open and closed halves of a cycle,
+        // after the crossover settles.
+        let peaks = |frequency: f32| {
+            let mut gate = TranceGate::new();
+            gate.initialize(parameters);
+            let mut buffer: Vec<f32> = (0..parameters.gate_length * 2)
+                .flat_map(|index| {
+                    let x = (TAU * frequency * index as f32 / sample_rate as f32).sin();
+                    [x, x]
+                })
+                .collect();
+            gate.process(0, &mut buffer);
+            let peak = |frames: &[f32]| {
+                frames
+                    .iter()
+                    .step_by(2)
+                    .fold(0.0_f32, |peak, x| peak.max(x.abs()))
+            };
+            let cycle = &buffer[parameters.gate_length * 2..];
+            // Skip the ringing of the crossover around each switch.
+            let margin = 400;
+            (
+                peak(&cycle[2 * margin..2 * (half - margin)]),
+                peak(&cycle[2 * (half + margin)..2 * (parameters.gate_length - margin)]),
+            )
+        };
+
+        let (open, closed) = peaks(60.0);
+        assert!((open - 1.0).abs() < 0.02 && (closed - 1.0).abs() < 0.02);
+        let (open, closed) = peaks(8000.0);
+        assert!(
+            (open - 1.0).abs() < 0.02 && closed < 0.01,
+            "{} {}",
+            open,
+            closed
+        );
+
+        let full_band = TranceGateParameters {
+            gate_crossover_hz: None,
+            ..parameters
+        };
+        let mut gate = TranceGate::new();
+        gate.initialize(full_band);
+        let mut buffer = vec![1.0; 2 * parameters.gate_length];
+        gate.process(0, &mut buffer);
+        assert_eq!(buffer[2 * half], 0.0);
+    }
+
+    #[test]
+    fn gate_factors_match_gate_factor() {
+        let base = TranceGateParameters::new(0.01, 1.0, 44100.0);
+        let variants = [
+            base,
+            TranceGateParameters {
+                shape: GateShape::Sine,
+                ..base
+            },
+            TranceGateParameters {
+                shape: GateShape::Square,
+                ..base
+            },
+            // Fades that do not fit within each half, and instant steps.
+            TranceGateParameters {
+                fade_out: 400,
+                fade_in: 0,
+                ..base
+            },
+            TranceGateParameters {
+                fade_out: 0,
+                fade_in: 0,
+                gate_midpoint: 100,
+                ..base
+            },
+            TranceGateParameters {
+                shape: GateShape::Sine,
+                invert: true,
+                ..base
+            },
+            TranceGateParameters {
+                fade_curve: FadeCurve::Exponential,
+                ..base
+            },
+            TranceGateParameters {
+                shape: GateShape::Sine,
+                fade_curve: FadeCurve::Logarithmic,
+                fade_in: 0,
+                ..base
+            },
+            TranceGateParameters::new(0.0, 1.0, 44100.0),
+        ];
+        for parameters in variants {
+            let length = parameters.gate_length.max(1);
+            for start in [0, 1, length / 2, length - 1] {
+                let mut factors = vec![0.0; 3 * length];
+                parameters.gate_factors(start, &mut factors);
+                for (index, &factor) in factors.iter().enumerate() {
+                    let expected = parameters.gate_factor((start + index) % length);
+                    assert_eq!(factor, expected, "{:?} at {}", parameters, start + index);
                 }
+            }
+        }
+    }
+
+    #[test]
+    fn note_on_restarts_cycle_at_offset() {
+        let parameters = TranceGateParameters {
+            shape: GateShape::Square,
+            floor: 0.0,
+            ..TranceGateParameters::new(0.01, 1.0, 44100.0)
+        };
+        let half = parameters.gate_midpoint;
+        let mut free = TranceGate::new();
+        free.initialize(parameters);
+        let mut triggered = TranceGate::new();
+        triggered.initialize(parameters);
+        let mut restarted = TranceGate::new();
+        restarted.initialize(parameters);
+
+        // Start halfway into the closed half of the cycle.
+        let mut skipped = vec![1.0; 2 * (half + half / 2)];
+        free.process(UNKNOWN_POSITION, &mut skipped.clone());
+        triggered.process(UNKNOWN_POSITION, &mut skipped);
+
+        let (frames, offset) = (512, 67);
+        let mut expected = vec![1.0; 2 * frames];
+        free.process(UNKNOWN_POSITION, &mut expected[..2 * offset]);
+        restarted.process(UNKNOWN_POSITION, &mut expected[2 * offset..]);
+
+        let mut buffer = vec![1.0; 2 * frames];
+        let events = [
+            MidiEvent::note_off(3, 60),
+            MidiEvent::note_on(offset, 60, 100),
+            MidiEvent::note_on(frames, 62, 100),
+        ];
+        assert_eq!(triggered.process_midi(frames, &mut buffer, &events), frames);
+        assert_eq!(buffer, expected);
+        assert_eq!(buffer[2 * offset - 2], 0.0);
+        assert_eq!(buffer[2 * offset], 1.0);
+    }
+
+    #[test]
+    fn mix_envelope_reaches_end() {
+        let parameters = TranceGateParameters {
+            shape: GateShape::Square,
+            floor: 0.0,
+            ..TranceGateParameters::new(0.1, 0.2, 44100.0)
+        };
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        let duration = 1000;
+        gate.set_mix_envelope(0.2, 1.0, duration);
+
+        // Uneven blocks, with a parameter change that keeps the mix.
+        let mut buffer = vec![1.0; 2 * duration];
+        let (first, rest) = buffer.split_at_mut(2 * 333);
+        gate.process(UNKNOWN_POSITION, first);
+        gate.set_parameters(TranceGateParameters {
+            floor: 0.1,
+            ..parameters
+        });
+        for block in rest.chunks_mut(2 * 97) {
+            assert!(gate.mix_factor.value() < 1.0);
+            gate.process(UNKNOWN_POSITION, block);
+        }
+        assert_eq!(gate.mix_factor.value(), 1.0);
+        assert!(!gate.mix_factor.is_smoothing());
+
+        let mut buffer = vec![1.0; 2 * parameters.gate_length];
+        gate.process(UNKNOWN_POSITION, &mut buffer);
+        assert_eq!(gate.mix_factor.value(), 1.0);
+    }
+
+    #[test]
+    fn inverted_gate_is_complementary() {
+        let input: Vec<f32> = (0..8820)
+            .flat_map(|index| {
+                let x = (index as f32 * 0.03).sin();
+                [x, x]
+            })
+            .collect();
+        let output = |invert, floor| {
+            let parameters = TranceGateParameters::builder()
+                .gate_duration(0.05)
+                .mix_factor(1.0)
+                .floor(floor)
+                .invert(invert)
+                .build();
+            let mut gate = TranceGate::new();
+            gate.initialize(parameters);
+            let mut buffer = input.clone();
+            gate.process(0, &mut buffer);
+            buffer
+        };
+
+        let (normal, inverted) = (output(false, 0.0), output(true, 0.0));
+        assert_ne!(normal, inverted);
+        for ((x, normal), inverted) in input.iter().zip(normal.iter()).zip(inverted.iter()) {
+            assert!((normal + inverted - x).abs() < 1e-5);
+        }
+
+        // A raised floor keeps both within unity gain.
+        let (normal, inverted) = (output(false, 0.3), output(true, 0.3));
+        for ((x, normal), inverted) in input.iter().zip(normal.iter()).zip(inverted.iter()) {
+            assert!(normal.abs() <= x.abs() && inverted.abs() <= x.abs());
+            assert!((normal + inverted - 1.3 * x).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn fade_curves_are_monotonic_and_reach_endpoints() {
+        for fade_curve in [FadeCurve::Exponential, FadeCurve::Logarithmic] {
+            let parameters = TranceGateParameters {
+                fade_curve,
+                ..TranceGateParameters::new(0.1, 1.0, 44100.0)
+            };
+            let half = parameters.gate_midpoint;
+            let fade_out = parameters.fade_out;
+            let fade_end = fade_out + parameters.fade_in;
+            let factors: Vec<f32> = (0..parameters.gate_length)
+                .map(|counter| parameters.gate_factor(counter))
+                .collect();
+
+            assert!(factors[..=fade_out].iter().all(|&factor| factor == 1.0));
+            assert!(factors[fade_end..=half + fade_out]
+                .iter()
+                .all(|&factor| factor == 0.0));
+            assert_eq!(factors[half + fade_end], 1.0);
+            for pair in factors[..half].windows(2) {
+                assert!(pair[1] <= pair[0]);
+            }
+            for pair in factors[half..].windows(2) {
+                assert!(pair[1] >= pair[0]);
+            }
+            // The curve bends away from the linear ramp without jumping.
+            let linear = TranceGateParameters {
+                fade_curve: FadeCurve::Linear,
+                ..parameters
+            };
+            let middle = fade_out + parameters.fade_in / 2;
+            assert!((parameters.gate_factor(middle) - linear.gate_factor(middle)).abs() > 0.1);
+            for pair in factors.windows(2) {
+                assert!((pair[1] - pair[0]).abs() < 0.05);
+            }
+        }
+        assert_eq!(FadeCurve::Exponential.apply(0.0), 0.0);
+        assert_eq!(FadeCurve::Exponential.apply(1.0), 1.0);
+        assert!(FadeCurve::Exponential.apply(0.5) < 0.5);
+        assert!(FadeCurve::Logarithmic.apply(0.5) > 0.5);
+    }
+
+    #[test]
+    fn square_shape_switches_at_midpoint() {
+        let mut parameters = TranceGateParameters::new(0.01, 1.0, 44100.0);
+        parameters.shape = GateShape::Square;
+        let midpoint = parameters.gate_midpoint;
+
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        let mut buffer = vec![1.0; parameters.gate_length * 2];
+        gate.process(0, &mut buffer);
+
+        assert!(buffer[..midpoint * 2].iter().all(|&sample| sample == 1.0));
+        assert!(buffer[midpoint * 2..].iter().all(|&sample| sample == 0.1));
+    }
+
+    #[test]
+    fn sine_shape_shares_endpoints() {
+        let trapezoid = TranceGateParameters::new(0.01, 1.0, 44100.0);
+        let sine = TranceGateParameters {
+            shape: GateShape::Sine,
+            ..trapezoid
+        };
+        for counter in [0, trapezoid.gate_midpoint, trapezoid.gate_length - 1] {
+            let delta = trapezoid.gate_factor(counter) - sine.gate_factor(counter);
+            assert!(delta.abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn zero_floor_reaches_silence() {
+        let parameters = TranceGateParameters {
+            floor: 0.0,
+            ..TranceGateParameters::new(0.01, 1.0, 44100.0)
+        };
+        let midpoint = parameters.gate_midpoint;
+
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        let mut buffer = vec![1.0; parameters.gate_length * 2];
+        gate.process(0, &mut buffer);
+
+        assert_eq!(buffer[midpoint * 2], 0.0);
+        assert_eq!(buffer[midpoint * 2 + 1], 0.0);
+    }
+
+    #[test]
+    fn mono_matches_stereo() {
+        let parameters = TranceGateParameters::new(0.01, 0.9, 44100.0);
+        let input: Vec<f32> = (0..600).map(|index| (index as f32 * 0.1).sin()).collect();
+
+        let mut stereo = TranceGate::new();
+        stereo.initialize(parameters);
+        let mut stereo_buffer: Vec<f32> = input.iter().flat_map(|&x| [x, x]).collect();
+        stereo.process(0, &mut stereo_buffer);
+
+        let mut mono = TranceGate::new();
+        mono.initialize(parameters);
+        let mut mono_buffer = input;
+        mono.process_channels(1, &mut mono_buffer);
+
+        for (frame, &sample) in stereo_buffer.chunks_exact(2).zip(mono_buffer.iter()) {
+            assert_eq!(frame, [sample, sample]);
+        }
+    }
+
+    #[test]
+    fn mix_factor_glides() {
+        let parameters = TranceGateParameters {
+            shape: GateShape::Square,
+            ..TranceGateParameters::new(0.1, 0.0, 44100.0)
+        };
+        let midpoint = parameters.gate_midpoint;
+
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        gate.process(0, &mut vec![1.0; midpoint * 2]);
+        Effect::set_parameters(
+            &mut gate,
+            TranceGateParameters {
+                mix_factor: 1.0,
+                ..parameters
+            },
+        );
+        let mut buffer = vec![1.0; midpoint * 2];
+        gate.process(midpoint, &mut buffer);
+
+        // While closed, the depth follows `1.0 - 0.9 * mix_factor`.
+        let step = 0.9 / DEFAULT_SMOOTHING_SAMPLES as f32;
+        for pair in buffer.chunks_exact(2).collect::<Vec<_>>().windows(2) {
+            let delta = pair[0][0] - pair[1][0];
+            assert!((-1e-6..=step + 1e-6).contains(&delta));
+        }
+        assert!((buffer[DEFAULT_SMOOTHING_SAMPLES * 2] - 0.1).abs() < 1e-6);
+        assert!((buffer[buffer.len() - 1] - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn cycle_is_continuous() {
+        // The fades overrun each half, as in a hand-written preset.
+        let mut parameters = TranceGateParameters {
+            gate_length: 1001,
+            gate_midpoint: 500,
+            fade_out: 50,
+            fade_in: 600,
+            ..TranceGateParameters::new(0.01, 1.0, 44100.0)
+        };
+        for shape in [GateShape::Trapezoid, GateShape::Sine] {
+            parameters.shape = shape;
+            let factors: Vec<f32> = (0..parameters.gate_length * 2)
+                .map(|counter| parameters.gate_factor(counter % parameters.gate_length))
+                .collect();
+            for pair in factors.windows(2) {
+                assert!((pair[1] - pair[0]).abs() < 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn short_gates_stay_finite() {
+        let zero_fades = TranceGateParameters {
+            fade_out: 0,
+            fade_in: 0,
+            ..TranceGateParameters::new(0.01, 0.9, 44100.0)
+        };
+        for parameters in [
+            TranceGateParameters::new(0.00002, 0.9, 44100.0),
+            TranceGateParameters::new(0.0001, 0.9, 44100.0),
+            TranceGateParameters::new(0.0005, 0.9, 44100.0),
+            zero_fades,
+        ] {
+            for shape in [GateShape::Trapezoid, GateShape::Sine, GateShape::Square] {
+                let mut gate = TranceGate::new();
+                gate.initialize(TranceGateParameters {
+                    shape,
+                    ..parameters
+                });
+                let mut buffer = vec![1.0; 2048];
+                gate.process(0, &mut buffer);
+                assert!(buffer.iter().all(|sample| sample.is_finite()));
+            }
+        }
+    }
+
+    #[test]
+    fn stereo_offset_opposes_channels() {
+        let parameters = TranceGateParameters {
+            floor: 0.0,
+            ..TranceGateParameters::new(0.01, 1.0, 44100.0)
+        };
+        let half = parameters.gate_length / 2;
+        let mut gate = TranceGate::new();
+        gate.initialize(TranceGateParameters {
+            stereo_offset: half,
+            ..parameters
+        });
+        let mut buffer = vec![1.0; parameters.gate_length * 4];
+        gate.process(0, &mut buffer);
+
+        for (counter, frame) in buffer.chunks_exact(2).enumerate() {
+            let counter = counter % parameters.gate_length;
+            let right = parameters.gate_factor((counter + half) % parameters.gate_length);
+            assert_eq!(frame[0], parameters.gate_factor(counter));
+            assert!((frame[1] - right).abs() < 1e-6);
+        }
+        assert_eq!(buffer[0], 1.0);
+        assert_eq!(buffer[1], 0.0);
+    }
+
+    #[test]
+    fn sidechain_scales_depth() {
+        let parameters = TranceGateParameters {
+            floor: 0.0,
+            ..TranceGateParameters::new(0.01, 1.0, 44100.0)
+        };
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        // The sidechain alternates between loud and silent every 100 ms.
+        let sidechain: Vec<f32> = (0..44100)
+            .flat_map(|index| {
+                let x = if index / 4410 % 2 == 0 { 1.0 } else { 0.0 };
+                [x, x]
+            })
+            .collect();
+        let mut buffer = vec![1.0; sidechain.len()];
+        let frames = gate.process_sidechain(44100, &mut buffer, &sidechain);
+        assert_eq!(frames, 44100);
+
+        for section in 0..10 {
+            let end = (section + 1) * 4410;
+            let trough = buffer[2 * (end - 882)..2 * end]
+                .iter()
+                .fold(1.0_f32, |trough, sample| trough.min(*sample));
+            if section % 2 == 0 {
+                assert!(trough < 0.05, "{}", trough);
             } else {
-                let after_midpoint = self.counter - parameters.gate_midpoint;
-                if after_midpoint > parameters.fade_out {
-                    (after_midpoint - parameters.fade_out) as f32 / parameters.fade_in as f32
-                } else {
-                    0.0
+                assert!(trough > 0.8, "{}", trough);
+            }
+        }
+    }
+
+    #[test]
+    fn sidechain_truncates_to_shorter_buffer() {
+        let mut gate = TranceGate::new();
+        gate.initialize(TranceGateParameters::new(0.01, 1.0, 44100.0));
+        let mut buffer = vec![1.0; 2 * 1000];
+        let sidechain = vec![1.0; 2 * 600 + 1];
+        assert_eq!(gate.process_sidechain(1000, &mut buffer, &sidechain), 600);
+        assert!(buffer[2 * 600..].iter().all(|sample| *sample == 1.0));
+        assert_eq!(gate.process_sidechain(100, &mut buffer, &sidechain), 100);
+    }
+
+    #[test]
+    fn blocks_match_frames() {
+        let parameters = TranceGateParameters {
+            stereo_offset: 37,
+            ..TranceGateParameters::new(0.003, 0.9, 44100.0)
+        };
+        let mut blocks = TranceGate::new();
+        let mut frames = TranceGate::new();
+        blocks.initialize(parameters);
+        frames.initialize(parameters);
+        let input: Vec<f32> = (0..2 * 5000)
+            .map(|index| (index as f32 * 0.01).sin())
+            .collect();
+        // Odd block sizes such that the cycle wraps at every offset.
+        for chunk in input.chunks(2 * 97 + 1) {
+            let mut expected = chunk.to_vec();
+            let mut actual = chunk.to_vec();
+            frames.process_channels(2, &mut expected);
+            Effect::process(&mut blocks, UNKNOWN_POSITION, &mut actual);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn planar_matches_interleaved() {
+        let parameters = TranceGateParameters {
+            stereo_offset: 100,
+            ..TranceGateParameters::new(0.01, 0.9, 44100.0)
+        };
+        let mut interleaved = TranceGate::new();
+        let mut planar = TranceGate::new();
+        interleaved.initialize(parameters);
+        planar.initialize(parameters);
+        let mut buffer: Vec<f32> = (0..2 * 1000)
+            .map(|index| (index as f32 * 0.01).sin())
+            .collect();
+        let mut left: Vec<f32> = buffer.iter().step_by(2).copied().collect();
+        let mut right: Vec<f32> = buffer.iter().skip(1).step_by(2).copied().collect();
+        interleaved.process(0, &mut buffer);
+        planar.process_planar(1000, &mut [&mut left, &mut right]);
+        for (frame, (l, r)) in buffer.chunks_exact(2).zip(left.iter().zip(right.iter())) {
+            assert_eq!(frame[0], *l);
+            assert_eq!(frame[1], *r);
+        }
+    }
+
+    #[test]
+    fn f64_matches_f32() {
+        let parameters = TranceGateParameters {
+            stereo_offset: 100,
+            ..TranceGateParameters::new(0.01, 0.9, 44100.0)
+        };
+        let mut single = TranceGate::new();
+        let mut double = TranceGateImpl::<f64>::new();
+        single.initialize(parameters);
+        double.initialize(parameters);
+        let mut buffer: Vec<f32> = (0..2 * 1000)
+            .map(|index| (index as f32 * 0.01).sin())
+            .collect();
+        let mut wide: Vec<f64> = buffer.iter().map(|&sample| sample as f64).collect();
+        single.process(0, &mut buffer);
+        double.process(0, &mut wide);
+        for (&sample, &wide) in buffer.iter().zip(wide.iter()) {
+            assert!((sample - wide as f32).abs() <= f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn transport_sync_aligns_to_bars() {
+        let parameters = TranceGateParameters {
+            floor: 0.0,
+            ..TranceGateParameters::synced(120.0, NoteValue::DottedQuarter, 1.0, 48000.0)
+        };
+        let mut transport = Transport {
+            playing: true,
+            ..Transport::new(120.0, 48000.0)
+        };
+        let bar = transport.bar_samples() as u64;
+
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        gate.set_transport_sync(true);
+        // Some history that leaves the gate mid-cycle.
+        gate.process_with_transport(&transport, &mut vec![1.0; 2 * 777]);
+
+        for sample_pos in [bar * 3, bar * 5 + 100] {
+            transport.sample_pos = sample_pos;
+            let mut buffer = vec![1.0; 2 * 256];
+            gate.process_with_transport(&transport, &mut buffer);
+            let into_bar = (sample_pos % bar) as usize;
+            for (offset, frame) in buffer.chunks_exact(2).enumerate() {
+                let counter = (into_bar + offset) % parameters.gate_length;
+                assert_eq!(frame[0], parameters.gate_factor(counter));
+            }
+        }
+    }
+
+    #[test]
+    fn transport_sync_is_opt_in() {
+        let parameters = TranceGateParameters::new(0.01, 0.9, 48000.0);
+        let transport = Transport {
+            sample_pos: 12345,
+            playing: true,
+            ..Transport::new(120.0, 48000.0)
+        };
+        let mut expected = TranceGate::new();
+        let mut gate = TranceGate::new();
+        expected.initialize(parameters);
+        gate.initialize(parameters);
+        let mut buffer = vec![1.0; 2 * 256];
+        let mut expected_buffer = buffer.clone();
+        gate.process_with_transport(&transport, &mut buffer);
+        expected.process(0, &mut expected_buffer);
+        assert_eq!(buffer, expected_buffer);
+    }
+
+    #[test]
+    fn position_is_independent_of_history() {
+        let parameters = TranceGateParameters {
+            stereo_offset: 100,
+            ..TranceGateParameters::new(0.01, 0.9, 44100.0)
+        };
+        let position = 5 * parameters.gate_length + 123;
+
+        let mut fresh = TranceGate::new();
+        fresh.initialize(parameters);
+        let mut expected = vec![1.0; 2 * 256];
+        fresh.process(position, &mut expected);
+
+        // Seeks back and forth, as well as dropping a block.
+        let mut gate = TranceGate::new();
+        gate.initialize(parameters);
+        gate.process(0, &mut vec![1.0; 2 * 1000]);
+        gate.process(9000, &mut vec![1.0; 2 * 77]);
+        gate.process(UNKNOWN_POSITION, &mut [1.0; 2 * 3]);
+        let mut buffer = vec![1.0; 2 * 256];
+        gate.process(position, &mut buffer);
+
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn block_size_does_not_matter() {
+        // A cycle of 441 frames, such that blocks straddle its wrap.
+        let parameters = TranceGateParameters {
+            stereo_offset: 100,
+            ..TranceGateParameters::new(0.01, 0.9, 44100.0)
+        };
+        let gate = || {
+            let mut gate = TranceGate::new();
+            gate.initialize(parameters);
+            // Glide across several blocks.
+            gate.set_parameters(TranceGateParameters {
+                mix_factor: 0.5,
+                ..parameters
+            });
+            gate
+        };
+        let input: Vec<f32> = (0..2 * 1024)
+            .map(|index| (index as f32 * 0.01).sin())
+            .collect();
+        let mut expected = input.clone();
+        gate().process(0, &mut expected);
+
+        for sizes in [
+            &[512, 512][..],
+            &[1; 1024],
+            &[440, 1, 1, 582],
+            &[441, 441, 142],
+            &[3, 64, 7, 950],
+        ] {
+            for tracked in [true, false] {
+                let mut gate = gate();
+                let mut buffer = input.clone();
+                let mut position = 0;
+                for &size in sizes {
+                    let block = &mut buffer[2 * position..2 * (position + size)];
+                    gate.process(if tracked { position } else { UNKNOWN_POSITION }, block);
+                    position += size;
                 }
-            };
+                assert_eq!(buffer, expected, "{:?}", sizes);
+            }
+        }
+    }
 
-            // Transform gate_factor such that its baseline is 0.1
-            gate_factor = gate_factor * (1.0 - 0.1) + 0.1;
-            // Transform gate_factor relative to the mix_factor
-            gate_factor = gate_factor * parameters.mix_factor + (1.0 - parameters.mix_factor);
+    #[test]
+    fn unknown_position_continues_cycle() {
+        let parameters = TranceGateParameters::new(0.01, 0.9, 44100.0);
+        let mut whole = TranceGate::new();
+        let mut split = TranceGate::new();
+        whole.initialize(parameters);
+        split.initialize(parameters);
+        let mut expected = vec![1.0; 2 * 1000];
+        whole.process(0, &mut expected);
+        let mut buffer = vec![1.0; 2 * 1000];
+        let (first, second) = buffer.split_at_mut(2 * 300);
+        split.process(0, first);
+        split.process(UNKNOWN_POSITION, second);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn builder_matches_new() {
+        let built = TranceGateParameters::builder()
+            .gate_duration(0.01)
+            .mix_factor(0.9)
+            .build();
+        assert_eq!(built, TranceGateParameters::new(0.01, 0.9, 44100.0));
+    }
+
+    #[test]
+    fn builder_allows_asymmetric_fades() {
+        let parameters = TranceGateParameters::builder()
+            .gate_duration(0.01)
+            .fade_out_samples(10)
+            .fade_in_samples(1000)
+            .build();
+        assert_eq!(parameters.fade_out, 10);
+        assert_eq!(parameters.fade_in, parameters.gate_midpoint - 10);
+
+        // The gate is fully closed once the shortened ramp ends.
+        let closing = parameters.gate_factor(parameters.gate_midpoint - 1);
+        assert!(closing < 0.01);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let parameters = TranceGateParameters {
+            shape: GateShape::Sine,
+            ..TranceGateParameters::new(0.25, 0.7, 48000.0)
+        };
+        let json = serde_json::to_string(&parameters).unwrap();
+        let loaded: TranceGateParameters = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, parameters);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_sanitizes_fades() {
+        let json = r#"{
+            "gate_length": 1000, "gate_midpoint": 2000, "mix_factor": 3.0,
+            "fade_out": 600, "fade_in": 900, "sample_rate": 44100.0,
+            "shape": "Trapezoid", "floor": -1.0
+        }"#;
+        let loaded: TranceGateParameters = serde_json::from_str(json).unwrap();
+        assert_eq!(loaded.gate_midpoint, 1000);
+        assert_eq!(loaded.fade_out + loaded.fade_in, 1000);
+        assert_eq!(loaded.mix_factor, 1.0);
+        assert_eq!(loaded.floor, 0.0);
+    }
+
+    proptest! {
+        #[test]
+        fn split_blocks_match_one_block(
+            input in stereo_buffer(4096),
+            parameters in trance_gate_parameters(44100.0),
+            split_frame in 0_usize..4096,
+        ) {
+            let make = || {
+                let mut gate = TranceGate::new();
+                gate.initialize(parameters);
+                gate
+            };
+            check_split_invariant(make, &input, split_frame)?;
+        }
 
-            buffer[index * 2] *= gate_factor;
-            buffer[index * 2 + 1] *= gate_factor;
+        #[test]
+        fn output_is_finite_and_dry_is_transparent(
+            input in stereo_buffer(1024),
+            parameters in trance_gate_parameters(44100.0),
+        ) {
+            let mut gate = TranceGate::new();
+            gate.initialize(parameters);
+            let mut buffer = input.clone();
+            gate.process(0, &mut buffer);
+            check_finite(&buffer)?;
 
-            self.counter += 1;
+            let mut gate = TranceGate::new();
+            gate.initialize(TranceGateParameters {
+                mix_factor: 0.0,
+                ..parameters
+            });
+            check_transparent(&mut gate, &input)?;
         }
     }
 }
@@ -0,0 +1,165 @@
+//! Modulates the amplitude of the signal with a free-running LFO.
+use super::Effect;
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+use crate::core::{
+    dsp::{Lfo, Waveform},
+    tempo::{note_duration_secs, NoteValue},
+};
+
+/// The parameters consumed by [`Tremolo`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TremoloParameters {
+    /// The frequency of the modulation in Hz.
+    pub rate_hz: f32,
+    /// How far the amplitude dips at the bottom of the cycle, from `0.0`
+    /// for no modulation to `1.0` for full silence.
+    pub depth: f32,
+    /// The shape of the modulation.
+    pub waveform: Waveform,
+    /// How far ahead the right channel runs as a fraction of a cycle,
+    /// where `0.5` makes the channels alternate.
+    pub stereo_phase: f32,
+}
+
+impl TremoloParameters {
+    /// Creates a new [`TremoloParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a gentle, rotary tremolo:
+    ///
+    /// ```rust
+    /// # use photon::core::{dsp::Waveform, effect::tremolo::*};
+    /// let _ = TremoloParameters::new(5.0, 0.5, Waveform::Sine, 0.25);
+    /// ```
+    pub fn new(rate_hz: f32, depth: f32, waveform: Waveform, stereo_phase: f32) -> Self {
+        Self {
+            rate_hz: rate_hz.max(0.0),
+            depth: depth.clamp(0.0, 1.0),
+            waveform,
+            stereo_phase: stereo_phase.rem_euclid(1.0),
+        }
+    }
+
+    /// Creates a new [`TremoloParameters`] completing a cycle every
+    /// `division` at the given `bpm`.
+    ///
+    /// # Example
+    ///
+    /// If you want a tremolo pulsing on every eighth note at 120 BPM:
+    ///
+    /// ```rust
+    /// # use photon::core::{dsp::Waveform, effect::tremolo::*, tempo::NoteValue};
+    /// let parameters = TremoloParameters::synced(120.0, NoteValue::Eighth, 1.0, Waveform::Sine);
+    /// assert_eq!(parameters.rate_hz, 4.0);
+    /// ```
+    pub fn synced(bpm: f64, division: NoteValue, depth: f32, waveform: Waveform) -> Self {
+        let rate_hz = 1.0 / note_duration_secs(bpm, division);
+        Self::new(rate_hz as f32, depth, waveform, 0.0)
+    }
+
+    /// The gain applied for a `modulation` in `-1.0..=1.0`.
+    fn gain(&self, modulation: f32) -> f32 {
+        1.0 - self.depth.clamp(0.0, 1.0) * 0.5 * (1.0 - modulation)
+    }
+}
+
+/// The tremolo DSP and its internal state.
+#[derive(Debug)]
+pub struct Tremolo {
+    /// The parameters for the effect.
+    parameters: Option<TremoloParameters>,
+    /// The modulation shared by both channels.
+    lfo: Lfo,
+}
+
+impl Tremolo {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            parameters: None,
+            lfo: Lfo::new(Waveform::Sine, 0.0, sample_rate),
+        }
+    }
+}
+
+impl Tremolo {
+    /// Initializes the [`Tremolo`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: TremoloParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Tremolo`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Tremolo {
+    type Parameters = TremoloParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let offset = parameters.stereo_phase.rem_euclid(1.0) as f64;
+        for frame in buffer.chunks_exact_mut(2) {
+            let right = parameters
+                .waveform
+                .evaluate((self.lfo.phase() + offset).fract());
+            let left = self.lfo.next();
+            frame[0] *= parameters.gain(left);
+            frame[1] *= parameters.gain(right);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.lfo.reset();
+    }
+
+    /// Replaces the parameters of the effect, keeping the phase of the
+    /// modulation such that rate changes do not click.
+    fn set_parameters(&mut self, parameters: TremoloParameters) {
+        self.lfo.set_frequency(parameters.rate_hz as f64);
+        self.lfo.set_waveform(parameters.waveform);
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tremolo, TremoloParameters};
+    use crate::core::{dsp::Waveform, effect::Effect};
+
+    #[test]
+    fn envelope_follows_lfo() {
+        let sample_rate = 1000.0;
+        for waveform in [Waveform::Sine, Waveform::Triangle, Waveform::Saw] {
+            let mut tremolo = Tremolo::new(sample_rate);
+            tremolo.initialize(TremoloParameters::new(2.0, 0.8, waveform, 0.0));
+            let mut buffer = vec![1.0; 2000];
+            tremolo.process(0, &mut buffer);
+            for (index, frame) in buffer.chunks_exact(2).enumerate() {
+                let phase = (index as f64 * 2.0 / sample_rate).fract();
+                let expected = 1.0 - 0.8 * 0.5 * (1.0 - waveform.evaluate(phase));
+                assert!((frame[0] - expected).abs() < 1e-4);
+                assert_eq!(frame[0], frame[1]);
+            }
+        }
+    }
+
+    #[test]
+    fn stereo_phase_alternates_channels() {
+        let mut tremolo = Tremolo::new(1000.0);
+        tremolo.initialize(TremoloParameters::new(1.0, 1.0, Waveform::Sine, 0.5));
+        let mut buffer = vec![1.0; 2000];
+        tremolo.process(0, &mut buffer);
+        for frame in buffer.chunks_exact(2) {
+            assert!((frame[0] + frame[1] - 1.0).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Calibrates the level of each channel on its own.
+use super::Effect;
+use crate::core::dsp::{decibel::db_to_gain, SmoothedValue, Smoothing};
+
+/// The time taken for trim changes to settle.
+pub const TRIM_SMOOTHING_MS: f32 = 20.0;
+
+/// The parameters consumed by [`Trim`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrimParameters {
+    /// The gain applied to the left channel, in dB.
+    pub left_db: f32,
+    /// The gain applied to the right channel, in dB.
+    pub right_db: f32,
+}
+
+impl TrimParameters {
+    /// Creates a new [`TrimParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want to correct a recording whose left channel runs hot:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::trim::*;
+    /// let _ = TrimParameters::new(-1.5, 0.0);
+    /// ```
+    pub fn new(left_db: f32, right_db: f32) -> Self {
+        Self { left_db, right_db }
+    }
+}
+
+/// The trim DSP and its internal state.
+///
+/// Unlike a panner, the channels are not traded against each other, as
+/// the gains are meant to calibrate each channel to an absolute level.
+#[derive(Debug)]
+pub struct Trim {
+    /// The parameters for the effect.
+    parameters: Option<TrimParameters>,
+    /// The linear gain of each channel, gliding towards the parameters.
+    gains: [SmoothedValue; 2],
+}
+
+impl Trim {
+    pub fn new(sample_rate: f64) -> Self {
+        let mut gain = SmoothedValue::new(Smoothing::Linear, 1.0);
+        gain.set_smoothing_time(TRIM_SMOOTHING_MS, sample_rate);
+        Self {
+            parameters: None,
+            gains: [gain, gain],
+        }
+    }
+}
+
+impl Trim {
+    /// Initializes the [`Trim`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: TrimParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Trim`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Trim {
+    type Parameters = TrimParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        if self.parameters.is_none() {
+            return;
+        }
+        for frame in buffer.chunks_exact_mut(2) {
+            for (sample, gain) in frame.iter_mut().zip(self.gains.iter_mut()) {
+                *sample *= gain.next();
+            }
+        }
+    }
+
+    /// Clears the internal state of the effect, snapping the gains to
+    /// the parameters.
+    fn reset(&mut self) {
+        for gain in self.gains.iter_mut() {
+            let target = gain.target();
+            gain.set_value(target);
+        }
+    }
+
+    /// Replaces the parameters of the effect, gliding towards the new
+    /// gains over [`TRIM_SMOOTHING_MS`].
+    fn set_parameters(&mut self, parameters: TrimParameters) {
+        self.gains[0].set_target(db_to_gain(parameters.left_db));
+        self.gains[1].set_target(db_to_gain(parameters.right_db));
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Trim, TrimParameters};
+    use crate::core::{dsp::decibel::gain_to_db, effect::Effect};
+
+    #[test]
+    fn channels_are_trimmed_separately() {
+        let mut trim = Trim::new(44100.0);
+        trim.initialize(TrimParameters::new(3.0, -3.0));
+        let mut buffer = vec![0.5; 2 * 100];
+        trim.process(0, &mut buffer);
+        for frame in buffer.chunks_exact(2) {
+            assert!((gain_to_db(frame[0] / 0.5) - 3.0).abs() < 1e-3);
+            assert!((gain_to_db(frame[1] / 0.5) + 3.0).abs() < 1e-3);
+        }
+
+        // Changes glide rather than jump.
+        trim.set_parameters(TrimParameters::new(-3.0, -3.0));
+        let mut buffer = vec![0.5; 2 * 44100];
+        trim.process(0, &mut buffer);
+        let left: Vec<f32> = buffer.iter().step_by(2).copied().collect();
+        assert!(left.windows(2).all(|pair| (pair[1] - pair[0]).abs() < 1e-3));
+        assert!((gain_to_db(left[44099] / 0.5) + 3.0).abs() < 1e-3);
+        assert!(buffer.iter().skip(1).step_by(2).all(|&x| x == buffer[1]));
+    }
+}
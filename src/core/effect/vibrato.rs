@@ -0,0 +1,191 @@
+//! Wobbles the pitch of the signal with a modulated delay.
+use super::Effect;
+use crate::core::dsp::{DelayLine, Lfo, SmoothedValue, Smoothing, Waveform};
+
+/// The delay when the modulation is at its lowest.
+pub const MIN_DELAY_MS: f32 = 0.1;
+
+/// The largest modulation depth accepted by [`VibratoParameters`].
+pub const MAX_DEPTH_MS: f32 = 10.0;
+
+/// The time taken for depth changes to settle.
+pub const DEPTH_SMOOTHING_MS: f32 = 20.0;
+
+/// The parameters consumed by [`Vibrato`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VibratoParameters {
+    /// The frequency of the modulation in Hz.
+    pub rate_hz: f32,
+    /// The range swept by the delay, clamped to `0.0..=MAX_DEPTH_MS`
+    /// such that it stays within the delay line.
+    pub depth_ms: f32,
+}
+
+impl VibratoParameters {
+    /// Creates a new [`VibratoParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a subtle, singer-like vibrato:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::vibrato::*;
+    /// let _ = VibratoParameters::new(5.5, 1.5);
+    /// ```
+    pub fn new(rate_hz: f32, depth_ms: f32) -> Self {
+        Self {
+            rate_hz: rate_hz.max(0.0),
+            depth_ms: depth_ms.clamp(0.0, MAX_DEPTH_MS),
+        }
+    }
+}
+
+/// The vibrato DSP and its internal state.
+#[derive(Debug)]
+pub struct Vibrato {
+    /// The sample rate of the processed audio.
+    sample_rate: f64,
+    /// The parameters for the effect.
+    parameters: Option<VibratoParameters>,
+    /// The delay line of each channel.
+    lines: [DelayLine; 2],
+    /// The modulation shared by both channels, a sine such that the
+    /// pitch eases in and out of each turnaround.
+    lfo: Lfo,
+    /// The depth in samples, gliding towards the parameters.
+    depth: SmoothedValue,
+}
+
+impl Vibrato {
+    /// Creates a new [`Vibrato`], allocating enough delay for the
+    /// deepest modulation up front.
+    pub fn new(sample_rate: f64) -> Self {
+        let len = ((MIN_DELAY_MS + MAX_DEPTH_MS) as f64 * 0.001 * sample_rate) as usize + 4;
+        let line = DelayLine::new(len);
+        let mut depth = SmoothedValue::new(Smoothing::Exponential, 0.0);
+        depth.set_smoothing_time(DEPTH_SMOOTHING_MS, sample_rate);
+        Self {
+            sample_rate,
+            parameters: None,
+            lines: [line.clone(), line],
+            lfo: Lfo::new(Waveform::Sine, 0.0, sample_rate),
+            depth,
+        }
+    }
+
+    /// Converts a depth in ms to samples.
+    fn depth_samples(&self, depth_ms: f32) -> f32 {
+        depth_ms.clamp(0.0, MAX_DEPTH_MS) * (self.sample_rate * 0.001) as f32
+    }
+}
+
+impl Vibrato {
+    /// Initializes the [`Vibrato`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: VibratoParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+        self.depth
+            .set_value(self.depth_samples(parameters.depth_ms));
+    }
+
+    /// Deinitializes the [`Vibrato`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Vibrato {
+    type Parameters = VibratoParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        if self.parameters.is_none() {
+            return;
+        }
+        let base = MIN_DELAY_MS * (self.sample_rate * 0.001) as f32;
+        for frame in buffer.chunks_exact_mut(2) {
+            let delay = base + self.depth.next() * 0.5 * (1.0 + self.lfo.next());
+            for (sample, line) in frame.iter_mut().zip(self.lines.iter_mut()) {
+                line.write(*sample);
+                *sample = line.read_cubic(delay);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.clear();
+        }
+        self.lfo.reset();
+    }
+
+    /// Replaces the parameters of the effect, gliding towards the new
+    /// depth over [`DEPTH_SMOOTHING_MS`].
+    fn set_parameters(&mut self, parameters: VibratoParameters) {
+        self.lfo.set_frequency(parameters.rate_hz as f64);
+        self.depth
+            .set_target(self.depth_samples(parameters.depth_ms));
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{Vibrato, VibratoParameters, MAX_DEPTH_MS, MIN_DELAY_MS};
+    use crate::core::effect::Effect;
+
+    /// Measures the magnitude of a `frequency` in the left channel.
+    fn goertzel(buffer: &[f32], frequency: f64, sample_rate: f64) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (index, frame) in buffer.chunks_exact(2).enumerate() {
+            let phase = TAU * frequency * index as f64 / sample_rate;
+            re += frame[0] as f64 * phase.cos();
+            im -= frame[0] as f64 * phase.sin();
+        }
+        re.hypot(im) / (buffer.len() / 2) as f64
+    }
+
+    #[test]
+    fn modulation_sidebands() {
+        let sample_rate = 44100.0;
+        let input: Vec<f32> = (0..44100 * 2)
+            .flat_map(|index| {
+                let x = (TAU * 1000.0 * index as f64 / sample_rate).sin() as f32;
+                [x, x]
+            })
+            .collect();
+        let mut vibrato = Vibrato::new(sample_rate);
+        vibrato.initialize(VibratoParameters::new(5.0, 0.5));
+        let mut buffer = input.clone();
+        vibrato.process(0, &mut buffer);
+
+        let window = 44100 * 2;
+        for sideband in [995.0, 1005.0] {
+            assert!(goertzel(&input[window..], sideband, sample_rate) < 0.005);
+            assert!(goertzel(&buffer[window..], sideband, sample_rate) > 0.1);
+        }
+        // Without a dry blend, the carrier loses energy to the sidebands.
+        let dry = goertzel(&input[window..], 1000.0, sample_rate);
+        let wet = goertzel(&buffer[window..], 1000.0, sample_rate);
+        assert!(wet < dry * 0.9);
+    }
+
+    #[test]
+    fn deepest_sweep_fits_line() {
+        let sample_rate = 48000.0;
+        let mut vibrato = Vibrato::new(sample_rate);
+        let parameters = VibratoParameters::new(2.0, 100.0);
+        assert_eq!(parameters.depth_ms, MAX_DEPTH_MS);
+        vibrato.initialize(parameters);
+        let longest = (MIN_DELAY_MS + MAX_DEPTH_MS) as f64 * 0.001 * sample_rate;
+        assert!(vibrato.lines[0].len() as f64 > longest + 2.0);
+        // An impulse reaches the output as the delay sweeps past it.
+        let mut buffer = vec![0.0; 2 * 48000];
+        buffer[0] = 1.0;
+        vibrato.process(0, &mut buffer);
+        assert!(buffer.iter().step_by(2).any(|sample| sample.abs() > 0.1));
+    }
+}
@@ -0,0 +1,170 @@
+//! Folds the peaks of the signal back on themselves.
+//!
+//! # Overview
+//!
+//! The signal is amplified by the fold amount, and whatever exceeds
+//! `-1.0..=1.0` is reflected back from the edges, repeatedly for large
+//! amounts. Unlike clipping, the folded peaks keep moving, adding more
+//! harmonics the further the signal is pushed.
+use super::Effect;
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The largest fold amount accepted by [`WavefolderParameters`].
+pub const MAX_FOLD_AMOUNT: f32 = 16.0;
+
+/// The parameters consumed by [`Wavefolder`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WavefolderParameters {
+    /// The gain added before folding, clamped to
+    /// `0.0..=MAX_FOLD_AMOUNT`, where `0.0` leaves the signal unchanged.
+    pub fold_amount: f32,
+    /// The bias added before folding, clamped to `-1.0..=1.0`, which
+    /// folds the peaks asymmetrically.
+    pub offset: f32,
+    /// Determines how much of the folded signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+}
+
+impl WavefolderParameters {
+    /// Creates a new [`WavefolderParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a bright, slightly asymmetric fold:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::wavefolder::*;
+    /// let _ = WavefolderParameters::new(3.0, 0.2, 1.0);
+    /// ```
+    pub fn new(fold_amount: f32, offset: f32, mix: f32) -> Self {
+        Self {
+            fold_amount: fold_amount.clamp(0.0, MAX_FOLD_AMOUNT),
+            offset: offset.clamp(-1.0, 1.0),
+            mix: mix.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Folds a sample into `-1.0..=1.0`.
+    pub fn fold(&self, x: f32) -> f32 {
+        let fold_amount = self.fold_amount.clamp(0.0, MAX_FOLD_AMOUNT);
+        if fold_amount == 0.0 {
+            return x;
+        }
+        let x = x * (1.0 + fold_amount) + self.offset.clamp(-1.0, 1.0);
+        // A triangle wave of period 4 through the origin, which is the
+        // identity within -1.0..=1.0 and reflects at its edges.
+        4.0 * (((x - 1.0) * 0.25).rem_euclid(1.0) - 0.5).abs() - 1.0
+    }
+}
+
+/// The wavefolder DSP.
+#[derive(Debug)]
+pub struct Wavefolder {
+    /// The parameters for the effect.
+    parameters: Option<WavefolderParameters>,
+}
+
+impl Wavefolder {
+    pub fn new() -> Self {
+        Self { parameters: None }
+    }
+}
+
+impl Default for Wavefolder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Wavefolder {
+    /// Initializes the [`Wavefolder`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: WavefolderParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Wavefolder`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Wavefolder {
+    type Parameters = WavefolderParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        for frame in buffer.chunks_exact_mut(2) {
+            for sample in frame.iter_mut() {
+                let folded = parameters.fold(*sample);
+                *sample = *sample * (1.0 - parameters.mix) + folded * parameters.mix;
+            }
+        }
+    }
+
+    fn reset(&mut self) {}
+
+    fn set_parameters(&mut self, parameters: WavefolderParameters) {
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Wavefolder, WavefolderParameters, MAX_FOLD_AMOUNT};
+    use crate::core::effect::Effect;
+
+    fn ramp(frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|index| {
+                let x = index as f32 / (frames - 1) as f32 * 2.0 - 1.0;
+                [x, x]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ramp_stays_bounded() {
+        for (fold_amount, offset) in [(1.0, 0.0), (5.0, 0.5), (MAX_FOLD_AMOUNT, -1.0)] {
+            let mut folder = Wavefolder::new();
+            folder.initialize(WavefolderParameters::new(fold_amount, offset, 1.0));
+            let mut buffer = ramp(10000);
+            folder.process(0, &mut buffer);
+            assert!(buffer.iter().all(|sample| (-1.0..=1.0).contains(sample)));
+        }
+    }
+
+    #[test]
+    fn folds_increase_with_amount() {
+        let mut previous = 0;
+        for fold_amount in [1.0, 3.0, 9.0] {
+            let mut folder = Wavefolder::new();
+            folder.initialize(WavefolderParameters::new(fold_amount, 0.0, 1.0));
+            let mut buffer = ramp(10000);
+            folder.process(0, &mut buffer);
+            // Each fold reverses the direction of the ramp.
+            let left: Vec<f32> = buffer.iter().step_by(2).copied().collect();
+            let slopes: Vec<bool> = left.windows(2).map(|pair| pair[1] > pair[0]).collect();
+            let turns = slopes.windows(2).filter(|pair| pair[0] != pair[1]).count();
+            assert!(turns > previous, "{} <= {}", turns, previous);
+            previous = turns;
+        }
+    }
+
+    #[test]
+    fn zero_fold_passes_through() {
+        let mut folder = Wavefolder::new();
+        folder.initialize(WavefolderParameters::new(0.0, 0.5, 1.0));
+        let input: Vec<f32> = ramp(1000).iter().map(|sample| sample * 3.0).collect();
+        let mut buffer = input.clone();
+        folder.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+    }
+}
@@ -0,0 +1,278 @@
+//! Distorts the signal with an arbitrary transfer function.
+//!
+//! # Overview
+//!
+//! The output is `shape(drive * x)` for a user-supplied `shape`. Most
+//! shapers add harmonics past Nyquist, so the shaper runs inside an
+//! [`Oversampler`] at 2x or 4x the sample rate at the cost of some
+//! latency.
+use alloc::boxed::Box;
+use core::f32::consts::FRAC_PI_2;
+use core::fmt;
+
+use super::{Effect, UNKNOWN_POSITION};
+use crate::core::dsp::{delay_comp::DelayCompensator, oversample, FastTanh, Oversampler};
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The highest oversampling factor accepted by [`WaveshaperParameters`].
+pub const MAX_OVERSAMPLE: usize = oversample::MAX_FACTOR;
+
+/// The parameters consumed by [`Waveshaper`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WaveshaperParameters {
+    /// The gain applied before the shaper.
+    pub drive: f32,
+    /// Determines how much of the shaped signal is mixed with the
+    /// original audio.
+    pub mix: f32,
+    /// The oversampling factor, one of `1`, `2`, or `4`.
+    pub oversample: usize,
+}
+
+impl WaveshaperParameters {
+    /// Creates a new [`WaveshaperParameters`], rounding the
+    /// `oversample` factor down to a supported value.
+    ///
+    /// # Example
+    ///
+    /// If you want to fold a signal driven well past its peaks:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::waveshaper::*;
+    /// let _ = WaveshaperParameters::new(3.0, 1.0, 4);
+    /// ```
+    pub fn new(drive: f32, mix: f32, oversample: usize) -> Self {
+        Self {
+            drive: drive.max(0.0),
+            mix: mix.clamp(0.0, 1.0),
+            oversample: oversample::supported_factor(oversample),
+        }
+    }
+}
+
+/// Clips the signal to `-1.0..=1.0`.
+pub fn hard_clip(x: f32) -> f32 {
+    x.clamp(-1.0, 1.0)
+}
+
+/// Folds the signal back on itself along a sine, staying within
+/// `-1.0..=1.0` for any input.
+pub fn sine_fold(x: f32) -> f32 {
+    (x * FRAC_PI_2).sin()
+}
+
+/// The number of frames handed to the [`Oversampler`] at a time, such
+/// that the dry copy fits on the stack.
+const BLOCK_FRAMES: usize = 64;
+
+/// Applies the transfer function to every sample, at whichever rate the
+/// [`Oversampler`] runs it.
+struct Shaper {
+    /// The transfer function applied to each sample.
+    shape: Box<dyn Fn(f32) -> f32 + Send>,
+    /// The gain applied before the shaper.
+    drive: f32,
+}
+
+impl fmt::Debug for Shaper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Shaper")
+            .field("drive", &self.drive)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Effect for Shaper {
+    type Parameters = f32;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = (self.shape)(self.drive * *sample);
+        }
+    }
+
+    fn reset(&mut self) {}
+
+    fn set_parameters(&mut self, drive: f32) {
+        self.drive = drive;
+    }
+}
+
+/// The waveshaper DSP and its internal state.
+#[derive(Debug)]
+pub struct Waveshaper {
+    /// The parameters for the effect.
+    parameters: Option<WaveshaperParameters>,
+    /// Runs the transfer function at the oversampled rate.
+    oversampler: Oversampler<Shaper>,
+    /// Delays the dry signal of each channel to line up with the
+    /// oversampled path.
+    dry: [DelayCompensator; 2],
+}
+
+impl Waveshaper {
+    /// Creates a new [`Waveshaper`] applying the transfer function
+    /// `shape`.
+    pub fn new(shape: impl Fn(f32) -> f32 + Send + 'static) -> Self {
+        let shaper = Shaper {
+            shape: Box::new(shape),
+            drive: 1.0,
+        };
+        let dry = DelayCompensator::new(0);
+        Self {
+            parameters: None,
+            oversampler: Oversampler::new(shaper, 1),
+            dry: [dry.clone(), dry],
+        }
+    }
+
+    /// Creates a new [`Waveshaper`] with a `tanh` soft clipper.
+    pub fn tanh() -> Self {
+        Self::new(|x: f32| x.tanh())
+    }
+
+    /// Creates a new [`Waveshaper`] with a `tanh` soft clipper read from
+    /// a [`FastTanh`] table, trading exactness for speed.
+    pub fn fast_tanh() -> Self {
+        let table = FastTanh::new();
+        Self::new(move |x: f32| table.tanh(x))
+    }
+
+    /// Creates a new [`Waveshaper`] clipping with [`hard_clip`].
+    pub fn hard_clip() -> Self {
+        Self::new(hard_clip)
+    }
+
+    /// Creates a new [`Waveshaper`] folding with [`sine_fold`].
+    pub fn sine_fold() -> Self {
+        Self::new(sine_fold)
+    }
+}
+
+impl Waveshaper {
+    /// Initializes the [`Waveshaper`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: WaveshaperParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`Waveshaper`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for Waveshaper {
+    type Parameters = WaveshaperParameters;
+
+    /// The delay introduced by oversampling, in frames.
+    fn latency_samples(&self) -> usize {
+        match self.parameters {
+            Some(_) => self.oversampler.latency_samples(),
+            None => 0,
+        }
+    }
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let mut dry = [0.0; 2 * BLOCK_FRAMES];
+        for block in buffer.chunks_mut(2 * BLOCK_FRAMES) {
+            let dry = &mut dry[..block.len()];
+            dry.copy_from_slice(block);
+            // The shaper keeps no state over time, so the position is moot.
+            self.oversampler.process(UNKNOWN_POSITION, block);
+            for (wet, dry) in block.chunks_exact_mut(2).zip(dry.chunks_exact(2)) {
+                for ((wet, &dry), delay) in wet.iter_mut().zip(dry.iter()).zip(self.dry.iter_mut())
+                {
+                    *wet = delay.process(dry) * (1.0 - parameters.mix) + *wet * parameters.mix;
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.oversampler.reset();
+        self.dry.iter_mut().for_each(DelayCompensator::reset);
+    }
+
+    /// Replaces the parameters of the effect, reallocating the
+    /// oversampling filters if the factor changes.
+    fn set_parameters(&mut self, parameters: WaveshaperParameters) {
+        self.oversampler.set_factor(parameters.oversample);
+        self.oversampler.reserve(2 * BLOCK_FRAMES);
+        self.oversampler.set_parameters(parameters.drive);
+        let latency = self.oversampler.latency_samples();
+        for dry in self.dry.iter_mut() {
+            if dry.delay() != latency {
+                dry.set_delay(latency);
+            }
+        }
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Waveshaper, WaveshaperParameters};
+    use crate::core::{chain::ParallelChain, effect::Effect};
+
+    #[test]
+    fn identity_is_transparent() {
+        let mut shaper = Waveshaper::new(|x| x);
+        shaper.initialize(WaveshaperParameters::new(1.0, 1.0, 1));
+        let input: Vec<f32> = (0..2048).map(|index| (index as f32 * 0.05).sin()).collect();
+        let mut buffer = input.clone();
+        shaper.process(0, &mut buffer);
+        assert_eq!(buffer, input);
+    }
+
+    #[test]
+    fn prebuilt_shapers_are_bounded() {
+        for mut shaper in [
+            Waveshaper::tanh(),
+            Waveshaper::fast_tanh(),
+            Waveshaper::hard_clip(),
+            Waveshaper::sine_fold(),
+        ] {
+            shaper.initialize(WaveshaperParameters::new(8.0, 1.0, 2));
+            let mut buffer: Vec<f32> = (0..4096).map(|index| (index as f32 * 0.01).sin()).collect();
+            shaper.process(0, &mut buffer);
+            assert!(buffer.iter().all(|sample| sample.abs() < 1.1));
+            assert!(shaper.latency_samples() > 0);
+        }
+    }
+
+    #[test]
+    fn dry_lines_up_with_oversampled_path() {
+        let mut shaper = Waveshaper::tanh();
+        shaper.initialize(WaveshaperParameters::new(4.0, 0.0, 4));
+        let latency = shaper.latency_samples();
+        assert!(latency > 0);
+        let input: Vec<f32> = (0..2 * 1000)
+            .map(|index| (index as f32 * 0.05).sin())
+            .collect();
+        let mut buffer = input.clone();
+        for block in buffer.chunks_mut(2 * 100) {
+            shaper.process(0, block);
+        }
+        assert!(buffer[..2 * latency].iter().all(|&sample| sample == 0.0));
+        assert_eq!(buffer[2 * latency..], input[..input.len() - 2 * latency]);
+    }
+
+    #[test]
+    fn shapers_run_in_parallel_chains() {
+        let mut shaper = Waveshaper::hard_clip();
+        shaper.initialize(WaveshaperParameters::new(2.0, 1.0, 2));
+        let mut chain = ParallelChain::new();
+        chain.push(Box::new(shaper));
+        let mut buffer = vec![1.0; 2 * 256];
+        chain.process(0, &mut buffer);
+        assert!(buffer.iter().all(|sample| sample.abs() < 1.1));
+    }
+}
@@ -0,0 +1,175 @@
+//! Narrows, widens, and pans the stereo image.
+use core::f32::consts::{FRAC_PI_4, SQRT_2};
+
+use super::Effect;
+use crate::core::dsp::ms;
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The widest image accepted by [`WidthParameters`].
+pub const MAX_WIDTH: f32 = 2.0;
+
+/// Computes the gains of the left and right channels for a `pan` in
+/// `-1.0..=1.0` following a constant-power law, normalized to unity at
+/// the center.
+pub fn pan_gains(pan: f32) -> (f32, f32) {
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+    (angle.cos() * SQRT_2, angle.sin() * SQRT_2)
+}
+
+/// The parameters consumed by [`StereoWidth`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WidthParameters {
+    /// The scale of the side signal, clamped to `0.0..=MAX_WIDTH`, where
+    /// `0.0` collapses to mono and `1.0` leaves the image unchanged.
+    pub width: f32,
+    /// The balance between the channels, clamped to `-1.0..=1.0` from
+    /// hard left to hard right.
+    pub pan: f32,
+}
+
+impl WidthParameters {
+    /// Creates a new [`WidthParameters`].
+    ///
+    /// # Example
+    ///
+    /// If you want a slightly wider image leaning to the right:
+    ///
+    /// ```rust
+    /// # use photon::core::effect::width::*;
+    /// let _ = WidthParameters::new(1.5, 0.25);
+    /// ```
+    pub fn new(width: f32, pan: f32) -> Self {
+        Self {
+            width: width.clamp(0.0, MAX_WIDTH),
+            pan: pan.clamp(-1.0, 1.0),
+        }
+    }
+
+    /// Compute the gains of the left and right channels for the `pan`.
+    pub fn pan_gains(&self) -> (f32, f32) {
+        pan_gains(self.pan)
+    }
+}
+
+impl Default for WidthParameters {
+    fn default() -> Self {
+        Self::new(1.0, 0.0)
+    }
+}
+
+/// The stereo width DSP.
+#[derive(Debug)]
+pub struct StereoWidth {
+    /// The parameters for the effect.
+    parameters: Option<WidthParameters>,
+}
+
+impl StereoWidth {
+    pub fn new() -> Self {
+        Self { parameters: None }
+    }
+}
+
+impl Default for StereoWidth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StereoWidth {
+    /// Initializes the [`StereoWidth`] i.e. turning it on
+    pub fn initialize(&mut self, parameters: WidthParameters) {
+        self.set_parameters(parameters);
+        self.reset();
+    }
+
+    /// Deinitializes the [`StereoWidth`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.parameters = None;
+        self.reset();
+    }
+}
+
+impl Effect for StereoWidth {
+    type Parameters = WidthParameters;
+
+    fn process(&mut self, _: usize, buffer: &mut [f32]) {
+        let parameters = match self.parameters {
+            Some(parameters) => parameters,
+            None => return,
+        };
+        let width = parameters.width.clamp(0.0, MAX_WIDTH);
+        let (left, right) = parameters.pan_gains();
+        ms::encode(buffer);
+        for frame in buffer.chunks_exact_mut(2) {
+            frame[1] *= width;
+        }
+        ms::decode(buffer);
+        for frame in buffer.chunks_exact_mut(2) {
+            frame[0] *= left;
+            frame[1] *= right;
+        }
+    }
+
+    fn reset(&mut self) {}
+
+    fn set_parameters(&mut self, parameters: WidthParameters) {
+        self.parameters = Some(parameters);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StereoWidth, WidthParameters};
+    use crate::core::effect::Effect;
+
+    fn stereo() -> Vec<f32> {
+        (0..512)
+            .flat_map(|index| {
+                let index = index as f32;
+                [(index * 0.1).sin(), (index * 0.23).cos()]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn zero_width_is_mono() {
+        let mut width = StereoWidth::new();
+        width.initialize(WidthParameters::new(0.0, 0.0));
+        let mut buffer = stereo();
+        width.process(0, &mut buffer);
+        assert!(buffer.chunks_exact(2).all(|frame| frame[0] == frame[1]));
+    }
+
+    #[test]
+    fn unity_is_transparent() {
+        let mut width = StereoWidth::new();
+        width.initialize(WidthParameters::default());
+        let input = stereo();
+        let mut buffer = input.clone();
+        width.process(0, &mut buffer);
+        for (x, y) in buffer.iter().zip(input.iter()) {
+            assert!((x - y).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn hard_left_mutes_right() {
+        let mut width = StereoWidth::new();
+        width.initialize(WidthParameters::new(1.0, -1.0));
+        let mut buffer = stereo();
+        width.process(0, &mut buffer);
+        assert!(buffer.chunks_exact(2).all(|frame| frame[1].abs() < 1e-6));
+    }
+
+    #[test]
+    fn pan_is_constant_power() {
+        for step in 0..=20 {
+            let pan = step as f32 / 10.0 - 1.0;
+            let (left, right) = WidthParameters::new(1.0, pan).pan_gains();
+            assert!((left * left + right * right - 2.0).abs() < 1e-5);
+        }
+    }
+}
@@ -12,7 +12,9 @@ use std::sync::Arc;
 
 use rtrb::{Consumer, Producer};
 
-use super::effect::{Retrigger, RetriggerParameters, TranceGate, TranceGateParameters};
+use super::effect::{
+    Retrigger, RetriggerParameters, TranceGate, TranceGateParameters, DEFAULT_SAMPLE_RATE,
+};
 
 /// Messages into the engine.
 #[derive(Debug)]
@@ -112,8 +114,12 @@ impl Engine {
                     repeat_duration,
                     mix_factor,
                 } => {
-                    let parameters =
-                        RetriggerParameters::new(self.index, repeat_duration, mix_factor);
+                    let parameters = RetriggerParameters::new(
+                        self.index,
+                        repeat_duration,
+                        mix_factor,
+                        DEFAULT_SAMPLE_RATE,
+                    );
                     self.retrigger.initialize(parameters);
                 }
                 MessageIntoEngine::RetriggerOff => {
@@ -123,7 +129,8 @@ impl Engine {
                     gate_duration,
                     mix_factor,
                 } => {
-                    let parameters = TranceGateParameters::new(gate_duration, mix_factor);
+                    let parameters =
+                        TranceGateParameters::new(gate_duration, mix_factor, DEFAULT_SAMPLE_RATE);
                     self.trance_gate.initialize(parameters);
                 }
                 MessageIntoEngine::TranceGateOff => {
@@ -0,0 +1,9 @@
+//! Reading and writing audio outside of the engine, e.g. for offline
+//! rendering.
+pub mod convert;
+#[cfg(feature = "std")]
+pub mod wav;
+
+pub use convert::{Dither, Quantizer};
+#[cfg(feature = "std")]
+pub use wav::{read_wav, write_wav, WavData, WavError, WavFormat};
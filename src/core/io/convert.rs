@@ -0,0 +1,167 @@
+//! Converts between normalized floats and fixed-point samples.
+//!
+//! # Overview
+//!
+//! Floats in `-1.0..=1.0` map onto the full range of the integer type,
+//! where values outside of that range are clamped rather than wrapped.
+//! Rounding to the nearest integer leaves an error that follows the
+//! signal, which is heard as distortion in quiet passages; adding
+//! [`Dither`] before rounding turns that error into a steady noise
+//! floor instead.
+#[cfg(not(feature = "std"))]
+use crate::core::math::Float;
+
+/// The scale of a 16-bit sample.
+const I16_SCALE: f32 = 32768.0;
+
+/// The scale of a 24-bit sample.
+const I24_SCALE: f32 = 8388608.0;
+
+/// The smallest value of a 24-bit sample.
+pub const I24_MIN: i32 = -8388608;
+
+/// The largest value of a 24-bit sample.
+pub const I24_MAX: i32 = 8388607;
+
+/// The noise added before quantization.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// Rounds without dither, leaving the error correlated with the
+    /// signal.
+    None,
+    /// Adds uniform noise of one step peak-to-peak, which removes
+    /// distortion but lets the noise level follow the signal.
+    Rectangular,
+    /// Adds triangular noise of two steps peak-to-peak, which keeps the
+    /// noise floor constant and is the recommended choice.
+    #[default]
+    Triangular,
+}
+
+/// Converts a float to a 16-bit sample without dither.
+pub fn f32_to_i16(sample: f32) -> i16 {
+    quantize(sample * I16_SCALE, i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+/// Converts a float to a 24-bit sample held in an `i32`, without
+/// dither.
+pub fn f32_to_i24(sample: f32) -> i32 {
+    quantize(sample * I24_SCALE, I24_MIN as f32, I24_MAX as f32) as i32
+}
+
+/// Converts a 16-bit sample to a float.
+pub fn i16_to_f32(sample: i16) -> f32 {
+    sample as f32 / I16_SCALE
+}
+
+/// Converts a 24-bit sample held in an `i32` to a float.
+pub fn i24_to_f32(sample: i32) -> f32 {
+    sample.clamp(I24_MIN, I24_MAX) as f32 / I24_SCALE
+}
+
+/// Rounds and clamps a scaled sample.
+fn quantize(scaled: f32, min: f32, max: f32) -> f32 {
+    scaled.round().clamp(min, max)
+}
+
+/// Quantizes floats to fixed-point samples with [`Dither`].
+#[derive(Debug, Clone)]
+pub struct Quantizer {
+    /// The noise added before rounding.
+    dither: Dither,
+    /// The state of the noise generator.
+    state: u32,
+}
+
+impl Quantizer {
+    /// Creates a [`Quantizer`] whose noise is generated from `seed`.
+    ///
+    /// # Example
+    ///
+    /// If you want to export to 16-bit with the recommended dither:
+    ///
+    /// ```rust
+    /// # use photon::core::io::convert::*;
+    /// let mut quantizer = Quantizer::new(Dither::default(), 1);
+    /// let _ = quantizer.to_i16(0.25);
+    /// ```
+    pub fn new(dither: Dither, seed: u32) -> Self {
+        Self {
+            dither,
+            // The generator is stuck at zero.
+            state: seed.max(1),
+        }
+    }
+
+    /// Converts a float to a 16-bit sample.
+    pub fn to_i16(&mut self, sample: f32) -> i16 {
+        let scaled = sample.clamp(-1.0, 1.0) * I16_SCALE + self.noise();
+        quantize(scaled, i16::MIN as f32, i16::MAX as f32) as i16
+    }
+
+    /// Converts a float to a 24-bit sample held in an `i32`.
+    pub fn to_i24(&mut self, sample: f32) -> i32 {
+        let scaled = sample.clamp(-1.0, 1.0) * I24_SCALE + self.noise();
+        quantize(scaled, I24_MIN as f32, I24_MAX as f32) as i32
+    }
+
+    /// Generates the dither in steps of the target format.
+    fn noise(&mut self) -> f32 {
+        match self.dither {
+            Dither::None => 0.0,
+            Dither::Rectangular => self.uniform(),
+            Dither::Triangular => self.uniform() + self.uniform(),
+        }
+    }
+
+    /// Generates uniform noise in `-0.5..0.5` with a xorshift.
+    fn uniform(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        (self.state as f64 / u32::MAX as f64 - 0.5) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::TAU;
+
+    use super::{f32_to_i16, f32_to_i24, i16_to_f32, i24_to_f32, Dither, Quantizer};
+
+    #[test]
+    fn out_of_range_is_clamped() {
+        assert_eq!(f32_to_i16(2.0), i16::MAX);
+        assert_eq!(f32_to_i16(-2.0), i16::MIN);
+        assert_eq!(f32_to_i24(1.5), 8388607);
+        assert_eq!(f32_to_i24(-1.5), -8388608);
+        let mut quantizer = Quantizer::new(Dither::Triangular, 7);
+        assert!((0..100).all(|_| quantizer.to_i16(4.0) > i16::MAX - 2));
+        for value in [i16::MIN, -1, 0, 1, i16::MAX] {
+            assert_eq!(f32_to_i16(i16_to_f32(value)), value);
+        }
+        assert_eq!(f32_to_i24(i24_to_f32(-12345)), -12345);
+    }
+
+    /// Measures the third harmonic of the quantization error of a sine
+    /// spanning a couple of steps.
+    fn distortion(dither: Dither) -> f64 {
+        let mut quantizer = Quantizer::new(dither, 12345);
+        let (len, cycles) = (65536, 64.0);
+        let (re, im) = (0..len).fold((0.0, 0.0), |(re, im), index| {
+            let phase = TAU * cycles * index as f64 / len as f64;
+            let x = (2.3 * phase.sin() / 32768.0) as f32;
+            let error = i16_to_f32(quantizer.to_i16(x)) as f64 - x as f64;
+            let harmonic = 3.0 * phase;
+            (re + error * harmonic.cos(), im + error * harmonic.sin())
+        });
+        re.hypot(im) / len as f64
+    }
+
+    #[test]
+    fn triangular_dither_decorrelates_error() {
+        let plain = distortion(Dither::None);
+        let dithered = distortion(Dither::Triangular);
+        assert!(dithered < plain * 0.1, "{} {}", plain, dithered);
+    }
+}
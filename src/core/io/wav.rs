@@ -0,0 +1,359 @@
+//! Reads and writes uncompressed WAV files.
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use super::convert::{f32_to_i16, f32_to_i24, i16_to_f32, i24_to_f32};
+
+/// The format tag of integer PCM samples.
+const FORMAT_PCM: u16 = 1;
+
+/// The format tag of floating point samples.
+const FORMAT_FLOAT: u16 = 3;
+
+/// The format tag deferring to a sub-format in the extension.
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// The sample encodings supported by [`read_wav`] and [`write_wav`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WavFormat {
+    /// 16-bit signed integers.
+    Pcm16,
+    /// 24-bit signed integers, packed into three bytes.
+    Pcm24,
+    /// 32-bit floats.
+    #[default]
+    Float32,
+}
+
+impl WavFormat {
+    /// The size of a single sample in bytes.
+    pub fn bytes_per_sample(&self) -> usize {
+        match self {
+            WavFormat::Pcm16 => 2,
+            WavFormat::Pcm24 => 3,
+            WavFormat::Float32 => 4,
+        }
+    }
+
+    fn format_tag(&self) -> u16 {
+        match self {
+            WavFormat::Pcm16 | WavFormat::Pcm24 => FORMAT_PCM,
+            WavFormat::Float32 => FORMAT_FLOAT,
+        }
+    }
+}
+
+/// The errors produced while reading or writing WAV files.
+#[derive(Debug)]
+pub enum WavError {
+    /// The underlying reader or writer failed.
+    Io(io::Error),
+    /// The file is not a RIFF/WAVE file, or its chunks are malformed.
+    InvalidHeader(&'static str),
+    /// The file uses an encoding other than those in [`WavFormat`].
+    UnsupportedFormat {
+        format_tag: u16,
+        bits_per_sample: u16,
+    },
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WavError::Io(error) => write!(f, "i/o error: {}", error),
+            WavError::InvalidHeader(reason) => write!(f, "invalid header: {}", reason),
+            WavError::UnsupportedFormat {
+                format_tag,
+                bits_per_sample,
+            } => write!(
+                f,
+                "unsupported format: tag {:#06x} with {} bits per sample",
+                format_tag, bits_per_sample
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WavError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WavError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WavError {
+    fn from(error: io::Error) -> Self {
+        WavError::Io(error)
+    }
+}
+
+/// The contents of a WAV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavData {
+    /// The interleaved samples, normalized to `-1.0..=1.0`.
+    pub samples: Vec<f32>,
+    /// The number of audio channels.
+    pub channels: usize,
+    /// The sample rate of the audio.
+    pub sample_rate: usize,
+    /// The encoding of the samples within the file.
+    pub format: WavFormat,
+}
+
+impl WavData {
+    /// The number of frames in the file.
+    pub fn frames(&self) -> usize {
+        match self.channels {
+            0 => 0,
+            channels => self.samples.len() / channels,
+        }
+    }
+
+    /// Splits the interleaved samples into one buffer per channel.
+    pub fn deinterleaved(&self) -> Vec<Vec<f32>> {
+        (0..self.channels)
+            .map(|channel| {
+                self.samples
+                    .iter()
+                    .skip(channel)
+                    .step_by(self.channels)
+                    .copied()
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Reads a WAV file with 16-bit PCM, 24-bit PCM, or 32-bit float
+/// samples.
+pub fn read_wav(path: impl AsRef<Path>) -> Result<WavData, WavError> {
+    read(BufReader::new(File::open(path)?))
+}
+
+/// Writes a WAV file, encoding the samples using the [`WavFormat`] of
+/// the `data`.
+pub fn write_wav(path: impl AsRef<Path>, data: &WavData) -> Result<(), WavError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer, data)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads a WAV file from an arbitrary `reader`.
+pub fn read(mut reader: impl Read) -> Result<WavData, WavError> {
+    let mut header = [0; 12];
+    reader.read_exact(&mut header)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(WavError::InvalidHeader("missing RIFF/WAVE signature"));
+    }
+
+    let mut format = None;
+    loop {
+        let mut chunk = [0; 8];
+        if let Err(error) = reader.read_exact(&mut chunk) {
+            return match error.kind() {
+                io::ErrorKind::UnexpectedEof => Err(WavError::InvalidHeader("missing data chunk")),
+                _ => Err(error.into()),
+            };
+        }
+        let len = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as usize;
+        // Chunks are padded to an even number of bytes.
+        let padded = len + len % 2;
+        match &chunk[0..4] {
+            b"fmt " => {
+                let mut body = vec![0; padded];
+                reader.read_exact(&mut body)?;
+                format = Some(parse_format(&body[..len])?);
+            }
+            b"data" => {
+                let (channels, sample_rate, format) =
+                    format.ok_or(WavError::InvalidHeader("data chunk before fmt chunk"))?;
+                let mut body = vec![0; len];
+                reader.read_exact(&mut body)?;
+                let samples = decode(&body, format);
+                return Ok(WavData {
+                    samples,
+                    channels,
+                    sample_rate,
+                    format,
+                });
+            }
+            _ => {
+                io::copy(&mut (&mut reader).take(padded as u64), &mut io::sink())?;
+            }
+        }
+    }
+}
+
+/// Writes a WAV file to an arbitrary `writer`.
+pub fn write(mut writer: impl Write, data: &WavData) -> Result<(), WavError> {
+    let bytes_per_sample = data.format.bytes_per_sample();
+    let data_len = data.samples.len() * bytes_per_sample;
+    let riff_len = u32::try_from(36 + data_len + data_len % 2)
+        .map_err(|_| WavError::InvalidHeader("data exceeds 4 GiB"))?;
+    let block_align = data.channels * bytes_per_sample;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&riff_len.to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16_u32.to_le_bytes())?;
+    writer.write_all(&data.format.format_tag().to_le_bytes())?;
+    writer.write_all(&(data.channels as u16).to_le_bytes())?;
+    writer.write_all(&(data.sample_rate as u32).to_le_bytes())?;
+    writer.write_all(&((data.sample_rate * block_align) as u32).to_le_bytes())?;
+    writer.write_all(&(block_align as u16).to_le_bytes())?;
+    writer.write_all(&((bytes_per_sample * 8) as u16).to_le_bytes())?;
+
+    writer.write_all(b"data")?;
+    writer.write_all(&(data_len as u32).to_le_bytes())?;
+    for &sample in data.samples.iter() {
+        match data.format {
+            WavFormat::Pcm16 => {
+                writer.write_all(&f32_to_i16(sample).to_le_bytes())?;
+            }
+            WavFormat::Pcm24 => {
+                writer.write_all(&f32_to_i24(sample).to_le_bytes()[..3])?;
+            }
+            WavFormat::Float32 => {
+                writer.write_all(&sample.clamp(-1.0, 1.0).to_le_bytes())?;
+            }
+        }
+    }
+    if data_len % 2 == 1 {
+        writer.write_all(&[0])?;
+    }
+    Ok(())
+}
+
+/// Parses the body of a `fmt ` chunk into the channel count, sample
+/// rate, and sample format.
+fn parse_format(body: &[u8]) -> Result<(usize, usize, WavFormat), WavError> {
+    if body.len() < 16 {
+        return Err(WavError::InvalidHeader("fmt chunk is too short"));
+    }
+    let u16_at = |index: usize| u16::from_le_bytes([body[index], body[index + 1]]);
+    let mut format_tag = u16_at(0);
+    let channels = u16_at(2) as usize;
+    let sample_rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]) as usize;
+    let bits_per_sample = u16_at(14);
+    if format_tag == FORMAT_EXTENSIBLE {
+        if body.len() < 26 {
+            return Err(WavError::InvalidHeader("fmt extension is too short"));
+        }
+        // The sub-format GUID begins with the plain format tag.
+        format_tag = u16_at(24);
+    }
+    if channels == 0 {
+        return Err(WavError::InvalidHeader("zero channels"));
+    }
+    let format = match (format_tag, bits_per_sample) {
+        (FORMAT_PCM, 16) => WavFormat::Pcm16,
+        (FORMAT_PCM, 24) => WavFormat::Pcm24,
+        (FORMAT_FLOAT, 32) => WavFormat::Float32,
+        _ => {
+            return Err(WavError::UnsupportedFormat {
+                format_tag,
+                bits_per_sample,
+            })
+        }
+    };
+    Ok((channels, sample_rate, format))
+}
+
+/// Converts the body of a `data` chunk to normalized samples.
+fn decode(body: &[u8], format: WavFormat) -> Vec<f32> {
+    let chunks = body.chunks_exact(format.bytes_per_sample());
+    match format {
+        WavFormat::Pcm16 => chunks
+            .map(|bytes| i16_to_f32(i16::from_le_bytes([bytes[0], bytes[1]])))
+            .collect(),
+        WavFormat::Pcm24 => chunks
+            .map(|bytes| {
+                // Shift into the top of an i32 to sign-extend.
+                i24_to_f32(i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8)
+            })
+            .collect(),
+        WavFormat::Float32 => chunks
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{read, read_wav, write, write_wav, WavData, WavError, WavFormat};
+
+    fn sine(format: WavFormat) -> WavData {
+        WavData {
+            samples: (0..1001).map(|index| (index as f32 * 0.05).sin()).collect(),
+            channels: 1,
+            sample_rate: 48000,
+            format,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for (format, tolerance) in [
+            (WavFormat::Pcm16, 1.0 / 32768.0),
+            (WavFormat::Pcm24, 1.0 / 8388608.0),
+            (WavFormat::Float32, 0.0),
+        ] {
+            let data = sine(format);
+            let mut bytes = vec![];
+            write(&mut bytes, &data).unwrap();
+            let decoded = read(Cursor::new(bytes)).unwrap();
+            assert_eq!(decoded.channels, data.channels);
+            assert_eq!(decoded.sample_rate, data.sample_rate);
+            assert_eq!(decoded.format, format);
+            for (&x, &y) in decoded.samples.iter().zip(data.samples.iter()) {
+                assert!((x - y).abs() <= tolerance);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_file() {
+        let data = WavData {
+            samples: vec![0.0, 0.5, -0.5, 1.0],
+            channels: 2,
+            ..sine(WavFormat::Float32)
+        };
+        let path = std::env::temp_dir().join(format!("photon-wav-{}.wav", std::process::id()));
+        write_wav(&path, &data).unwrap();
+        let decoded = read_wav(&path);
+        std::fs::remove_file(&path).unwrap();
+        let decoded = decoded.unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(decoded.deinterleaved(), [[0.0, -0.5], [0.5, 1.0]]);
+    }
+
+    #[test]
+    fn rejects_bad_files() {
+        let garbage = read(Cursor::new(b"RIFX\0\0\0\0WAVE".to_vec()));
+        assert!(matches!(garbage, Err(WavError::InvalidHeader(_))));
+
+        let mut bytes = vec![];
+        write(&mut bytes, &sine(WavFormat::Pcm16)).unwrap();
+        // Claim 8-bit samples, which are not supported.
+        bytes[34] = 8;
+        let unsupported = read(Cursor::new(bytes));
+        assert!(matches!(
+            unsupported,
+            Err(WavError::UnsupportedFormat {
+                bits_per_sample: 8,
+                ..
+            })
+        ));
+    }
+}
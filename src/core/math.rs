@@ -0,0 +1,119 @@
+//! Routes the floating point functions missing from `core` through
+//! [`libm`] when building without `std`.
+
+/// The floating point functions used throughout the crate, implemented
+/// with [`libm`].
+///
+/// With `std`, the inherent methods of `f32` and `f64` take priority,
+/// such that this only needs to be imported for `no_std` builds:
+///
+/// ```rust
+/// #[cfg(not(feature = "std"))]
+/// use photon::core::math::Float;
+///
+/// assert_eq!(0.0_f32.sin(), 0.0);
+/// ```
+pub trait Float: Sized {
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn tanh(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn exp(self) -> Self;
+    fn log10(self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn sqrt(self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn fract(self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_float {
+    ($type:ty, $sin:ident, $cos:ident, $tan:ident, $tanh:ident, $atan2:ident, $exp:ident,
+     $log10:ident, $pow:ident, $sqrt:ident, $hypot:ident, $floor:ident, $ceil:ident, $round:ident,
+     $trunc:ident, $fmod:ident, $fabs:ident) => {
+        impl Float for $type {
+            fn sin(self) -> Self {
+                libm::$sin(self)
+            }
+
+            fn cos(self) -> Self {
+                libm::$cos(self)
+            }
+
+            fn tan(self) -> Self {
+                libm::$tan(self)
+            }
+
+            fn tanh(self) -> Self {
+                libm::$tanh(self)
+            }
+
+            fn atan2(self, other: Self) -> Self {
+                libm::$atan2(self, other)
+            }
+
+            fn exp(self) -> Self {
+                libm::$exp(self)
+            }
+
+            fn log10(self) -> Self {
+                libm::$log10(self)
+            }
+
+            fn powf(self, n: Self) -> Self {
+                libm::$pow(self, n)
+            }
+
+            fn powi(self, n: i32) -> Self {
+                libm::$pow(self, n as $type)
+            }
+
+            fn sqrt(self) -> Self {
+                libm::$sqrt(self)
+            }
+
+            fn hypot(self, other: Self) -> Self {
+                libm::$hypot(self, other)
+            }
+
+            fn floor(self) -> Self {
+                libm::$floor(self)
+            }
+
+            fn ceil(self) -> Self {
+                libm::$ceil(self)
+            }
+
+            fn round(self) -> Self {
+                libm::$round(self)
+            }
+
+            fn fract(self) -> Self {
+                self - libm::$trunc(self)
+            }
+
+            fn rem_euclid(self, rhs: Self) -> Self {
+                let r = libm::$fmod(self, rhs);
+                if r < 0.0 {
+                    r + libm::$fabs(rhs)
+                } else {
+                    r
+                }
+            }
+        }
+    };
+}
+
+impl_float!(
+    f32, sinf, cosf, tanf, tanhf, atan2f, expf, log10f, powf, sqrtf, hypotf, floorf, ceilf, roundf,
+    truncf, fmodf, fabsf
+);
+impl_float!(
+    f64, sin, cos, tan, tanh, atan2, exp, log10, pow, sqrt, hypot, floor, ceil, round, trunc, fmod,
+    fabs
+);
@@ -0,0 +1,44 @@
+//! Represents the MIDI events delivered by the host alongside a buffer.
+
+/// A note message within a [`MidiEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiMessage {
+    /// A key was pressed, where a `velocity` of `0` stands for a
+    /// [`MidiMessage::NoteOff`] as per the MIDI specification.
+    NoteOn { note: u8, velocity: u8 },
+    /// A key was released.
+    NoteOff { note: u8 },
+}
+
+/// A MIDI message timestamped within the buffer it arrived with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidiEvent {
+    /// The message itself.
+    pub message: MidiMessage,
+    /// The frame within the buffer at which the message takes effect.
+    pub sample_offset: usize,
+}
+
+impl MidiEvent {
+    /// Creates a new [`MidiEvent`] pressing a `note`.
+    pub fn note_on(sample_offset: usize, note: u8, velocity: u8) -> Self {
+        Self {
+            message: MidiMessage::NoteOn { note, velocity },
+            sample_offset,
+        }
+    }
+
+    /// Creates a new [`MidiEvent`] releasing a `note`.
+    pub fn note_off(sample_offset: usize, note: u8) -> Self {
+        Self {
+            message: MidiMessage::NoteOff { note },
+            sample_offset,
+        }
+    }
+
+    /// Whether the event presses a note, treating a
+    /// [`MidiMessage::NoteOn`] with a `velocity` of `0` as a release.
+    pub fn is_note_on(&self) -> bool {
+        matches!(self.message, MidiMessage::NoteOn { velocity, .. } if velocity > 0)
+    }
+}
@@ -0,0 +1,489 @@
+//! Saves and loads effect chains as JSON.
+//!
+//! # Overview
+//!
+//! A [`Preset`] lists the effects of a chain as a stable type tag along
+//! with its serialized parameters:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "effects": [
+//!     { "type": "delay", "parameters": { "delay_samples": 4410, ... } }
+//!   ]
+//! }
+//! ```
+//!
+//! Loading looks up each tag in a [`Registry`] to construct the effect,
+//! such that applications can register their own effects next to the
+//! built-in ones.
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::core::{
+    chain::Chain,
+    effect::{
+        AutoPan, AutoPanParameters, Bitcrusher, BitcrusherParameters, Chorus, ChorusParameters,
+        Compressor, CompressorParameters, Crossfeed, CrossfeedParameters, DcBlocker,
+        DcBlockerParameters, Delay, DelayParameters, Distortion, DistortionParameters, Effect,
+        EqParameters, Expander, ExpanderParameters, FilteredDelay, FilteredDelayParameters,
+        Flanger, FlangerParameters, Gain, GainParameters, Granular, GranularParameters, Haas,
+        HaasParameters, Limiter, LimiterParameters, NoiseGate, NoiseGateParameters, ParametricEq,
+        Phaser, PhaserParameters, PingPongDelay, PingPongParameters, PitchParameters, PitchShifter,
+        Reverb, ReverbParameters, RingMod, RingModParameters, SpectralGate, SpectralGateParameters,
+        StereoWidth, Tape, TapeParameters, TranceGate, TranceGateParameters, Tremolo,
+        TremoloParameters, Trim, TrimParameters, Vibrato, VibratoParameters, Wavefolder,
+        WavefolderParameters, WidthParameters, DEFAULT_SAMPLE_RATE,
+    },
+};
+
+/// The version of the preset format written by [`Preset::save`].
+pub const PRESET_VERSION: u32 = 1;
+
+/// The errors produced while saving or loading presets.
+#[derive(Debug)]
+pub enum PresetError {
+    /// The underlying file could not be read or written.
+    Io(io::Error),
+    /// The preset or the parameters of an effect are malformed.
+    Json(serde_json::Error),
+    /// The preset was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// The preset names an effect missing from the [`Registry`].
+    UnknownEffect(String),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresetError::Io(error) => write!(f, "i/o error: {}", error),
+            PresetError::Json(error) => write!(f, "malformed preset: {}", error),
+            PresetError::UnsupportedVersion(version) => {
+                write!(f, "unsupported preset version {}", version)
+            }
+            PresetError::UnknownEffect(tag) => write!(f, "unknown effect type `{}`", tag),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PresetError::Io(error) => Some(error),
+            PresetError::Json(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PresetError {
+    fn from(error: io::Error) -> Self {
+        PresetError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for PresetError {
+    fn from(error: serde_json::Error) -> Self {
+        PresetError::Json(error)
+    }
+}
+
+/// A single effect within a [`Preset`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PresetEffect {
+    /// The stable name of the effect, used to look it up when loading.
+    #[serde(rename = "type")]
+    pub tag: String,
+    /// The serialized parameters of the effect.
+    pub parameters: serde_json::Value,
+}
+
+/// The serialized form of an effect chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preset {
+    /// The version of the format the preset was written in.
+    pub version: u32,
+    /// The effects, in processing order.
+    pub effects: Vec<PresetEffect>,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Preset {
+    pub fn new() -> Self {
+        Self {
+            version: PRESET_VERSION,
+            effects: vec![],
+        }
+    }
+
+    /// Appends an effect to the end of the preset.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use photon::core::{effect::DelayParameters, preset::Preset};
+    /// let mut preset = Preset::new();
+    /// preset.push("delay", &DelayParameters::new(4410, 0.5, 0.3)).unwrap();
+    /// ```
+    pub fn push<P: Serialize>(&mut self, tag: &str, parameters: &P) -> Result<(), PresetError> {
+        self.effects.push(PresetEffect {
+            tag: tag.to_string(),
+            parameters: serde_json::to_value(parameters)?,
+        });
+        Ok(())
+    }
+
+    /// Writes the preset to a JSON file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PresetError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads a preset from a JSON file without constructing its effects.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, PresetError> {
+        let preset: Preset = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if preset.version > PRESET_VERSION {
+            return Err(PresetError::UnsupportedVersion(preset.version));
+        }
+        Ok(preset)
+    }
+
+    /// Reads a preset from a JSON file and constructs its effects with
+    /// the built-in [`Registry`] at the [default sample rate].
+    ///
+    /// [default sample rate]: DEFAULT_SAMPLE_RATE
+    pub fn load(path: impl AsRef<Path>) -> Result<Chain, PresetError> {
+        Self::load_with(path, &Registry::default(), DEFAULT_SAMPLE_RATE)
+    }
+
+    /// Reads a preset from a JSON file and constructs its effects with a
+    /// custom `registry`.
+    pub fn load_with(
+        path: impl AsRef<Path>,
+        registry: &Registry,
+        sample_rate: f64,
+    ) -> Result<Chain, PresetError> {
+        Self::read(path)?.build(registry, sample_rate)
+    }
+
+    /// Constructs the effects of the preset into a [`Chain`].
+    pub fn build(&self, registry: &Registry, sample_rate: f64) -> Result<Chain, PresetError> {
+        let mut chain = Chain::new();
+        for effect in self.effects.iter() {
+            chain.push(registry.construct(effect, sample_rate)?);
+        }
+        Ok(chain)
+    }
+}
+
+/// Constructs an effect from its serialized parameters and the sample
+/// rate.
+type Constructor =
+    Box<dyn Fn(serde_json::Value, f64) -> Result<Box<dyn Effect>, serde_json::Error>>;
+
+/// Maps the type tags of a [`Preset`] onto effect constructors.
+pub struct Registry {
+    constructors: HashMap<String, Constructor>,
+}
+
+impl Registry {
+    /// Creates a [`Registry`] without any effects.
+    pub fn empty() -> Self {
+        Self {
+            constructors: HashMap::new(),
+        }
+    }
+
+    /// Registers an effect under `tag`, replacing any previous effect
+    /// with the same tag.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use photon::core::{effect::*, preset::Registry};
+    /// let mut registry = Registry::empty();
+    /// registry.register("my_delay", |parameters: DelayParameters, _| {
+    ///     let mut delay = Delay::new();
+    ///     delay.initialize(parameters);
+    ///     Box::new(delay)
+    /// });
+    /// ```
+    pub fn register<P, F>(&mut self, tag: &str, construct: F)
+    where
+        P: DeserializeOwned,
+        F: Fn(P, f64) -> Box<dyn Effect> + 'static,
+    {
+        let constructor: Constructor = Box::new(move |value, sample_rate| {
+            Ok(construct(serde_json::from_value(value)?, sample_rate))
+        });
+        self.constructors.insert(tag.to_string(), constructor);
+    }
+
+    /// Whether an effect is registered under `tag`.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.constructors.contains_key(tag)
+    }
+
+    /// Constructs a single effect of a [`Preset`].
+    pub fn construct(
+        &self,
+        effect: &PresetEffect,
+        sample_rate: f64,
+    ) -> Result<Box<dyn Effect>, PresetError> {
+        let constructor = self
+            .constructors
+            .get(&effect.tag)
+            .ok_or_else(|| PresetError::UnknownEffect(effect.tag.clone()))?;
+        Ok(constructor(effect.parameters.clone(), sample_rate)?)
+    }
+}
+
+impl Default for Registry {
+    /// Creates a [`Registry`] with the built-in effects, whose tags are
+    /// part of the preset format and never change.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register("autopan", |parameters: AutoPanParameters, sample_rate| {
+            let mut effect = AutoPan::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("bitcrusher", |parameters: BitcrusherParameters, _| {
+            let mut effect = Bitcrusher::new();
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("chorus", |parameters: ChorusParameters, sample_rate| {
+            let mut effect = Chorus::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register(
+            "compressor",
+            |parameters: CompressorParameters, sample_rate| {
+                let mut effect = Compressor::new(sample_rate);
+                effect.initialize(parameters);
+                Box::new(effect)
+            },
+        );
+        registry.register(
+            "crossfeed",
+            |parameters: CrossfeedParameters, sample_rate| {
+                let mut effect = Crossfeed::new(sample_rate);
+                effect.initialize(parameters);
+                Box::new(effect)
+            },
+        );
+        registry.register("dc_blocker", |parameters: DcBlockerParameters, _| {
+            let mut effect = DcBlocker::new();
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("delay", |parameters: DelayParameters, _| {
+            let mut effect = Delay::new();
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register(
+            "distortion",
+            |parameters: DistortionParameters, sample_rate| {
+                let mut effect = Distortion::new(sample_rate);
+                effect.initialize(parameters);
+                Box::new(effect)
+            },
+        );
+        registry.register("eq", |parameters: EqParameters, sample_rate| {
+            let mut effect = ParametricEq::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("expander", |parameters: ExpanderParameters, sample_rate| {
+            let mut effect = Expander::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register(
+            "filtered_delay",
+            |parameters: FilteredDelayParameters, sample_rate| {
+                let mut effect = FilteredDelay::new(sample_rate);
+                effect.initialize(parameters);
+                Box::new(effect)
+            },
+        );
+        registry.register("flanger", |parameters: FlangerParameters, sample_rate| {
+            let mut effect = Flanger::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("gain", |parameters: GainParameters, sample_rate| {
+            let mut effect = Gain::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("granular", |parameters: GranularParameters, sample_rate| {
+            let mut effect = Granular::new(sample_rate, 1);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("haas", |parameters: HaasParameters, sample_rate| {
+            let mut effect = Haas::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("limiter", |parameters: LimiterParameters, sample_rate| {
+            let mut effect = Limiter::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register(
+            "noise_gate",
+            |parameters: NoiseGateParameters, sample_rate| {
+                let mut effect = NoiseGate::new(sample_rate);
+                effect.initialize(parameters);
+                Box::new(effect)
+            },
+        );
+        registry.register("phaser", |parameters: PhaserParameters, sample_rate| {
+            let mut effect = Phaser::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("pingpong", |parameters: PingPongParameters, _| {
+            let mut effect = PingPongDelay::new();
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("pitch_shift", |parameters: PitchParameters, sample_rate| {
+            let mut effect = PitchShifter::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("reverb", |parameters: ReverbParameters, sample_rate| {
+            let mut effect = Reverb::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("ringmod", |parameters: RingModParameters, sample_rate| {
+            let mut effect = RingMod::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("spectral_gate", |parameters: SpectralGateParameters, _| {
+            let mut effect = SpectralGate::new();
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("tape", |parameters: TapeParameters, sample_rate| {
+            let mut effect = Tape::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register(
+            "trance_gate",
+            |parameters: TranceGateParameters, sample_rate| {
+                let mut effect = TranceGate::new();
+                effect.initialize(parameters.with_sample_rate(sample_rate));
+                Box::new(effect)
+            },
+        );
+        registry.register("tremolo", |parameters: TremoloParameters, sample_rate| {
+            let mut effect = Tremolo::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("trim", |parameters: TrimParameters, sample_rate| {
+            let mut effect = Trim::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("vibrato", |parameters: VibratoParameters, sample_rate| {
+            let mut effect = Vibrato::new(sample_rate);
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("wavefolder", |parameters: WavefolderParameters, _| {
+            let mut effect = Wavefolder::new();
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry.register("width", |parameters: WidthParameters, _| {
+            let mut effect = StereoWidth::new();
+            effect.initialize(parameters);
+            Box::new(effect)
+        });
+        registry
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.constructors.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Preset, PresetError, Registry};
+    use crate::core::{
+        chain::Chain,
+        effect::{Delay, DelayParameters, TranceGate, TranceGateParameters},
+    };
+
+    #[test]
+    fn chain_round_trip() {
+        let gate = TranceGateParameters::new(0.05, 0.9, 44100.0);
+        let delay = DelayParameters::new(441, 0.5, 0.4);
+
+        let mut preset = Preset::new();
+        preset.push("trance_gate", &gate).unwrap();
+        preset.push("delay", &delay).unwrap();
+        let path = std::env::temp_dir().join(format!("photon-preset-{}.json", std::process::id()));
+        preset.save(&path).unwrap();
+        let loaded = Preset::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let mut loaded = loaded.unwrap();
+
+        let mut expected = Chain::new();
+        let mut effect = TranceGate::new();
+        effect.initialize(gate);
+        expected.push(Box::new(effect));
+        let mut effect = Delay::new();
+        effect.initialize(delay);
+        expected.push(Box::new(effect));
+
+        let input: Vec<f32> = (0..8192).map(|index| (index as f32 * 0.03).sin()).collect();
+        let mut buffer = input.clone();
+        loaded.process(0, &mut buffer);
+        let mut expected_buffer = input;
+        expected.process(0, &mut expected_buffer);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(buffer, expected_buffer);
+    }
+
+    #[test]
+    fn unknown_effects_are_reported() {
+        let mut preset = Preset::new();
+        preset
+            .push("delay", &DelayParameters::new(1, 0.0, 0.0))
+            .unwrap();
+        preset.push("time_machine", &0).unwrap();
+        match preset.build(&Registry::default(), 44100.0) {
+            Err(PresetError::UnknownEffect(tag)) => assert_eq!(tag, "time_machine"),
+            other => panic!("expected an unknown effect, got {:?}", other),
+        }
+    }
+}
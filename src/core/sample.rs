@@ -0,0 +1,97 @@
+//! Abstracts over the floating point types that samples are stored in.
+use core::fmt::Debug;
+use core::ops::{Add, Mul, Sub};
+
+#[cfg(feature = "simd")]
+mod simd;
+
+/// A floating point type that audio can be processed in, such as `f64`
+/// for the extra precision of offline rendering.
+///
+/// Parameters stay in `f32` and go through [`from_f32`] when they are
+/// applied to samples.
+///
+/// [`from_f32`]: Self::from_f32
+pub trait Sample:
+    Copy
+    + Debug
+    + Default
+    + PartialEq
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+{
+    /// The additive identity i.e. silence.
+    const ZERO: Self;
+    /// The multiplicative identity i.e. unity gain.
+    const ONE: Self;
+
+    /// Converts an `f32` into a sample.
+    fn from_f32(value: f32) -> Self;
+
+    /// Converts an `f64` into a sample, rounding if needed.
+    fn from_f64(value: f64) -> Self;
+
+    /// Converts the sample into an `f32`, rounding if needed.
+    fn to_f32(self) -> f32;
+
+    /// Converts the sample into an `f64`.
+    fn to_f64(self) -> f64;
+
+    /// Multiplies each sample of the `buffer` by the matching `factors`,
+    /// up to the shorter of the two.
+    fn multiply(buffer: &mut [Self], factors: &[Self]) {
+        for (sample, &factor) in buffer.iter_mut().zip(factors) {
+            *sample = *sample * factor;
+        }
+    }
+}
+
+impl Sample for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    /// Uses SSE on `x86_64` when the `simd` feature is enabled.
+    #[cfg(feature = "simd")]
+    fn multiply(buffer: &mut [Self], factors: &[Self]) {
+        simd::multiply(buffer, factors);
+    }
+}
+
+impl Sample for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
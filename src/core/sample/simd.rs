@@ -0,0 +1,30 @@
+//! Multiplies `f32` buffers several samples at a time.
+
+/// Multiplies each sample of the `buffer` by the matching `factors`.
+#[cfg(target_arch = "x86_64")]
+pub fn multiply(buffer: &mut [f32], factors: &[f32]) {
+    use core::arch::x86_64::{_mm_loadu_ps, _mm_mul_ps, _mm_storeu_ps};
+
+    let len = buffer.len().min(factors.len());
+    let lanes = len / 4 * 4;
+    for index in (0..lanes).step_by(4) {
+        // SAFETY: SSE is part of the x86_64 baseline, and both slices
+        // hold at least `index + 4` samples.
+        unsafe {
+            let x = _mm_loadu_ps(buffer.as_ptr().add(index));
+            let factor = _mm_loadu_ps(factors.as_ptr().add(index));
+            _mm_storeu_ps(buffer.as_mut_ptr().add(index), _mm_mul_ps(x, factor));
+        }
+    }
+    for (sample, factor) in buffer[lanes..len].iter_mut().zip(&factors[lanes..len]) {
+        *sample *= factor;
+    }
+}
+
+/// Multiplies each sample of the `buffer` by the matching `factors`.
+#[cfg(not(target_arch = "x86_64"))]
+pub fn multiply(buffer: &mut [f32], factors: &[f32]) {
+    for (sample, factor) in buffer.iter_mut().zip(factors) {
+        *sample *= factor;
+    }
+}
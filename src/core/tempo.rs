@@ -0,0 +1,71 @@
+//! Converts musical note values into durations.
+
+/// The length of a note relative to a bar of 4/4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteValue {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    Sixteenth,
+    ThirtySecond,
+    DottedHalf,
+    DottedQuarter,
+    DottedEighth,
+    DottedSixteenth,
+    HalfTriplet,
+    QuarterTriplet,
+    EighthTriplet,
+    SixteenthTriplet,
+}
+
+impl NoteValue {
+    /// The length of the note in quarter notes, where dotted notes are
+    /// half again as long and triplets are two thirds as long.
+    pub fn beats(&self) -> f64 {
+        match self {
+            NoteValue::Whole => 4.0,
+            NoteValue::Half => 2.0,
+            NoteValue::Quarter => 1.0,
+            NoteValue::Eighth => 0.5,
+            NoteValue::Sixteenth => 0.25,
+            NoteValue::ThirtySecond => 0.125,
+            NoteValue::DottedHalf => 2.0 * 1.5,
+            NoteValue::DottedQuarter => 1.5,
+            NoteValue::DottedEighth => 0.5 * 1.5,
+            NoteValue::DottedSixteenth => 0.25 * 1.5,
+            NoteValue::HalfTriplet => 2.0 * 2.0 / 3.0,
+            NoteValue::QuarterTriplet => 2.0 / 3.0,
+            NoteValue::EighthTriplet => 0.5 * 2.0 / 3.0,
+            NoteValue::SixteenthTriplet => 0.25 * 2.0 / 3.0,
+        }
+    }
+}
+
+/// Computes the duration of a note in seconds at the given tempo.
+///
+/// # Example
+///
+/// ```rust
+/// # use photon::core::tempo::*;
+/// assert_eq!(note_duration_secs(120.0, NoteValue::Quarter), 0.5);
+/// ```
+pub fn note_duration_secs(bpm: f64, division: NoteValue) -> f64 {
+    60.0 / bpm * division.beats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{note_duration_secs, NoteValue};
+
+    #[test]
+    fn durations_at_120_bpm() {
+        assert_eq!(note_duration_secs(120.0, NoteValue::Quarter), 0.5);
+        assert_eq!(note_duration_secs(120.0, NoteValue::Whole), 2.0);
+        assert_eq!(note_duration_secs(120.0, NoteValue::DottedQuarter), 0.75);
+        let triplet = note_duration_secs(120.0, NoteValue::EighthTriplet);
+        assert!((triplet * 3.0 - note_duration_secs(120.0, NoteValue::Quarter)).abs() < 1e-12);
+        let dotted = note_duration_secs(120.0, NoteValue::DottedEighth);
+        assert!((dotted - note_duration_secs(120.0, NoteValue::Eighth) * 1.5).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,289 @@
+//! Helpers for testing the real-time safety of effects.
+//!
+//! # Overview
+//!
+//! Allocating on the audio thread may block on a lock inside of the
+//! allocator, which is heard as a dropout. [`AuditAllocator`] wraps the
+//! system allocator and counts the allocations made while a
+//! [`NoAllocGuard`] is alive on the same thread, and the guard panics
+//! when it is dropped if there were any. Panicking from within the
+//! allocator itself is undefined behavior, hence the deferral.
+//!
+//! The allocator has to be installed by the test binary:
+//!
+//! ```rust
+//! use photon::core::effect::{Effect, TranceGate, TranceGateParameters};
+//! use photon::core::testing::{AuditAllocator, NoAllocGuard};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: AuditAllocator = AuditAllocator;
+//!
+//! let mut gate = TranceGate::new();
+//! gate.initialize(TranceGateParameters::new(0.5, 0.8, 44100.0));
+//! let mut buffer = vec![0.0; 1024];
+//!
+//! let guard = NoAllocGuard::new();
+//! gate.process(0, &mut buffer);
+//! drop(guard);
+//! ```
+pub mod property;
+pub mod sweep;
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// Whether the [`AuditAllocator`] has served any allocation, i.e. that
+/// it is installed.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+std::thread_local! {
+    /// The number of guards alive on this thread.
+    static GUARDS: Cell<usize> = const { Cell::new(0) };
+    /// The number of allocations made on this thread while guarded.
+    static VIOLATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A global allocator that forwards to the [`System`] allocator while
+/// counting the allocations made under a [`NoAllocGuard`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuditAllocator;
+
+impl AuditAllocator {
+    /// Counts an allocation, reallocation, or deallocation if this
+    /// thread is guarded.
+    fn audit(&self) {
+        INSTALLED.store(true, Ordering::Relaxed);
+        // The thread locals are unavailable while the thread exits.
+        let _ = GUARDS.try_with(|guards| {
+            if guards.get() > 0 {
+                let _ = VIOLATIONS.try_with(|violations| violations.set(violations.get() + 1));
+            }
+        });
+    }
+}
+
+unsafe impl GlobalAlloc for AuditAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.audit();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        self.audit();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.audit();
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.audit();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Forbids allocating on the current thread for as long as it is alive,
+/// panicking when dropped if anything allocated in the meantime.
+#[derive(Debug)]
+pub struct NoAllocGuard {
+    /// The number of violations on this thread when the guard was made.
+    start: usize,
+}
+
+impl NoAllocGuard {
+    /// Starts guarding the current thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`AuditAllocator`] is not the global allocator, as
+    /// the guard would then pass silently.
+    pub fn new() -> Self {
+        assert!(
+            INSTALLED.load(Ordering::Relaxed),
+            "the AuditAllocator must be installed as the global allocator!"
+        );
+        GUARDS.with(|guards| guards.set(guards.get() + 1));
+        Self {
+            start: VIOLATIONS.with(Cell::get),
+        }
+    }
+
+    /// The number of allocations made since the guard was created.
+    pub fn allocations(&self) -> usize {
+        VIOLATIONS.with(Cell::get) - self.start
+    }
+}
+
+impl Default for NoAllocGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for NoAllocGuard {
+    fn drop(&mut self) {
+        let allocations = self.allocations();
+        GUARDS.with(|guards| guards.set(guards.get() - 1));
+        if allocations > 0 && !std::thread::panicking() {
+            panic!("{} allocations while a NoAllocGuard was alive", allocations);
+        }
+    }
+}
+
+/// Runs `f` under a [`NoAllocGuard`], panicking if it allocates.
+pub fn assert_no_alloc<R>(f: impl FnOnce() -> R) -> R {
+    let guard = NoAllocGuard::new();
+    let result = f();
+    drop(guard);
+    result
+}
+
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: AuditAllocator = AuditAllocator;
+
+#[cfg(test)]
+mod tests {
+    use std::hint::black_box;
+
+    use super::{assert_no_alloc, NoAllocGuard};
+    use crate::core::dsp::Waveform;
+    use crate::core::effect::{haas::Side, *};
+
+    #[test]
+    #[should_panic(expected = "allocations while a NoAllocGuard was alive")]
+    fn allocation_is_caught() {
+        let _guard = NoAllocGuard::new();
+        black_box(vec![0.0_f32; 16]);
+    }
+
+    #[test]
+    fn trance_gate_does_not_allocate() {
+        let mut gate = TranceGate::new();
+        gate.initialize(TranceGateParameters::new(0.01, 0.8, 44100.0));
+        let mut buffer = vec![0.5; 2 * 4096];
+        assert_no_alloc(|| {
+            gate.process(0, &mut buffer);
+            gate.process(UNKNOWN_POSITION, &mut buffer);
+        });
+    }
+
+    /// Boxes an `effect` after setting its `parameters`.
+    fn boxed<E: Effect + 'static>(mut effect: E, parameters: E::Parameters) -> Box<dyn Effect> {
+        effect.set_parameters(parameters);
+        effect.reset();
+        Box::new(effect)
+    }
+
+    #[test]
+    fn effects_do_not_allocate() {
+        let sample_rate = 44100.0;
+        let ir: Vec<f32> = (0..500).map(|index| 0.99_f32.powi(index)).collect();
+        let mut effects = [
+            boxed(
+                AutoPan::new(sample_rate),
+                AutoPanParameters::new(2.0, 1.0, Waveform::Sine),
+            ),
+            boxed(Bitcrusher::new(), BitcrusherParameters::new(8, 4, 1.0)),
+            boxed(
+                Chorus::new(sample_rate),
+                ChorusParameters::new(0.8, 4.0, 3, 0.5),
+            ),
+            boxed(
+                Compressor::new(sample_rate),
+                CompressorParameters::new(-20.0, 4.0, 5.0, 50.0, 6.0),
+            ),
+            boxed(Convolver::new(&ir, 64), ConvolverParameters::new(0.5)),
+            boxed(
+                Crossfeed::new(sample_rate),
+                CrossfeedParameters::new(0.3, 0.3, 700.0),
+            ),
+            boxed(DcBlocker::new(), DcBlockerParameters::new(0.995)),
+            boxed(Delay::new(), DelayParameters::new(441, 0.5, 0.4)),
+            boxed(
+                Distortion::new(sample_rate),
+                DistortionParameters::new(0.8, 0.5, 1.0, 2),
+            ),
+            boxed(
+                Expander::new(sample_rate),
+                ExpanderParameters::new(-40.0, 2.0, 1.0, 50.0, 6.0),
+            ),
+            boxed(
+                FilteredDelay::new(sample_rate),
+                FilteredDelayParameters::new(441, 0.6, 3000.0, 200.0, 0.4),
+            ),
+            boxed(
+                Flanger::new(sample_rate),
+                FlangerParameters::new(0.5, 2.0, 0.5, 0.5),
+            ),
+            boxed(Gain::new(sample_rate), GainParameters::new(-6.0)),
+            boxed(
+                Granular::new(sample_rate, 1),
+                GranularParameters::new(50.0, 20.0, 0.5, 0.5, 0.5),
+            ),
+            boxed(
+                Haas::new(sample_rate),
+                HaasParameters::new(15.0, Side::Right),
+            ),
+            boxed(
+                Limiter::new(sample_rate),
+                LimiterParameters::new(5.0, -1.0, 50.0),
+            ),
+            boxed(
+                NoiseGate::new(sample_rate),
+                NoiseGateParameters::new(-40.0, 1.0, 10.0, 50.0, 3.0),
+            ),
+            boxed(
+                Phaser::new(sample_rate),
+                PhaserParameters::new(0.5, 0.8, 4, 0.5, 0.5),
+            ),
+            boxed(PingPongDelay::new(), PingPongParameters::new(441, 0.5, 0.4)),
+            boxed(
+                PitchShifter::new(sample_rate),
+                PitchParameters::new(7.0, 0.5),
+            ),
+            boxed(
+                Reverb::new(sample_rate),
+                ReverbParameters::new(0.8, 0.5, 1.0, 0.3),
+            ),
+            boxed(
+                RingMod::new(sample_rate),
+                RingModParameters::new(440.0, 0.5),
+            ),
+            boxed(
+                SpectralGate::new(),
+                SpectralGateParameters::new(-40.0, 1024, 4),
+            ),
+            boxed(Stutter::new(), StutterParameters::new(441, 4, 1.0)),
+            boxed(
+                Tape::new(sample_rate),
+                TapeParameters::new(0.5, 1.0, 0.5, 0.5),
+            ),
+            boxed(
+                Tremolo::new(sample_rate),
+                TremoloParameters::new(4.0, 0.8, Waveform::Sine, 0.0),
+            ),
+            boxed(Trim::new(sample_rate), TrimParameters::new(3.0, -3.0)),
+            boxed(Vibrato::new(sample_rate), VibratoParameters::new(5.0, 2.0)),
+            boxed(Wavefolder::new(), WavefolderParameters::new(2.0, 0.1, 1.0)),
+            boxed(
+                Waveshaper::new(|x: f32| x.clamp(-0.5, 0.5)),
+                WaveshaperParameters::new(2.0, 1.0, 1),
+            ),
+            boxed(StereoWidth::new(), WidthParameters::new(1.5, 0.0)),
+        ];
+        let mut buffer: Vec<f32> = (0..2 * 4096)
+            .map(|index| (index as f32 * 0.01).sin())
+            .collect();
+        for (index, effect) in effects.iter_mut().enumerate() {
+            let guard = NoAllocGuard::new();
+            effect.process(0, &mut buffer);
+            assert_eq!(guard.allocations(), 0, "effect {} allocated", index);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Strategies and invariants for property-based tests of effects.
+//!
+//! # Overview
+//!
+//! The strategies generate inputs with [`proptest`], while the checks
+//! return a [`TestCaseError`] such that they compose with `?` inside of
+//! a `proptest!` block:
+//!
+//! ```rust
+//! use photon::core::effect::TranceGate;
+//! use photon::core::testing::property::*;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     fn gate_is_finite(
+//!         input in stereo_buffer(512),
+//!         parameters in trance_gate_parameters(44100.0),
+//!     ) {
+//!         let mut gate = TranceGate::new();
+//!         gate.initialize(parameters);
+//!         let mut buffer = input;
+//!         gate.process(0, &mut buffer);
+//!         check_finite(&buffer)?;
+//!     }
+//! }
+//! # gate_is_finite();
+//! ```
+use proptest::{prelude::*, test_runner::TestCaseError};
+
+use crate::core::effect::{trance_gate::GateShape, DelayParameters, Effect, TranceGateParameters};
+
+/// Generates interleaved stereo buffers of up to `max_frames` frames,
+/// with samples in `-1.0..=1.0`.
+pub fn stereo_buffer(max_frames: usize) -> impl Strategy<Value = Vec<f32>> {
+    prop::collection::vec(-1.0_f32..=1.0, 0..=max_frames * 2).prop_map(|mut buffer| {
+        buffer.truncate(buffer.len() & !1);
+        buffer
+    })
+}
+
+/// Generates valid [`TranceGateParameters`] at the `sample_rate`, with
+/// cycles between 1 ms and 2 s.
+pub fn trance_gate_parameters(sample_rate: f64) -> impl Strategy<Value = TranceGateParameters> {
+    (
+        0.001_f64..2.0,
+        0.0_f32..=1.0,
+        prop_oneof![
+            Just(GateShape::Trapezoid),
+            Just(GateShape::Sine),
+            Just(GateShape::Square),
+        ],
+        0.0_f32..=1.0,
+        0.0_f64..1.0,
+    )
+        .prop_map(move |(duration, mix_factor, shape, floor, offset)| {
+            let parameters = TranceGateParameters::new(duration, mix_factor, sample_rate);
+            TranceGateParameters {
+                shape,
+                floor,
+                stereo_offset: (parameters.gate_length as f64 * offset) as usize,
+                ..parameters
+            }
+        })
+}
+
+/// Generates valid [`DelayParameters`] with delays of up to
+/// `max_delay_samples` frames.
+pub fn delay_parameters(max_delay_samples: usize) -> impl Strategy<Value = DelayParameters> {
+    (1..=max_delay_samples.max(1), 0.0_f32..=0.99, 0.0_f32..=1.0).prop_map(
+        |(delay_samples, feedback, mix)| DelayParameters::new(delay_samples, feedback, mix),
+    )
+}
+
+/// Checks that every sample of the `buffer` is finite.
+pub fn check_finite(buffer: &[f32]) -> Result<(), TestCaseError> {
+    match buffer.iter().position(|sample| !sample.is_finite()) {
+        Some(index) => Err(TestCaseError::fail(format!(
+            "sample {} is {}",
+            index, buffer[index]
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the `effect` passes the `input` through unchanged, e.g.
+/// when its mix is `0.0`.
+pub fn check_transparent(effect: &mut dyn Effect, input: &[f32]) -> Result<(), TestCaseError> {
+    let mut buffer = input.to_vec();
+    effect.process(0, &mut buffer);
+    prop_assert_eq!(buffer, input);
+    Ok(())
+}
+
+/// Checks that processing the `input` in one block yields the same
+/// output as processing it in two blocks split at `split_frame`, with
+/// fresh effects from `make`.
+///
+/// Both blocks are passed their absolute positions, as a host tracking
+/// the playhead would.
+pub fn check_split_invariant<E: Effect>(
+    make: impl Fn() -> E,
+    input: &[f32],
+    split_frame: usize,
+) -> Result<(), TestCaseError> {
+    let split_frame = split_frame.min(input.len() / 2);
+    let mut whole = input.to_vec();
+    make().process(0, &mut whole);
+
+    let mut split = input.to_vec();
+    let mut effect = make();
+    let (first, second) = split.split_at_mut(split_frame * 2);
+    effect.process(0, first);
+    effect.process(split_frame, second);
+
+    prop_assert_eq!(whole, split, "split at frame {}", split_frame);
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! Measures the frequency response of an effect with a sine sweep.
+//!
+//! # Overview
+//!
+//! A logarithmic sweep spends equal time in every octave, such that
+//! its spectrum is smooth and covers the whole audible range. Dividing
+//! the spectrum of the processed sweep by that of the original one
+//! deconvolves the transfer function of the effect, as long as the
+//! effect is linear and time-invariant.
+//!
+//! ```rust
+//! use photon::core::dsp::Biquad;
+//! use photon::core::testing::sweep::*;
+//!
+//! let input = generate_log_sweep(20.0, 20000.0, 1.0, 48000.0);
+//! let mut filter = Biquad::lowpass(1000.0, 0.5_f64.sqrt(), 48000.0);
+//! let output: Vec<f32> = input.iter().map(|&x| filter.process(x)).collect();
+//! let response = measure_magnitude_response(&input, &output, 48000.0);
+//! assert!((magnitude_at(&response, 1000.0) + 3.0).abs() < 0.1);
+//! ```
+use std::f64::consts::{PI, TAU};
+
+use crate::core::dsp::{
+    decibel::gain_to_db,
+    fft::{Complex, Fft},
+};
+
+/// The length of the fades at each end of the sweep, which keep the
+/// abrupt start and stop from spreading across the spectrum.
+const FADE_SECS: f64 = 0.01;
+
+/// The level below the loudest bin of the sweep at which bins are left
+/// out of the measured response, as they lie outside of the sweep.
+const COVERAGE_DB: f32 = -30.0;
+
+/// Generates an exponential sine sweep from `start_hz` to `end_hz`
+/// over `duration_s`, fading in and out at its ends.
+pub fn generate_log_sweep(
+    start_hz: f64,
+    end_hz: f64,
+    duration_s: f64,
+    sample_rate: f64,
+) -> Vec<f32> {
+    let len = (duration_s * sample_rate) as usize;
+    let rate = (end_hz / start_hz).ln();
+    let fade = (FADE_SECS * sample_rate).max(1.0);
+    (0..len)
+        .map(|index| {
+            let t = index as f64 / sample_rate;
+            let phase = TAU * start_hz * duration_s / rate * ((t / duration_s * rate).exp() - 1.0);
+            let edge = index.min(len - 1 - index) as f64;
+            let gain = if edge < fade {
+                0.5 - 0.5 * (PI * edge / fade).cos()
+            } else {
+                1.0
+            };
+            (phase.sin() * gain) as f32
+        })
+        .collect()
+}
+
+/// Measures the magnitude response of the effect that turned `input`
+/// into `output`, as pairs of frequencies in Hz and gains in dB.
+///
+/// There is one pair for each bin of the underlying transform that is
+/// covered by the sweep, in ascending order of frequency. The `output`
+/// may be longer than the `input` to include the tail of the effect,
+/// whose truncation otherwise shows up as ripple.
+pub fn measure_magnitude_response(
+    input: &[f32],
+    output: &[f32],
+    sample_rate: f64,
+) -> Vec<(f32, f32)> {
+    let size = (input.len().max(output.len()) * 2).next_power_of_two();
+    let fft = Fft::new(size);
+    let spectrum = |signal: &[f32]| {
+        let mut bins = vec![Complex::ZERO; size];
+        for (bin, &x) in bins.iter_mut().zip(signal) {
+            bin.re = x;
+        }
+        fft.forward(&mut bins);
+        bins
+    };
+    let (x, y) = (spectrum(input), spectrum(output));
+
+    let energy: Vec<f32> = x[..size / 2]
+        .iter()
+        .map(|x| x.re * x.re + x.im * x.im)
+        .collect();
+    let loudest = energy.iter().fold(0.0_f32, |a, &b| a.max(b));
+    let threshold = loudest * 10.0_f32.powf(COVERAGE_DB / 10.0);
+    (1..size / 2)
+        .filter(|&bin| energy[bin] > threshold)
+        .map(|bin| {
+            let frequency = (bin as f64 * sample_rate / size as f64) as f32;
+            let gain = (y[bin] * x[bin].conj()).norm() / energy[bin];
+            (frequency, gain_to_db(gain))
+        })
+        .collect()
+}
+
+/// Interpolates the gain in dB of a measured `response` at a
+/// `frequency`, clamping to its ends.
+///
+/// # Panics
+///
+/// Panics if the `response` is empty.
+pub fn magnitude_at(response: &[(f32, f32)], frequency: f32) -> f32 {
+    let index = response.partition_point(|&(f, _)| f < frequency);
+    if index == 0 {
+        return response[0].1;
+    }
+    if index == response.len() {
+        return response[index - 1].1;
+    }
+    let ((f0, g0), (f1, g1)) = (response[index - 1], response[index]);
+    g0 + (g1 - g0) * (frequency - f0) / (f1 - f0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use super::{generate_log_sweep, magnitude_at, measure_magnitude_response};
+    use crate::core::dsp::{Biquad, BiquadCoefficients};
+
+    #[test]
+    fn biquads_match_their_design() {
+        let sample_rate = 48000.0;
+        let input = generate_log_sweep(20.0, 20000.0, 2.0, sample_rate);
+        for coefficients in [
+            BiquadCoefficients::lowpass(1000.0, FRAC_1_SQRT_2, sample_rate),
+            BiquadCoefficients::peak(2000.0, 1.0, 1.0, sample_rate),
+            BiquadCoefficients::peak(2000.0, 1.0, -1.0, sample_rate),
+        ] {
+            let mut filter = Biquad::new(coefficients);
+            let mut output: Vec<f32> = input.iter().map(|&x| filter.process(x)).collect();
+            output.extend((0..4800).map(|_| filter.process(0.0)));
+            let response = measure_magnitude_response(&input, &output, sample_rate);
+
+            for step in 0..40 {
+                let frequency = 50.0 * 2.0_f64.powf(step as f64 / 5.0);
+                let expected = 20.0 * coefficients.magnitude(frequency, sample_rate).log10();
+                if expected < -40.0 {
+                    continue;
+                }
+                let measured = magnitude_at(&response, frequency as f32) as f64;
+                assert!(
+                    (measured - expected).abs() < 0.1,
+                    "{} Hz: {} {}",
+                    frequency,
+                    measured,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn boost_is_distinguishable() {
+        let sample_rate = 44100.0;
+        let input = generate_log_sweep(20.0, 20000.0, 1.0, sample_rate);
+        let mut filter = Biquad::peak(1000.0, 2.0, 1.0, sample_rate);
+        let output: Vec<f32> = input.iter().map(|&x| filter.process(x)).collect();
+        let response = measure_magnitude_response(&input, &output, sample_rate);
+        assert!((magnitude_at(&response, 1000.0) - 1.0).abs() < 0.1);
+        assert!(magnitude_at(&response, 100.0).abs() < 0.1);
+    }
+}
@@ -0,0 +1,122 @@
+//! Tracks the playhead of the host, for effects that sync to the tempo.
+
+/// The number of beats in a bar, which is always 4/4.
+pub const BEATS_PER_BAR: f64 = 4.0;
+
+/// The position and tempo of the playhead, as reported by the host at
+/// the start of each buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transport {
+    /// The frame index of the playhead, where `0` is the start of the
+    /// first bar.
+    pub sample_pos: u64,
+    /// The tempo in beats per minute.
+    pub bpm: f64,
+    /// The sample rate that the positions are measured in.
+    pub sample_rate: f64,
+    /// Determines if playback is active.
+    pub playing: bool,
+    /// The frame index that the playhead jumps back to when looping.
+    pub loop_start: u64,
+    /// The frame index at which the playhead jumps back to the
+    /// `loop_start`, where looping is disabled unless it comes after the
+    /// `loop_start`.
+    pub loop_end: u64,
+}
+
+impl Transport {
+    /// Creates a new [`Transport`] stopped at the start of the first bar,
+    /// without looping.
+    pub fn new(bpm: f64, sample_rate: f64) -> Self {
+        Self {
+            sample_pos: 0,
+            bpm,
+            sample_rate,
+            playing: false,
+            loop_start: 0,
+            loop_end: 0,
+        }
+    }
+
+    /// Whether the playhead jumps back to the `loop_start`.
+    pub fn is_looping(&self) -> bool {
+        self.loop_end > self.loop_start
+    }
+
+    /// The length of a beat in frames.
+    pub fn beat_samples(&self) -> f64 {
+        60.0 / self.bpm * self.sample_rate
+    }
+
+    /// The length of a bar in frames.
+    pub fn bar_samples(&self) -> f64 {
+        self.beat_samples() * BEATS_PER_BAR
+    }
+
+    /// The number of frames since the start of the current bar.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use photon::core::transport::*;
+    /// let mut transport = Transport::new(120.0, 48000.0);
+    /// transport.sample_pos = 96000 * 3 + 10;
+    /// assert_eq!(transport.samples_into_bar(), 10);
+    /// ```
+    pub fn samples_into_bar(&self) -> u64 {
+        (self.sample_pos as f64 % self.bar_samples()) as u64
+    }
+
+    /// Moves the playhead forward by `frames` while playing, jumping
+    /// back to the `loop_start` every time it reaches the `loop_end`.
+    pub fn advance(&mut self, frames: u64) {
+        if !self.playing {
+            return;
+        }
+        let sample_pos = self.sample_pos + frames;
+        self.sample_pos = if self.is_looping() && self.sample_pos < self.loop_end {
+            let length = self.loop_end - self.loop_start;
+            match sample_pos.checked_sub(self.loop_end) {
+                Some(overrun) => self.loop_start + overrun % length,
+                None => sample_pos,
+            }
+        } else {
+            sample_pos
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transport;
+
+    #[test]
+    fn advance_wraps_within_loop() {
+        let mut transport = Transport {
+            playing: true,
+            loop_start: 100,
+            loop_end: 200,
+            ..Transport::new(120.0, 48000.0)
+        };
+        transport.advance(150);
+        assert_eq!(transport.sample_pos, 150);
+        transport.advance(60);
+        assert_eq!(transport.sample_pos, 110);
+        transport.advance(250);
+        assert_eq!(transport.sample_pos, 160);
+    }
+
+    #[test]
+    fn advance_holds_while_stopped() {
+        let mut transport = Transport::new(120.0, 48000.0);
+        transport.advance(512);
+        assert_eq!(transport.sample_pos, 0);
+    }
+
+    #[test]
+    fn bars_at_120_bpm() {
+        let transport = Transport::new(120.0, 48000.0);
+        assert_eq!(transport.beat_samples(), 24000.0);
+        assert_eq!(transport.bar_samples(), 96000.0);
+    }
+}
@@ -1 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod core;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,163 @@
+//! Exposes effects to JavaScript, e.g. for running inside of an
+//! `AudioWorkletProcessor`.
+//!
+//! Samples are exchanged through buffers in the Wasm memory, which are
+//! allocated once with [`alloc_samples`] and viewed from JavaScript as a
+//! `Float32Array`:
+//!
+//! ```js
+//! const ptr = wasm.alloc_samples(2 * RENDER_QUANTUM_FRAMES);
+//! const view = new Float32Array(wasm.memory.buffer, ptr, 2 * RENDER_QUANTUM_FRAMES);
+//! ```
+//!
+//! The view has to be recreated if the memory grows, as that detaches
+//! its `ArrayBuffer`.
+use alloc::{boxed::Box, vec};
+use core::{ptr, slice};
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::core::effect::{TranceGate, TranceGateParameters, UNKNOWN_POSITION};
+
+/// The number of frames passed to an `AudioWorkletProcessor` per call.
+pub const RENDER_QUANTUM_FRAMES: usize = 128;
+
+/// Allocates a zeroed buffer of `len` samples in the Wasm memory,
+/// returning a pointer to its start.
+///
+/// The buffer lives until it is passed to [`free_samples`].
+#[wasm_bindgen]
+pub fn alloc_samples(len: usize) -> *mut f32 {
+    Box::into_raw(vec![0.0_f32; len].into_boxed_slice()) as *mut f32
+}
+
+/// Frees a buffer returned by [`alloc_samples`].
+///
+/// # Safety
+///
+/// `ptr` and `len` must come from the same call to [`alloc_samples`],
+/// and the buffer must not be used afterwards.
+#[wasm_bindgen]
+pub unsafe fn free_samples(ptr: *mut f32, len: usize) {
+    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)));
+}
+
+/// A [`TranceGate`] exported to JavaScript.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct WasmTranceGate {
+    gate: TranceGate,
+}
+
+#[wasm_bindgen]
+impl WasmTranceGate {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self {
+            gate: TranceGate::new(),
+        }
+    }
+
+    /// Initializes the [`TranceGate`] i.e. turning it on, see
+    /// [`TranceGateParameters::new`].
+    pub fn initialize(&mut self, gate_duration: f64, mix_factor: f32, sample_rate: f64) {
+        self.gate.initialize(TranceGateParameters::new(
+            gate_duration,
+            mix_factor,
+            sample_rate,
+        ));
+    }
+
+    /// Deinitializes the [`TranceGate`] i.e. turning it off
+    pub fn deinitialize(&mut self) {
+        self.gate.deinitialize();
+    }
+
+    /// Applies the effect in-place to `len` interleaved stereo samples
+    /// starting at `ptr`, without allocating.
+    ///
+    /// An `AudioWorkletProcessor` receives each channel separately, so
+    /// its left and right channels have to be interleaved as `[l0, r0,
+    /// l1, r1, ...]` first, or passed to [`process_planar`] instead.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to `len` samples in the Wasm memory, such as a
+    /// buffer from [`alloc_samples`].
+    ///
+    /// [`process_planar`]: Self::process_planar
+    pub unsafe fn process(&mut self, ptr: *mut f32, len: usize) {
+        let buffer = slice::from_raw_parts_mut(ptr, len);
+        self.gate.process(UNKNOWN_POSITION, buffer);
+    }
+
+    /// Applies the effect in-place to `frames` samples of separate
+    /// `left` and `right` channels, without allocating.
+    ///
+    /// This matches the layout of the channels given to an
+    /// `AudioWorkletProcessor`.
+    ///
+    /// # Safety
+    ///
+    /// `left` and `right` must each point to `frames` samples in the
+    /// Wasm memory, and must not overlap.
+    pub unsafe fn process_planar(&mut self, left: *mut f32, right: *mut f32, frames: usize) {
+        let left = slice::from_raw_parts_mut(left, frames);
+        let right = slice::from_raw_parts_mut(right, frames);
+        self.gate.process_planar(frames, &mut [left, right]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::slice;
+
+    use super::{alloc_samples, free_samples, WasmTranceGate, RENDER_QUANTUM_FRAMES};
+    use crate::core::effect::{TranceGate, TranceGateParameters, UNKNOWN_POSITION};
+
+    #[test]
+    fn render_quanta_match_gate() {
+        let mut expected = TranceGate::new();
+        expected.initialize(TranceGateParameters::new(0.01, 0.9, 48000.0));
+        let mut gate = WasmTranceGate::new();
+        gate.initialize(0.01, 0.9, 48000.0);
+
+        let len = 2 * RENDER_QUANTUM_FRAMES;
+        let ptr = alloc_samples(len);
+        for quantum in 0..10 {
+            let input: Vec<f32> = (0..len)
+                .map(|index| ((quantum * len + index) as f32 * 0.01).sin())
+                .collect();
+            let mut buffer = input.clone();
+            expected.process(UNKNOWN_POSITION, &mut buffer);
+            unsafe {
+                slice::from_raw_parts_mut(ptr, len).copy_from_slice(&input);
+                gate.process(ptr, len);
+                assert_eq!(slice::from_raw_parts(ptr, len), &buffer[..]);
+            }
+        }
+        unsafe { free_samples(ptr, len) };
+    }
+
+    #[test]
+    fn planar_matches_interleaved() {
+        let mut interleaved = WasmTranceGate::new();
+        let mut planar = WasmTranceGate::new();
+        interleaved.initialize(0.01, 0.9, 48000.0);
+        planar.initialize(0.01, 0.9, 48000.0);
+
+        let mut buffer: Vec<f32> = (0..2 * RENDER_QUANTUM_FRAMES)
+            .map(|index| (index as f32 * 0.01).sin())
+            .collect();
+        let mut left: Vec<f32> = buffer.iter().step_by(2).copied().collect();
+        let mut right: Vec<f32> = buffer.iter().skip(1).step_by(2).copied().collect();
+        unsafe {
+            interleaved.process(buffer.as_mut_ptr(), buffer.len());
+            planar.process_planar(left.as_mut_ptr(), right.as_mut_ptr(), RENDER_QUANTUM_FRAMES);
+        }
+        for (frame, (l, r)) in buffer.chunks_exact(2).zip(left.iter().zip(right.iter())) {
+            assert_eq!(frame, [*l, *r]);
+        }
+    }
+}